@@ -1,16 +1,23 @@
 use crate::{
-    matrix::Matrix2D,
+    error::BoardError,
+    hole_set::HoleSet,
+    matrix::{is_content_line, Matrix2D},
+    render::{self, RenderStyle},
     vec2::{Square, Vec2},
+    zobrist::ZobristTable,
 };
-use rand::{prelude::SliceRandom, thread_rng};
+use rand::{prelude::SliceRandom, thread_rng, Rng};
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{hash_map::DefaultHasher, BTreeMap, HashMap, HashSet},
     fmt::{Debug, Display},
+    hash::{Hash, Hasher},
     str::FromStr,
+    sync::Arc,
 };
 
 /// Direction on the board
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Dir {
     Up,
     Down,
@@ -19,6 +26,9 @@ pub enum Dir {
 }
 
 impl Dir {
+    /// All directions
+    pub const ALL: [Dir; 4] = [Dir::Up, Dir::Down, Dir::Left, Dir::Right];
+
     /// Convert direction to corresponding vector
     pub fn to_vec2(self) -> Vec2 {
         match self {
@@ -38,13 +48,61 @@ impl Dir {
             Dir::Right => Dir::Left,
         }
     }
+
+    /// Rotate the direction clockwise by 90 degrees, see [`Board::rotated90`]
+    pub fn rotate90(self) -> Self {
+        match self {
+            Dir::Up => Dir::Right,
+            Dir::Right => Dir::Down,
+            Dir::Down => Dir::Left,
+            Dir::Left => Dir::Up,
+        }
+    }
+
+    /// Mirror the direction left to right, see [`Board::mirrored_h`]
+    pub fn mirror_h(self) -> Self {
+        match self {
+            Dir::Left => Dir::Right,
+            Dir::Right => Dir::Left,
+            dir => dir,
+        }
+    }
+
+    /// Mirror the direction top to bottom, see [`Board::mirrored_v`]
+    pub fn mirror_v(self) -> Self {
+        match self {
+            Dir::Up => Dir::Down,
+            Dir::Down => Dir::Up,
+            dir => dir,
+        }
+    }
+}
+
+impl Display for Dir {
+    /// Write the initial of the direction, e.g. `U` for up
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let initial = match self {
+            Dir::Up => "U",
+            Dir::Down => "D",
+            Dir::Left => "L",
+            Dir::Right => "R",
+        };
+        f.write_str(initial)
+    }
 }
 
+/// Id of a block, 0 is reserved for holes
+pub type BlockId = i16;
+
+/// Value of immovable wall cells in the grid
+pub const WALL: BlockId = -1;
+
 /// Block on board
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub(crate) struct Block {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Block {
     /// Block's id, should be unique
-    id: i8,
+    id: BlockId,
     /// Position of block, which is the top-left cell's position here
     pos: Vec2,
     /// Width & height og this block
@@ -52,122 +110,677 @@ pub(crate) struct Block {
 }
 
 impl Block {
-    /// Build block from positions, note that positions must be sorted in row majoring order
-    pub fn from_positions(id: i8, positions: &[Vec2]) -> Result<Self, String> {
-        match positions.len() {
-            1 => Ok(Block {
-                id,
-                pos: positions[0],
-                size: Vec2::new(1, 1),
-            }),
-            2 => {
-                let pos = positions[0];
-                let size = if positions[1] == &pos + &Vec2::new(1, 0) {
-                    Vec2::new(2, 1)
-                } else if positions[1] == &pos + &Vec2::new(0, 1) {
-                    Vec2::new(1, 2)
-                } else {
-                    return Err("Positions cannot form a block".to_string());
-                };
+    /// Get the block's id
+    pub fn id(&self) -> BlockId {
+        self.id
+    }
+
+    /// Get the position of the block's top-left cell
+    pub fn pos(&self) -> Vec2 {
+        self.pos
+    }
+
+    /// Get the block's width & height
+    pub fn size(&self) -> Vec2 {
+        self.size
+    }
+
+    /// Get positions of cells covered by the block in row-major order
+    pub fn cells(&self) -> impl Iterator<Item = Vec2> {
+        let Block { pos, size, .. } = *self;
+        (0..size.y).flat_map(move |dy| (0..size.x).map(move |dx| Vec2::new(pos.x + dx, pos.y + dy)))
+    }
+
+    /// Build block from positions, which must fill a rectangle without gaps
+    pub fn from_positions(id: BlockId, positions: &[Vec2]) -> Result<Self, BoardError> {
+        let first = positions
+            .first()
+            .ok_or(BoardError::InvalidBlockShape { id })?;
+        let (mut min, mut max) = (*first, *first);
+        for pos in positions {
+            min = Vec2::new(std::cmp::min(min.x, pos.x), std::cmp::min(min.y, pos.y));
+            max = Vec2::new(std::cmp::max(max.x, pos.x), std::cmp::max(max.y, pos.y));
+        }
+        let size = max - min + Vec2::new(1, 1);
+        let cells = positions.iter().collect::<HashSet<_>>();
+        let is_filled = cells.len() == positions.len()
+            && cells.len() == size.x as usize * size.y as usize
+            && Square::new(min, size)
+                .row_iter()
+                .all(|pos| cells.contains(&pos));
+        if !is_filled {
+            return Err(BoardError::InvalidBlockShape { id });
+        }
+
+        Ok(Block { id, pos: min, size })
+    }
+}
+
+/// Result of [`Board::quick_solvability`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Solvability {
+    /// The board is already at its goal
+    Solved,
+    /// The board can never reach its goal
+    Unsolvable,
+    /// Cheap checks cannot tell, a search is needed
+    Unknown,
+}
+
+/// Represente a move of a board
+pub type Move = (BlockId, Dir);
+
+/// Format a move as the block id followed by the direction, e.g. `7L`
+pub fn format_move((id, dir): Move) -> String {
+    format!("{}{}", id, dir)
+}
+
+/// A block whose position differs between two states, see [`BoardState::diff`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlockDiff {
+    pub id: BlockId,
+    /// Position in the first state
+    pub from: Vec2,
+    /// Position in the other state
+    pub to: Vec2,
+}
+
+/// Result of [`Board::verify_solution`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// Number of moves replayed
+    pub move_count: usize,
+    /// Whether the board is at its goal after all moves
+    pub reached_goal: bool,
+}
+
+/// Error of [`Board::verify_solution`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyError {
+    /// Index of the first invalid move
+    pub index: usize,
+    pub reason: BoardError,
+}
+
+impl Display for VerifyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid move {}: {}", self.index, self.reason)
+    }
+}
+
+/// Error of [`Board::apply_moves`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApplyError {
+    /// Index of the first move which cannot be applied
+    pub index: usize,
+    /// Why the move cannot be applied
+    pub reason: BoardError,
+}
+
+impl Display for ApplyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Cannot apply move {}: {}", self.index, self.reason)
+    }
+}
+
+impl From<ApplyError> for String {
+    fn from(e: ApplyError) -> Self {
+        e.to_string()
+    }
+}
 
-                Ok(Block { id, pos, size })
+/// Error of [`Board::generate_with_rng`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GenerateError {
+    /// The board has no cells
+    InvalidSize(Vec2),
+    /// No block size is given to choose from
+    NoBlockSizes,
+    /// The requested number of blocks cannot fit into the board
+    TooManyBlocks(BlockId),
+    /// Shuffling cannot move the board away from its goal, even after trying
+    /// [`Board::MAX_GENERATE_ATTEMPTS`] layouts
+    StuckAtGoal,
+    /// The generated blocks cannot form a valid board, e.g. they cannot be packed into a goal
+    Invalid(BoardError),
+}
+
+impl Display for GenerateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GenerateError::InvalidSize(size) => write!(f, "Invalid board size {}", size),
+            GenerateError::NoBlockSizes => write!(f, "No block size to generate"),
+            GenerateError::TooManyBlocks(count) => {
+                write!(f, "Cannot fit {} blocks into board", count)
             }
-            4 => {
-                let pos = positions[0];
-                let deltas = vec![Vec2::new(1, 0), Vec2::new(0, 1), Vec2::new(1, 1)];
+            GenerateError::StuckAtGoal => write!(f, "Cannot shuffle board away from its goal"),
+            GenerateError::Invalid(reason) => write!(f, "Invalid generated board: {}", reason),
+        }
+    }
+}
 
-                for (i, delta) in deltas.iter().enumerate() {
-                    if positions[i + 1] != &pos + delta {
-                        return Err("Positions cannot form a block".to_string());
-                    }
-                }
+/// How [`Board::generate_with_shapes_and_rng`] shuffles a board, both are enabled by default
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShuffleOptions {
+    /// Never take the inverse of the previous move unless it is the only valid move
+    pub forbid_backtrack: bool,
+    /// Keep moving after the last round while the board is at its goal
+    pub forbid_goal: bool,
+}
 
-                Ok(Block {
-                    id,
-                    pos,
-                    size: Vec2::new(2, 2),
-                })
+impl Default for ShuffleOptions {
+    fn default() -> Self {
+        Self {
+            forbid_backtrack: true,
+            forbid_goal: true,
+        }
+    }
+}
+
+impl From<GenerateError> for String {
+    fn from(e: GenerateError) -> Self {
+        e.to_string()
+    }
+}
+
+/// Minimum total cost of assigning each row to a distinct column of a square matrix,
+/// by the Hungarian algorithm
+fn min_cost_assignment(cost: &[Vec<i32>]) -> i32 {
+    let n = cost.len();
+    // Potentials and matching are 1-indexed, column 0 is a virtual one
+    let mut row_potential = vec![0; n + 1];
+    let mut col_potential = vec![0; n + 1];
+    let mut matched_row = vec![0; n + 1];
+    let mut way = vec![0; n + 1];
+    for row in 1..=n {
+        matched_row[0] = row;
+        let mut col = 0;
+        let mut min_slack = vec![i32::MAX; n + 1];
+        let mut used = vec![false; n + 1];
+        loop {
+            used[col] = true;
+            let curr_row = matched_row[col];
+            let mut delta = i32::MAX;
+            let mut next_col = 0;
+            for j in 1..=n {
+                if used[j] {
+                    continue;
+                }
+                let slack = cost[curr_row - 1][j - 1] - row_potential[curr_row] - col_potential[j];
+                if slack < min_slack[j] {
+                    min_slack[j] = slack;
+                    way[j] = col;
+                }
+                if min_slack[j] < delta {
+                    delta = min_slack[j];
+                    next_col = j;
+                }
+            }
+            for j in 0..=n {
+                if used[j] {
+                    row_potential[matched_row[j]] += delta;
+                    col_potential[j] -= delta;
+                } else {
+                    min_slack[j] -= delta;
+                }
             }
-            len => {
-                return Err(format!(
-                    "Invalid position size {}, allowed values are 1, 2, 4",
-                    len
-                ));
+            col = next_col;
+            if matched_row[col] == 0 {
+                break;
             }
         }
+        // Flip the augmenting path
+        while col != 0 {
+            let prev_col = way[col];
+            matched_row[col] = matched_row[prev_col];
+            col = prev_col;
+        }
     }
+
+    (1..=n).map(|col| cost[matched_row[col] - 1][col - 1]).sum()
 }
 
-/// Represente a move of a board
-pub type Move = (i8, Dir);
+/// Parts of a board which never change by moves, shared between clones
+#[derive(Debug, Default, Clone, PartialEq)]
+struct BoardSpec {
+    /// The final state the board want to reach
+    final_state: BoardState,
+    /// Keys to hash the current state
+    zobrist_table: ZobristTable,
+    /// Index of each block in [`BoardState::blocks`] by id, which may skip numbers
+    block_indices: Vec<Option<usize>>,
+}
 
 /// Board of sliding puzzle
-#[derive(Debug, Default, Clone, PartialEq)]
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "BoardData", into = "BoardData")
+)]
 pub struct Board {
     /// Grid to store cells are occupied by which id
-    grid: Matrix2D<i8>,
+    grid: Matrix2D<BlockId>,
     /// Current state of board
     state: BoardState,
-    /// The final state this board want to reach
-    final_state: BoardState,
+    /// Goal and hash keys, cloning a board only bumps the reference count
+    spec: Arc<BoardSpec>,
     _possible_moves: HashSet<Move>,
-    holes: HashSet<Vec2>,
+    holes: HoleSet,
+    /// Zobrist hash of the current state
+    zobrist_hash: u64,
+    /// Manhattan distance to the final state, maintained incrementally by moves
+    heuristic: i32,
+    /// Moves applied by [`Board::push_move`] which have not been undone yet,
+    /// ignored when comparing boards
+    history: Vec<Move>,
+    /// Number of moves applied, see [`Board::move_count`], ignored when comparing boards
+    move_count: usize,
+}
+
+/// Board state with blocks of the same shape treated as interchangeable
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CanonicalState {
+    /// Size of the board
+    size: Vec2,
+    /// Fingerprint of wall cells, see [`BoardState`]
+    walls: u64,
+    /// (size, position) of blocks, sorted by shape then position
+    blocks: Vec<(Vec2, Vec2)>,
+}
+
+/// Block positions of a [`BoardState`] packed into bytes, see [`BoardState::pack`]
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PackedState {
+    /// Size of the board
+    size: Vec2,
+    /// Fingerprint of walls and block shapes, so that states of different boards almost never equal
+    fixed: u64,
+    /// Row-major cell index of each block in id order
+    positions: Box<[u8]>,
 }
 
 /// Board state, store all block data
 #[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BoardState {
     /// Size of the board
     size: Vec2,
-    /// Blocks of this board, should be sorted by id and blank id is not allowed
+    /// Fingerprint of wall cells, so that states of boards with different walls almost never equal
+    walls: u64,
+    /// Blocks of this board, should be sorted by id and blank id is not allowed.
+    /// Ids are positive but not necessarily contiguous
     blocks: Vec<Block>,
 }
 
 impl BoardState {
-    pub(crate) fn new(size: Vec2, blocks: Vec<Block>) -> Self {
-        Self { size, blocks }
+    /// Build a state of blocks placed on `grid`, whose size and walls are recorded
+    pub(crate) fn new(grid: &Matrix2D<BlockId>, blocks: Vec<Block>) -> Self {
+        let size = grid.size();
+        let mut hasher = DefaultHasher::new();
+        for (pos, &id) in grid.iter_positions() {
+            if id == WALL {
+                pos.hash(&mut hasher);
+            }
+        }
+        Self {
+            size,
+            walls: hasher.finish(),
+            blocks,
+        }
+    }
+
+    /// Build a state on the same board with different blocks
+    fn with_blocks(&self, blocks: Vec<Block>) -> Self {
+        Self {
+            size: self.size,
+            walls: self.walls,
+            blocks,
+        }
+    }
+
+    /// Get blocks sorted by id
+    pub fn blocks(&self) -> &[Block] {
+        &self.blocks
+    }
+
+    /// How many bytes a row-major cell index takes in [`PackedState`]
+    fn packed_width(&self) -> usize {
+        match self.size.x as usize * self.size.y as usize {
+            count if count <= 1 << 8 => 1,
+            count if count <= 1 << 16 => 2,
+            _ => 4,
+        }
+    }
+
+    /// Fingerprint of walls and block shapes, which never change by moves
+    fn fixed_fingerprint(&self) -> u64 {
+        self.blocks.iter().fold(self.walls, |hash, block| {
+            let shape = (block.id as u16 as u64) << 32
+                | (block.size.x as u16 as u64) << 16
+                | block.size.y as u16 as u64;
+            (hash.rotate_left(5) ^ shape).wrapping_mul(0x517c_c1b7_2722_0a95)
+        })
+    }
+
+    /// Pack positions of blocks in id order, each as its row-major cell index.
+    /// Walls and block shapes are only kept as a fingerprint since they never change by moves
+    pub fn pack(&self) -> PackedState {
+        let width = self.packed_width();
+        let mut bytes = Vec::with_capacity(self.blocks.len() * width);
+        for block in &self.blocks {
+            let index = block.pos.y as u32 * self.size.x as u32 + block.pos.x as u32;
+            bytes.extend_from_slice(&index.to_be_bytes()[4 - width..]);
+        }
+        PackedState {
+            size: self.size,
+            fixed: self.fixed_fingerprint(),
+            positions: bytes.into_boxed_slice(),
+        }
+    }
+
+    /// Restore a state packed from a state of the same board as this one
+    pub fn unpack(&self, packed: &PackedState) -> BoardState {
+        debug_assert!(
+            packed.size == self.size && packed.fixed == self.fixed_fingerprint(),
+            "Packed state should come from the same board"
+        );
+        let width = self.packed_width();
+        let blocks = self
+            .blocks
+            .iter()
+            .zip(packed.positions.chunks_exact(width))
+            .map(|(block, bytes)| {
+                let index = bytes
+                    .iter()
+                    .fold(0, |index, &byte| index << 8 | byte as u32);
+                let x = (index % self.size.x as u32) as i16;
+                let y = (index / self.size.x as u32) as i16;
+                Block {
+                    pos: Vec2::new(x, y),
+                    ..*block
+                }
+            })
+            .collect();
+        self.with_blocks(blocks)
+    }
+
+    /// Mirror the state left to right, block ids are kept
+    pub fn mirrored(&self, board_size: Vec2) -> BoardState {
+        let blocks = self
+            .blocks
+            .iter()
+            .map(|block| Block {
+                pos: Vec2::new(board_size.x - block.pos.x - block.size.x, block.pos.y),
+                ..*block
+            })
+            .collect();
+        self.with_blocks(blocks)
+    }
+
+    /// Forget block ids, so that states only differ by swapping blocks
+    /// with the same shape share the same canonical state
+    pub fn canonical(&self) -> CanonicalState {
+        let mut blocks = self
+            .blocks
+            .iter()
+            .map(|block| (block.size, block.pos))
+            .collect::<Vec<_>>();
+        blocks.sort_unstable();
+        CanonicalState {
+            size: self.size,
+            walls: self.walls,
+            blocks,
+        }
+    }
+
+    /// Rename blocks by `relabeling`, which is indexed by the position of blocks in id order
+    pub fn relabeled(&self, relabeling: &[BlockId]) -> BoardState {
+        let mut blocks = self
+            .blocks
+            .iter()
+            .zip(relabeling)
+            .map(|(block, &id)| Block { id, ..*block })
+            .collect::<Vec<_>>();
+        blocks.sort_by_key(|block| block.id);
+        self.with_blocks(blocks)
+    }
+
+    /// List blocks which are placed differently in `other`, sorted by id.
+    /// Both states must have the same size and block shapes
+    pub fn diff(&self, other: &BoardState) -> Result<Vec<BlockDiff>, BoardError> {
+        if self.size != other.size {
+            return Err(BoardError::Incompatible(format!(
+                "size {} differs from {}",
+                self.size, other.size
+            )));
+        }
+        if self.blocks.len() != other.blocks.len() {
+            return Err(BoardError::Incompatible(format!(
+                "{} blocks differ from {}",
+                self.blocks.len(),
+                other.blocks.len()
+            )));
+        }
+        let mut diffs = vec![];
+        for (block, other_block) in self.blocks.iter().zip(&other.blocks) {
+            if block.size != other_block.size {
+                return Err(BoardError::Incompatible(format!(
+                    "block {} has size {} and {}",
+                    block.id, block.size, other_block.size
+                )));
+            }
+            if block.pos != other_block.pos {
+                diffs.push(BlockDiff {
+                    id: block.id,
+                    from: block.pos,
+                    to: other_block.pos,
+                });
+            }
+        }
+
+        Ok(diffs)
     }
 }
 
 impl FromStr for Board {
-    type Err = String;
+    type Err = BoardError;
 
+    /// Parse a grid, optionally followed by a blank line and the goal grid,
+    /// which has no size line since it shares the size of the first one
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let id_grid = input.parse::<Matrix2D<i8>>()?;
-        Self::try_from(id_grid)
+        match Self::split_goal(input) {
+            Some((initial, goal)) => Self::parse_with_goal(initial, goal),
+            None => {
+                let id_grid = input.parse::<Matrix2D<BlockId>>()?;
+                Self::try_from(id_grid)
+            }
+        }
     }
 }
 
 impl Board {
-    /// Convert positions to blocks
-    fn parse_blocks(blocks: HashMap<i8, Vec<Vec2>>) -> Result<Vec<Block>, String> {
-        let mut results = vec![];
-        let block_cnt = blocks.len() as i8;
-
-        for id in 1..=block_cnt {
-            let block = match blocks.get(&id) {
-                Some(positions) => Block::from_positions(id, positions)?,
-                None => return Err(format!("Missing block id {}", id)),
+    /// Build a board which should reach `goal` instead of the packed layout.
+    /// Both grids must have the same size, and each id must have the same shape in both
+    pub fn with_goal(
+        initial: Matrix2D<BlockId>,
+        goal: Matrix2D<BlockId>,
+    ) -> Result<Board, BoardError> {
+        if initial.size() != goal.size() {
+            return Err(BoardError::InvalidGoal(format!(
+                "Goal size {} differs from board size {}",
+                goal.size(),
+                initial.size()
+            )));
+        }
+        let is_wall_moved = initial
+            .iter()
+            .zip(goal.iter())
+            .any(|(cell, goal_cell)| (*cell == WALL) != (*goal_cell == WALL));
+        if is_wall_moved {
+            return Err(BoardError::InvalidGoal(
+                "Walls of goal differ from those of board".to_string(),
+            ));
+        }
+        let (goal_blocks, _) = Self::parse_grid(&goal)?;
+        let (blocks, holes) = Self::parse_grid(&initial)?;
+        if goal_blocks.len() != blocks.len() {
+            return Err(BoardError::InvalidGoal(format!(
+                "Goal has {} blocks, but board has {}",
+                goal_blocks.len(),
+                blocks.len()
+            )));
+        }
+        let ids = |blocks: &[Block]| blocks.iter().map(|block| block.id).collect::<Vec<_>>();
+        if ids(&goal_blocks) != ids(&blocks) {
+            return Err(BoardError::InvalidGoal(format!(
+                "Goal has blocks {:?}, but board has {:?}",
+                ids(&goal_blocks),
+                ids(&blocks)
+            )));
+        }
+        for (block, goal_block) in blocks.iter().zip(&goal_blocks) {
+            if block.size != goal_block.size {
+                return Err(BoardError::InvalidGoal(format!(
+                    "Block {} has size {} on board but {} in goal",
+                    block.id, block.size, goal_block.size
+                )));
+            }
+        }
+        let final_state = BoardState::new(&goal, goal_blocks);
+        Ok(Self::new(initial, blocks, holes, final_state))
+    }
+
+    /// Parse the initial grid and the goal grid without size line, see [`Board::with_goal`]
+    pub fn parse_with_goal(initial: &str, goal: &str) -> Result<Board, BoardError> {
+        let initial = initial.parse::<Matrix2D<BlockId>>()?;
+        let size = initial.size();
+        let goal = format!("{} {}\n{}", size.y, size.x, goal);
+        Self::with_goal(initial, goal.parse()?)
+    }
+
+    /// Split input after the rows of the first grid,
+    /// if another grid follows them after a blank or comment line
+    fn split_goal(input: &str) -> Option<(&str, &str)> {
+        let mut offset = 0;
+        let mut rows_left = None;
+        for line in input.split_inclusive('\n') {
+            offset += line.len();
+            if !is_content_line(line) {
+                continue;
+            }
+            let rows = match rows_left {
+                None => line.split_whitespace().next()?.parse::<usize>().ok()?,
+                Some(rows) => rows - 1,
             };
-            results.push(block);
+            rows_left = Some(rows);
+            if rows == 0 {
+                break;
+            }
+        }
+        let rest = &input[offset..];
+        let is_separated = rest
+            .lines()
+            .next()
+            .is_some_and(|line| !is_content_line(line));
+        (rows_left == Some(0) && is_separated && rest.lines().any(is_content_line))
+            .then(|| (&input[..offset], rest))
+    }
+
+    fn new(
+        grid: Matrix2D<BlockId>,
+        blocks: Vec<Block>,
+        holes: HoleSet,
+        final_state: BoardState,
+    ) -> Self {
+        let state = BoardState::new(&grid, blocks);
+        let _possible_moves = Self::generate_possible_moves(&holes, &grid);
+        let zobrist_table = ZobristTable::new(grid.size(), state.blocks.len());
+        let zobrist_hash = state
+            .blocks
+            .iter()
+            .enumerate()
+            .fold(0, |hash, (index, block)| {
+                hash ^ zobrist_table.key(index, block.pos)
+            });
+        let max_id = state.blocks.last().map_or(0, |block| block.id as usize);
+        let mut block_indices = vec![None; max_id + 1];
+        for (index, block) in state.blocks.iter().enumerate() {
+            block_indices[block.id as usize] = Some(index);
         }
 
-        Ok(results)
+        let mut board = Board {
+            grid,
+            state,
+            spec: Arc::new(BoardSpec {
+                final_state,
+                zobrist_table,
+                block_indices,
+            }),
+            _possible_moves,
+            holes,
+            zobrist_hash,
+            heuristic: 0,
+            history: vec![],
+            move_count: 0,
+        };
+        board.heuristic = board.recompute_heuristic();
+        board
     }
 
-    /// Generate the final state from board size & blocks
-    fn generate_final_state(size: Vec2, blocks: &[Block]) -> Result<BoardState, String> {
-        let mut grid = Matrix2D::fill(size, 0);
+    /// Parse blocks sorted by id and holes of a grid
+    fn parse_grid(grid: &Matrix2D<BlockId>) -> Result<(Vec<Block>, HoleSet), BoardError> {
+        let mut blocks = HashMap::new();
+        for (pos, &id) in grid.iter_positions() {
+            if id != WALL && id != 0 {
+                blocks.entry(id).or_insert(vec![]).push(pos);
+            }
+        }
+        let mut holes = HoleSet::new(grid.size());
+        for pos in grid.find_all(&0) {
+            holes.insert(pos);
+        }
+        Ok((Self::parse_blocks(blocks)?, holes))
+    }
+
+    /// Convert positions to blocks sorted by id. Ids must be positive, but may skip numbers
+    fn parse_blocks(blocks: HashMap<BlockId, Vec<Vec2>>) -> Result<Vec<Block>, BoardError> {
+        let mut ids = blocks.keys().copied().collect::<Vec<_>>();
+        ids.sort_unstable();
+        ids.into_iter()
+            .map(|id| {
+                if id <= 0 {
+                    return Err(BoardError::InvalidId(id));
+                }
+                Block::from_positions(id, &blocks[&id])
+            })
+            .collect()
+    }
+
+    /// Generate the final state by packing blocks in row-major order around the walls of `grid`
+    fn generate_final_state(
+        grid: &Matrix2D<BlockId>,
+        blocks: &[Block],
+    ) -> Result<BoardState, BoardError> {
+        let size = grid.size();
+        let mut grid = grid.clone();
+        for cell in grid.iter_mut() {
+            if *cell != WALL {
+                *cell = 0;
+            }
+        }
         let mut next_block_id = 0;
         let mut result_blocks = Vec::with_capacity(blocks.len());
         let mut holes = vec![];
 
-        for pos in Square::at_origin(size).row_iter() {
-            if grid.get(pos).unwrap() == &0 {
+        for pos in grid.positions() {
+            if grid[pos] == 0 {
                 if let Some(block) = blocks.get(next_block_id) {
-                    // TODO: return error instead of assert
-                    assert_eq!(block.id, (next_block_id + 1) as i8);
-                    if grid.try_fill(pos, block.size, block.id).is_ok() {
+                    if grid
+                        .try_fill_without_cover(pos, block.size, block.id)
+                        .is_ok()
+                    {
                         result_blocks.push(Block {
                             id: block.id,
                             pos,
@@ -183,25 +796,27 @@ impl Board {
             }
         }
 
-        if result_blocks.get(next_block_id).is_some() {
-            return Err(format!(
-                "Cannot fit those blocks into board with size {}x{}",
-                size.y, size.x
-            ));
+        if next_block_id < blocks.len() {
+            return Err(BoardError::CannotPack { size });
         }
 
         holes.sort();
-        Ok(BoardState::new(size, result_blocks))
+        Ok(BoardState::new(&grid, result_blocks))
     }
 
-    fn generate_possible_moves(holes: &HashSet<Vec2>, id_grid: &Matrix2D<i8>) -> HashSet<Move> {
-        let moves = Self::dir_and_vecs(&[Dir::Up, Dir::Down, Dir::Left, Dir::Right]);
+    fn generate_possible_moves(holes: &HoleSet, id_grid: &Matrix2D<BlockId>) -> HashSet<Move> {
+        let moves = Self::dir_and_vecs(&Dir::ALL);
         let mut possible_moves = HashSet::new();
 
-        for hole in holes {
+        for hole in holes.iter() {
             for (v, d) in &moves {
-                if let Some(id) = id_grid.get(hole + v) {
-                    if id != &0 {
+                // Skip neighbours whose position cannot be represented
+                let Some(neighbour) = hole.checked_add(*v) else {
+                    continue;
+                };
+                if let Some(id) = id_grid.get(neighbour) {
+                    // Neither holes nor walls can move
+                    if id > &0 {
                         possible_moves.insert((*id, d.inverse()));
                     }
                 }
@@ -211,66 +826,345 @@ impl Board {
         possible_moves
     }
 
-    pub fn move_block(&mut self, id: i8, dir: Dir) -> Result<(), String> {
+    /// Recompute possible moves of blocks next to `changed` cells. A block next to several
+    /// of them is recomputed several times, which is cheaper than collecting unique ids
+    fn update_possible_moves(&mut self, changed: impl Iterator<Item = Vec2>) {
+        for pos in changed {
+            for dir in Dir::ALL {
+                match self.grid.get(pos + dir.to_vec2()) {
+                    Some(&id) if id > 0 => self.update_block_moves(id),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Recompute possible moves of the block
+    fn update_block_moves(&mut self, id: BlockId) {
+        let block = &self.state.blocks[self.block_index(id).expect("Ids on the grid are valid")];
+        for dir in Dir::ALL {
+            let vec = dir.to_vec2();
+            let is_possible = Square::new(block.pos, block.size)
+                .col_iter()
+                .any(|pos| self.grid.get(pos + vec) == Some(&0));
+            if is_possible {
+                self._possible_moves.insert((id, dir));
+            } else {
+                self._possible_moves.remove(&(id, dir));
+            }
+        }
+    }
+
+    pub fn move_block(&mut self, id: BlockId, dir: Dir) -> Result<(), BoardError> {
+        self.shift_block(id, dir)?;
+        self.move_count += 1;
+        Ok(())
+    }
+
+    /// Move a block without counting the move
+    fn shift_block(&mut self, id: BlockId, dir: Dir) -> Result<(), BoardError> {
+        // Check everything before mutating, so that a failed move leaves the board unchanged
         self.is_valid_move((id, dir))?;
-        let block = self
-            .state
-            .blocks
-            .get_mut((id - 1) as usize)
-            .ok_or_else(|| format!("id {} not found", id))?;
-        assert_eq!(id, block.id);
-        self.grid.try_fill(block.pos, block.size, 0)?;
-        self.holes
-            .extend(Square::new(block.pos, block.size).col_iter());
-        block.pos = &block.pos + &dir.to_vec2();
-        self.grid.try_fill(block.pos, block.size, block.id)?;
-        for pos in Square::new(block.pos, block.size).col_iter() {
-            self.holes.remove(&pos);
+        let index = self.block_index(id).expect("Valid moves have valid ids");
+        let block = &self.state.blocks[index];
+        let before = Square::new(block.pos, block.size);
+        if before.col_iter().any(|pos| self.grid.get(pos) != Some(&id)) {
+            return Err(BoardError::Invalid(format!(
+                "Block {} does not match the grid",
+                id
+            )));
+        }
+        let after_pos = block.pos + dir.to_vec2();
+        let after = Square::new(after_pos, block.size);
+
+        for pos in before.col_iter() {
+            // SAFETY: cells of the block are checked to hold its id above
+            unsafe { *self.grid.get_unchecked_mut(pos) = 0 };
+            self.holes.insert(pos);
+        }
+        for pos in after.col_iter() {
+            // SAFETY: `is_valid_move` checked cells after the move are inside the grid
+            unsafe { *self.grid.get_unchecked_mut(pos) = id };
+            self.holes.remove(pos);
+        }
+        self.zobrist_hash ^= self.spec.zobrist_table.key(index, block.pos);
+        self.zobrist_hash ^= self.spec.zobrist_table.key(index, after_pos);
+        // Blocks are in the same order in the final state, so only the moved one changes
+        let target = self.spec.final_state.blocks[index].pos;
+        self.heuristic += after_pos.manhattan(target) - block.pos.manhattan(target);
+        self.state.blocks[index].pos = after_pos;
+        // Only cells which turn into holes or get filled change possible moves
+        let changed = before
+            .col_iter()
+            .filter(|pos| !after.contains(pos))
+            .chain(after.col_iter().filter(|pos| !before.contains(pos)));
+        self.update_possible_moves(changed);
+        debug_assert_eq!(
+            self._possible_moves,
+            Self::generate_possible_moves(&self.holes, &self.grid)
+        );
+        debug_assert_eq!(self.heuristic, self.recompute_heuristic());
+
+        Ok(())
+    }
+
+    /// Move a block in the direction until it hits something, return how many cells it travelled
+    pub fn slide_block(&mut self, id: BlockId, dir: Dir) -> Result<usize, BoardError> {
+        self.move_block(id, dir)?;
+        let mut distance = 1;
+        while self.move_block(id, dir).is_ok() {
+            distance += 1;
         }
-        // FIXME: This might be insufficient
-        self._possible_moves = Self::generate_possible_moves(&self.holes, &self.grid);
+        Ok(distance)
+    }
+
+    /// Move a block like [`Board::move_block`], and record the move so that it can be undone
+    pub fn push_move(&mut self, id: BlockId, dir: Dir) -> Result<(), BoardError> {
+        self.move_block(id, dir)?;
+        self.history.push((id, dir));
+        Ok(())
+    }
 
+    /// Undo the latest move applied by [`Board::push_move`], return `None` if there is none
+    pub fn undo(&mut self) -> Option<Move> {
+        let (id, dir) = self.history.pop()?;
+        self.shift_block(id, dir.inverse())
+            .expect("Inverse of an applied move should be valid");
+        // The move may have been applied before the count was reset
+        self.move_count = self.move_count.saturating_sub(1);
+        Some((id, dir))
+    }
+
+    /// Apply moves in order. If any of them fails, the moves already applied are undone,
+    /// so the board is left unchanged
+    pub fn apply_moves(&mut self, moves: &[Move]) -> Result<(), ApplyError> {
+        for (index, &(id, dir)) in moves.iter().enumerate() {
+            if let Err(reason) = self.push_move(id, dir) {
+                for _ in 0..index {
+                    self.undo();
+                }
+                return Err(ApplyError { index, reason });
+            }
+        }
         Ok(())
     }
 
+    /// Get a copy of the board with the move applied, the board itself is left unchanged
+    pub fn apply(&self, (id, dir): Move) -> Result<Board, BoardError> {
+        let mut next = self.clone();
+        next.move_block(id, dir)?;
+        Ok(next)
+    }
+
+    /// Iterate over boards reachable by one move, each paired with the move.
+    /// Boards are cloned lazily, and invalid moves are skipped
+    pub fn neighbors(&self) -> impl Iterator<Item = (Move, Board)> + '_ {
+        self.possible_moves()
+            .into_iter()
+            .filter_map(move |m| self.apply(m).ok().map(|next| (m, next)))
+    }
+
+    /// Moves applied by [`Board::push_move`] which have not been undone, oldest first
+    pub fn history(&self) -> &[Move] {
+        &self.history
+    }
+
+    /// Number of moves applied by [`Board::move_block`] and others, minus those undone.
+    /// Failed moves are not counted
+    pub fn move_count(&self) -> usize {
+        self.move_count
+    }
+
+    /// Start counting moves from 0 again. Moves applied before can still be undone,
+    /// which keeps the count at 0
+    pub fn reset_move_count(&mut self) {
+        self.move_count = 0;
+    }
+
+    /// Index of block `id` in the blocks of states, `None` if there is no such block
+    fn block_index(&self, id: BlockId) -> Option<usize> {
+        let id = usize::try_from(id).ok()?;
+        *self.spec.block_indices.get(id)?
+    }
+
     /// Check whether a move is valid
-    fn is_valid_move(&self, (id, dir): Move) -> Result<(), String> {
+    fn is_valid_move(&self, (id, dir): Move) -> Result<(), BoardError> {
         let block = self
-            .state
-            .blocks
-            .get((id - 1) as usize)
-            .ok_or_else(|| format!("id {} not found", id))?;
+            .block_index(id)
+            .map(|index| &self.state.blocks[index])
+            .ok_or(BoardError::UnknownBlock { id })?;
         assert_eq!(id, block.id);
         let move_vec = dir.to_vec2();
 
         for before_move in Square::new(block.pos, block.size).col_iter() {
-            let after_move = &before_move + &move_vec;
+            let Some(after_move) = before_move.checked_add(move_vec) else {
+                return Err(BoardError::OutOfRange {
+                    pos: before_move.saturating_add(move_vec),
+                });
+            };
             if let Some(next_id) = self.grid.get(after_move) {
                 if next_id != &0 && next_id != &id {
-                    return Err(format!(
-                        "Invalid move, {} has occupied by {}",
-                        after_move, next_id,
-                    ));
+                    return Err(BoardError::Occupied {
+                        pos: after_move,
+                        by: *next_id,
+                    });
                 }
             } else {
-                return Err("Move out of range".to_string());
+                return Err(BoardError::OutOfRange { pos: after_move });
             }
         }
 
         Ok(())
     }
 
+    /// Replay `moves` on a copy of the board, report whether they reach the goal.
+    /// The board itself is left untouched
+    pub fn verify_solution(&self, moves: &[Move]) -> Result<VerifyReport, VerifyError> {
+        let mut board = self.clone();
+        for (index, &(id, dir)) in moves.iter().enumerate() {
+            board
+                .is_valid_move((id, dir))
+                .map_err(|reason| VerifyError { index, reason })?;
+            board
+                .move_block(id, dir)
+                .expect("Validated move should be applied");
+        }
+        Ok(VerifyReport {
+            move_count: moves.len(),
+            reached_goal: board.is_goal(),
+        })
+    }
+
     fn dir_and_vecs(dirs: &[Dir]) -> Vec<(Vec2, Dir)> {
         dirs.iter().map(|d| (d.to_vec2(), *d)).collect()
     }
 
     pub fn is_goal(&self) -> bool {
-        self.state == self.final_state
-    }
-
-    /// Get possible moves from current state
-    pub fn possible_moves(&self) -> Vec<Move> {
-        self._possible_moves.clone().into_iter().collect::<Vec<_>>()
+        self.state == self.spec.final_state
+    }
+
+    /// Check cheap necessary conditions of solvability without searching.
+    ///
+    /// Moving a block needs as many holes as its side perpendicular to the move,
+    /// so a misplaced block which is larger than the hole count can never move.
+    /// Non-wall cells are flood filled into regions, and a misplaced block is also stuck
+    /// if its goal lies in another region or its region has no hole. Regions of holes alone
+    /// are not checked, since holes split by blocks join again once the blocks move.
+    /// A board on which no block can move at all is also unsolvable unless it is solved.
+    pub fn quick_solvability(&self) -> Solvability {
+        if self.is_goal() {
+            return Solvability::Solved;
+        }
+        let hole_count = self.grid.count(&0);
+        let has_stuck_block = self
+            .state
+            .blocks
+            .iter()
+            .zip(&self.spec.final_state.blocks)
+            .any(|(curr, target)| {
+                curr.pos != target.pos
+                    && std::cmp::min(curr.size.x, curr.size.y) as usize > hole_count
+            });
+        if has_stuck_block {
+            return Solvability::Unsolvable;
+        }
+        // Walls never move, so a block stays in the region of non-wall cells it starts in,
+        // and a region without holes never changes
+        let regions = self.grid.components(|&id| id != WALL);
+        let region_of = |pos: Vec2| regions.iter().position(|region| region.contains(&pos));
+        let has_trapped_block = self
+            .state
+            .blocks
+            .iter()
+            .zip(&self.spec.final_state.blocks)
+            .any(|(curr, target)| {
+                let region = region_of(curr.pos);
+                curr.pos != target.pos
+                    && (region != region_of(target.pos)
+                        || region.is_none_or(|region| {
+                            regions[region].iter().all(|&pos| self.grid[pos] != 0)
+                        }))
+            });
+        if has_trapped_block {
+            return Solvability::Unsolvable;
+        }
+        let can_move = self
+            ._possible_moves
+            .iter()
+            .any(|&m| self.is_valid_move(m).is_ok());
+        if !can_move {
+            return Solvability::Unsolvable;
+        }
+
+        Solvability::Unknown
+    }
+
+    /// Check the permutation parity of boards of single-cell blocks with one hole.
+    ///
+    /// Each move swaps the hole with a neighbouring block, which flips both the parity of
+    /// the permutation taking cells to their goal positions and the parity of the hole's
+    /// manhattan distance to its goal. The board is unsolvable if the two parities differ.
+    /// Moves on other boards do not keep parities, so they are always `Unknown`.
+    pub fn parity_solvability(&self) -> Solvability {
+        if self.is_goal() {
+            return Solvability::Solved;
+        }
+        let unit = Vec2::new(1, 1);
+        if self.hole_count() != 1 || self.state.blocks.iter().any(|block| block.size != unit) {
+            return Solvability::Unknown;
+        }
+        let mut targets: HashMap<Vec2, Vec2> = self
+            .state
+            .blocks
+            .iter()
+            .zip(&self.spec.final_state.blocks)
+            .map(|(curr, target)| (curr.pos, target.pos))
+            .collect();
+        let occupied: HashSet<Vec2> = targets.values().copied().collect();
+        let hole = self.grid.find(&0).expect("Board should have a hole");
+        let goal_hole = self
+            .grid
+            .iter_positions()
+            .map(|(pos, _)| pos)
+            .find(|pos| self.grid[*pos] != WALL && !occupied.contains(pos))
+            .expect("Goal should have a hole");
+        targets.insert(hole, goal_hole);
+
+        // A cycle of length n is made of n - 1 swaps
+        let mut swaps = 0;
+        let mut seen = HashSet::new();
+        for &start in targets.keys() {
+            if seen.contains(&start) {
+                continue;
+            }
+            let mut pos = start;
+            while seen.insert(pos) {
+                pos = targets[&pos];
+                swaps += 1;
+            }
+            swaps -= 1;
+        }
+        let distance = (hole.x - goal_hole.x).abs() + (hole.y - goal_hole.y).abs();
+        if swaps % 2 != distance as usize % 2 {
+            Solvability::Unsolvable
+        } else {
+            Solvability::Unknown
+        }
+    }
+
+    /// Get possible moves from current state
+    pub fn possible_moves(&self) -> Vec<Move> {
+        self.possible_moves_iter().collect()
+    }
+
+    /// Number of possible moves from current state
+    pub fn branching_factor(&self) -> usize {
+        self._possible_moves.len()
+    }
+
+    /// Iterate over possible moves from current state without allocating
+    pub fn possible_moves_iter(&self) -> impl Iterator<Item = Move> + '_ {
+        self._possible_moves.iter().copied()
     }
 
     /// Get a reference to the board's state.
@@ -278,111 +1172,635 @@ impl Board {
         &self.state
     }
 
+    /// List blocks which are placed differently on `other`, see [`BoardState::diff`]
+    pub fn diff(&self, other: &Board) -> Result<Vec<BlockDiff>, BoardError> {
+        self.state.diff(&other.state)
+    }
+
+    /// Get a reference to the state this board want to reach.
+    pub fn final_state(&self) -> &BoardState {
+        &self.spec.final_state
+    }
+
+    /// Get the goal state, same as [`Board::final_state`]
+    pub fn goal(&self) -> &BoardState {
+        &self.spec.final_state
+    }
+
+    /// Make the board reach `goal` instead, the current state is kept.
+    /// The goal must be a state of the same board, where each block keeps its shape
+    pub fn retarget(&mut self, goal: BoardState) -> Result<(), BoardError> {
+        if goal.size != self.state.size {
+            return Err(BoardError::InvalidGoal(format!(
+                "Goal size {} differs from board size {}",
+                goal.size, self.state.size
+            )));
+        }
+        if goal.walls != self.state.walls {
+            return Err(BoardError::InvalidGoal(
+                "Walls of goal differ from those of board".to_string(),
+            ));
+        }
+        if goal.blocks.len() != self.state.blocks.len() {
+            return Err(BoardError::InvalidGoal(format!(
+                "Goal has {} blocks, but board has {}",
+                goal.blocks.len(),
+                self.state.blocks.len()
+            )));
+        }
+        for (block, goal_block) in self.state.blocks.iter().zip(&goal.blocks) {
+            if block.id != goal_block.id {
+                return Err(BoardError::InvalidGoal(format!(
+                    "Goal has block {} where board has block {}",
+                    goal_block.id, block.id
+                )));
+            }
+            if block.size != goal_block.size {
+                return Err(BoardError::InvalidGoal(format!(
+                    "Block {} has size {} on board but {} in goal",
+                    block.id, block.size, goal_block.size
+                )));
+            }
+        }
+        // Blocks of the goal should neither overlap each other nor walls
+        let mut grid = self.grid.clone();
+        for cell in grid.iter_mut() {
+            if *cell != WALL {
+                *cell = 0;
+            }
+        }
+        for block in &goal.blocks {
+            grid.try_fill_without_cover(block.pos, block.size, block.id)
+                .map_err(|e| BoardError::InvalidGoal(e.to_string()))?;
+        }
+
+        Arc::make_mut(&mut self.spec).final_state = goal;
+        self.heuristic = self.recompute_heuristic();
+        Ok(())
+    }
+
+    /// Get the size of the board
+    pub fn size(&self) -> Vec2 {
+        self.grid.size()
+    }
+
+    /// Get blocks sorted by id, same as those of [`Board::state`]
+    pub fn blocks(&self) -> &[Block] {
+        self.state.blocks()
+    }
+
+    /// Get positions of holes in row-major order
+    pub fn holes(&self) -> impl Iterator<Item = Vec2> + '_ {
+        self.holes.iter()
+    }
+
+    /// Get the grid storing which block occupies each cell, 0 for holes
+    pub fn grid(&self) -> &Matrix2D<BlockId> {
+        &self.grid
+    }
+
+    /// Get ids of cells in row-major order, 0 for holes
+    pub fn id_grid(&self) -> &[BlockId] {
+        &self.grid
+    }
+
+    /// Get the number of holes, which never changes by moves
+    pub fn hole_count(&self) -> usize {
+        self.holes.len()
+    }
+
+    /// Count blocks by their size
+    pub fn shape_histogram(&self) -> BTreeMap<Vec2, usize> {
+        let mut histogram = BTreeMap::new();
+        for block in self.blocks() {
+            *histogram.entry(block.size).or_default() += 1;
+        }
+        histogram
+    }
+
+    /// Check whether holes form a single region connected horizontally or vertically
+    pub fn holes_connected(&self) -> bool {
+        self.grid.components(|&id| id == 0).len() <= 1
+    }
+
+    /// Check whether the goal is left-right symmetric, up to swapping blocks with the same shape.
+    /// If so, return the id each block of the mirrored goal should be renamed to, indexed by the position of blocks in id order
+    pub fn mirror_relabeling(&self) -> Option<Vec<BlockId>> {
+        let size = self.grid.size();
+        let has_symmetric_walls = self.grid.positions().all(|pos| {
+            let mirrored = Vec2::new(size.x - 1 - pos.x, pos.y);
+            (self.grid[pos] == WALL) == (self.grid[mirrored] == WALL)
+        });
+        if !has_symmetric_walls {
+            return None;
+        }
+        self.spec
+            .final_state
+            .mirrored(self.grid.size())
+            .blocks
+            .iter()
+            .map(|mirrored| {
+                self.spec
+                    .final_state
+                    .blocks
+                    .iter()
+                    .find(|block| block.pos == mirrored.pos && block.size == mirrored.size)
+                    .map(|block| block.id)
+            })
+            .collect()
+    }
+
+    /// Get the grid of the final state, which has the same walls as the board
+    pub fn goal_grid(&self) -> Matrix2D<BlockId> {
+        let mut grid = self.grid.clone();
+        for cell in grid.iter_mut() {
+            if *cell != WALL {
+                *cell = 0;
+            }
+        }
+        for block in &self.spec.final_state.blocks {
+            grid.try_fill(block.pos, block.size, block.id)
+                .expect("Goal blocks should be inside the grid");
+        }
+        grid
+    }
+
+    /// Relabel blocks in row-major order of their top-left cells, so that boards numbered
+    /// differently share the same text. The goal is relabeled the same way, so it is the
+    /// same layout as before. Also return the mapping from old ids to new ids
+    pub fn normalize_ids(&self) -> (Board, HashMap<BlockId, BlockId>) {
+        let mut blocks = self.blocks().to_vec();
+        blocks.sort_by_key(|block| (block.pos.y, block.pos.x));
+        let mapping = blocks
+            .iter()
+            .enumerate()
+            .map(|(index, block)| (block.id, index as BlockId + 1))
+            .collect::<HashMap<_, _>>();
+        let mut grid = self.grid.clone();
+        for cell in grid.iter_mut() {
+            if *cell > 0 {
+                *cell = mapping[cell];
+            }
+        }
+        let (blocks, holes) = Self::parse_grid(&grid).expect("Relabeled grid should be valid");
+        let relabeling = self
+            .blocks()
+            .iter()
+            .map(|block| mapping[&block.id])
+            .collect::<Vec<_>>();
+        let final_state = self.spec.final_state.relabeled(&relabeling);
+        (Self::new(grid, blocks, holes, final_state), mapping)
+    }
+
+    /// Get the board rotated clockwise by 90 degrees, together with its goal.
+    /// Solutions can be mapped onto it by [`Dir::rotate90`]
+    pub fn rotated90(&self) -> Board {
+        let size = self.grid.size();
+        self.transformed(Vec2::new(size.y, size.x), |pos| {
+            Vec2::new(size.y - 1 - pos.y, pos.x)
+        })
+    }
+
+    /// Get the board mirrored left to right, together with its goal.
+    /// Solutions can be mapped onto it by [`Dir::mirror_h`]
+    pub fn mirrored_h(&self) -> Board {
+        let size = self.grid.size();
+        self.transformed(size, |pos| Vec2::new(size.x - 1 - pos.x, pos.y))
+    }
+
+    /// Get the board mirrored top to bottom, together with its goal.
+    /// Solutions can be mapped onto it by [`Dir::mirror_v`]
+    pub fn mirrored_v(&self) -> Board {
+        let size = self.grid.size();
+        self.transformed(size, |pos| Vec2::new(pos.x, size.y - 1 - pos.y))
+    }
+
+    /// Get the board without border rows and columns which are holes both now and in the goal.
+    /// Positions of blocks and goals are shifted, so solutions of both boards are the same
+    pub fn crop_to_content(&self) -> Board {
+        let goal_grid = self.goal_grid();
+        let content = self
+            .grid
+            .positions()
+            .filter(|&pos| self.grid[pos] != 0 || goal_grid[pos] != 0)
+            .collect::<Vec<_>>();
+        if content.is_empty() {
+            return self.clone();
+        }
+        let min = |axis: fn(&Vec2) -> i16| content.iter().map(axis).min().unwrap();
+        let max = |axis: fn(&Vec2) -> i16| content.iter().map(axis).max().unwrap();
+        let (min_x, min_y) = (min(|pos| pos.x), min(|pos| pos.y));
+        let size = Vec2::new(max(|pos| pos.x) - min_x + 1, max(|pos| pos.y) - min_y + 1);
+        self.transformed(size, |pos| Vec2::new(pos.x - min_x, pos.y - min_y))
+    }
+
+    /// Place the board at `offset` of a larger board of `new_size`, other cells are holes.
+    /// The goal is moved along with the board
+    pub fn embed(&self, new_size: Vec2, offset: Vec2) -> Result<Board, BoardError> {
+        let size = self.grid.size();
+        let corner = offset + size - Vec2::new(1, 1);
+        for pos in [offset, corner] {
+            if pos.x < 0 || pos.y < 0 || pos.x >= new_size.x || pos.y >= new_size.y {
+                return Err(BoardError::OutOfRange { pos });
+            }
+        }
+        Ok(self.transformed(new_size, |pos| pos + offset))
+    }
+
+    /// Move each cell of the grid and the goal grid to `map(pos)` on a board of `size`,
+    /// other cells are holes. Cells mapped outside are dropped, so they should be holes
+    fn transformed(&self, size: Vec2, map: impl Fn(Vec2) -> Vec2) -> Board {
+        let goal_grid = self.goal_grid();
+        let mut grid = Matrix2D::fill(size, 0);
+        let mut goal = Matrix2D::fill(size, 0);
+        for (pos, &id) in self.grid.iter_positions() {
+            let target = map(pos);
+            if let (Some(cell), Some(goal_cell)) = (grid.get_mut(target), goal.get_mut(target)) {
+                *cell = id;
+                *goal_cell = goal_grid[pos];
+            }
+        }
+        Board::with_goal(grid, goal).expect("Transformed board should be valid")
+    }
+
+    /// Same as the [`Display`] output, but prefixed by `comment` as `#` lines
+    pub fn to_string_with_comment(&self, comment: &str) -> String {
+        let mut output = String::new();
+        for line in comment.lines() {
+            output.push_str(&format!("# {}\n", line));
+        }
+        output.push_str(&self.to_string());
+        output
+    }
+
+    /// Same as the [`Display`] output, followed by a blank line and the goal grid,
+    /// which [`Board::from_str`] parses back
+    pub fn to_string_with_goal(&self) -> String {
+        format!("{}\n{}", self, self.goal_grid().to_grid_string())
+    }
+
+    /// Human-readable view of the grid, one bracketed row per line
+    pub fn to_pretty_string(&self) -> String {
+        let size = self.grid.size();
+        let mut output = format!("{}x{}\n", size.y, size.x);
+        for row in self.grid.rows() {
+            output.push_str(&format!("{:?}\n", row));
+        }
+        output
+    }
+
+    /// Draw the grid with aligned cells in given style, see [`RenderStyle`]
+    pub fn render(&self, style: RenderStyle) -> String {
+        render::render(&self.grid, style)
+    }
+
+    /// Get the Zobrist hash of current state, which is maintained incrementally by moves
+    pub fn zobrist_hash(&self) -> u64 {
+        self.zobrist_hash
+    }
+
+    /// Sum of Manhattan distances between blocks and their goal positions,
+    /// which is maintained incrementally by moves
     pub fn heuristic(&self) -> i32 {
+        self.heuristic
+    }
+
+    /// Compute [`Board::heuristic`] from scratch
+    pub fn recompute_heuristic(&self) -> i32 {
         self.state
             .blocks
             .iter()
-            .zip(&self.final_state.blocks)
-            .map(|(curr, target)| {
-                (curr.pos.x - target.pos.x).abs() as i32 + (curr.pos.y - target.pos.y).abs() as i32
+            .zip(&self.spec.final_state.blocks)
+            .map(|(curr, target)| curr.pos.manhattan(target.pos))
+            .sum()
+    }
+
+    /// Like [`Board::heuristic`], but blocks of the same shape are interchangeable.
+    ///
+    /// Each shape class is matched to its goal positions by a minimum-cost assignment,
+    /// so this never exceeds [`Board::heuristic`], which is just one of the assignments.
+    pub fn heuristic_matched(&self) -> i32 {
+        let mut classes: HashMap<Vec2, (Vec<Vec2>, Vec<Vec2>)> = HashMap::new();
+        for (curr, target) in self.state.blocks.iter().zip(&self.spec.final_state.blocks) {
+            let (currs, targets) = classes.entry(curr.size).or_default();
+            currs.push(curr.pos);
+            targets.push(target.pos);
+        }
+
+        classes
+            .into_values()
+            .map(|(currs, targets)| match currs.len() {
+                1 => currs[0].manhattan(targets[0]),
+                _ => {
+                    let cost = currs
+                        .iter()
+                        .map(|&curr| {
+                            targets
+                                .iter()
+                                .map(|&target| curr.manhattan(target))
+                                .collect()
+                        })
+                        .collect::<Vec<_>>();
+                    min_cost_assignment(&cost)
+                }
             })
             .sum()
     }
 
-    /// Randonly generate a valid board
-    pub fn generate(size: Vec2, block_count: i8, shuffle_round: usize) -> Self {
-        let mut next_id = 1;
-        let mut possible_block_sizes = vec![
-            Vec2::new(2, 1),
-            Vec2::new(1, 1),
-            Vec2::new(1, 2),
-            Vec2::new(2, 2),
-        ];
-        let mut grid = Matrix2D::fill(size, 0i8);
-        let mut rng = thread_rng();
+    /// Manhattan distance plus linear conflicts.
+    ///
+    /// Two blocks sharing the same row with both of their goal positions also in that row,
+    /// but in inverted order, cannot pass each other unless one of them leaves the row,
+    /// which costs at least 2 extra moves. Columns are handled in the same way.
+    pub fn heuristic_linear_conflict(&self) -> i32 {
+        let row_conflicts = self.line_conflicts(|pos| pos.y, |pos| pos.x);
+        let col_conflicts = self.line_conflicts(|pos| pos.x, |pos| pos.y);
+        self.heuristic() + 2 * (row_conflicts + col_conflicts)
+    }
+
+    /// Count the minimum number of blocks which have to leave their line to resolve all conflicts
+    fn line_conflicts(&self, line: impl Fn(Vec2) -> i16, order: impl Fn(Vec2) -> i16) -> i32 {
+        let mut lines: HashMap<i16, Vec<(i16, i16)>> = HashMap::new();
+        for (curr, target) in self.state.blocks.iter().zip(&self.spec.final_state.blocks) {
+            if line(curr.pos) == line(target.pos) {
+                lines
+                    .entry(line(curr.pos))
+                    .or_default()
+                    .push((order(curr.pos), order(target.pos)));
+            }
+        }
 
-        for pos in Square::at_origin(size).row_iter() {
-            if grid.get(pos).unwrap() == &0 {
-                possible_block_sizes.shuffle(&mut rng);
-                let id = next_id;
-                next_id += 1;
-                for block_size in &possible_block_sizes {
-                    if grid.try_fill_without_cover(pos, *block_size, id).is_ok() {
-                        break;
+        lines
+            .into_values()
+            .map(|mut blocks| {
+                // Blocks which can stay are those forming the longest increasing subsequence
+                // of goal order, sorted by current order
+                blocks.sort();
+                let mut tails: Vec<i16> = vec![];
+                for (_, target) in &blocks {
+                    match tails.binary_search(target) {
+                        Ok(_) => {}
+                        Err(i) if i == tails.len() => tails.push(*target),
+                        Err(i) => tails[i] = *target,
                     }
                 }
-                if next_id > block_count {
+                (blocks.len() - tails.len()) as i32
+            })
+            .sum()
+    }
+
+    /// Block sizes of [`Board::generate`]
+    pub const DEFAULT_BLOCK_SIZES: [Vec2; 4] = [
+        Vec2::new(2, 1),
+        Vec2::new(1, 1),
+        Vec2::new(1, 2),
+        Vec2::new(2, 2),
+    ];
+
+    /// At most how many moves are made after shuffling to leave the goal
+    pub const MAX_EXTRA_SHUFFLE: usize = 64;
+
+    /// At most how many layouts are tried when they cannot be shuffled away from the goal
+    pub const MAX_GENERATE_ATTEMPTS: usize = 16;
+
+    /// Randonly generate a valid board
+    pub fn generate(size: Vec2, block_count: BlockId, shuffle_round: usize) -> Self {
+        Self::generate_with_rng(size, block_count, shuffle_round, &mut thread_rng())
+            .expect("Invalid generate parameters")
+    }
+
+    /// Randonly generate a valid board by given random number generator,
+    /// so that a seeded generator always yields the same board
+    pub fn generate_with_rng<R: Rng>(
+        size: Vec2,
+        block_count: BlockId,
+        shuffle_round: usize,
+        rng: &mut R,
+    ) -> Result<Self, GenerateError> {
+        Self::generate_with_shapes_and_rng(
+            size,
+            block_count,
+            shuffle_round,
+            &Self::DEFAULT_BLOCK_SIZES,
+            ShuffleOptions::default(),
+            rng,
+        )
+    }
+
+    /// Randonly generate a valid board whose blocks have one of the given sizes.
+    /// Cells which no size fits into are left as holes
+    pub fn generate_with_shapes(
+        size: Vec2,
+        block_count: BlockId,
+        shuffle_round: usize,
+        block_sizes: &[Vec2],
+    ) -> Self {
+        Self::generate_with_shapes_and_rng(
+            size,
+            block_count,
+            shuffle_round,
+            block_sizes,
+            ShuffleOptions::default(),
+            &mut thread_rng(),
+        )
+        .expect("Invalid generate parameters")
+    }
+
+    /// Like [`Board::generate_with_shapes`], but by given random number generator and shuffle options
+    pub fn generate_with_shapes_and_rng<R: Rng>(
+        size: Vec2,
+        block_count: BlockId,
+        shuffle_round: usize,
+        block_sizes: &[Vec2],
+        options: ShuffleOptions,
+        rng: &mut R,
+    ) -> Result<Self, GenerateError> {
+        if size.x <= 0 || size.y <= 0 {
+            return Err(GenerateError::InvalidSize(size));
+        }
+        if block_sizes.is_empty() {
+            return Err(GenerateError::NoBlockSizes);
+        }
+        // Some layouts cannot move at all, try another one if they cannot leave the goal
+        for _ in 0..Self::MAX_GENERATE_ATTEMPTS {
+            let mut board = Self::random_layout(size, block_count, block_sizes, rng)?;
+            match board.shuffle(shuffle_round, options, rng) {
+                Err(GenerateError::StuckAtGoal) => continue,
+                result => return result.map(|_| board),
+            }
+        }
+
+        Err(GenerateError::StuckAtGoal)
+    }
+
+    /// Place exactly `block_count` blocks with sizes randomly chosen from `block_sizes`
+    fn random_layout<R: Rng>(
+        size: Vec2,
+        block_count: BlockId,
+        block_sizes: &[Vec2],
+        rng: &mut R,
+    ) -> Result<Self, GenerateError> {
+        let mut next_id = 1;
+        let mut possible_block_sizes = block_sizes.to_vec();
+        let mut grid = Matrix2D::<BlockId>::fill(size, 0);
+        // Empty cells from current position to the end
+        let mut free_cells = size.x as i32 * size.y as i32;
+        // Keep a hole if there is room, so that the board can move
+        let min_holes = i32::from(free_cells > block_count as i32);
+
+        for pos in grid.positions() {
+            if next_id > block_count {
+                break;
+            }
+            if grid[pos] != 0 {
+                continue;
+            }
+            possible_block_sizes.shuffle(rng);
+            let mut used_cells = 1;
+            for block_size in &possible_block_sizes {
+                let area = block_size.x as i32 * block_size.y as i32;
+                // Leave at least one cell for each remaining block
+                if free_cells - area < (block_count - next_id) as i32 + min_holes {
+                    continue;
+                }
+                if grid
+                    .try_fill_without_cover(pos, *block_size, next_id)
+                    .is_ok()
+                {
+                    next_id += 1;
+                    used_cells = area;
                     break;
                 }
             }
+            free_cells -= used_cells;
+        }
+        if next_id <= block_count {
+            return Err(GenerateError::TooManyBlocks(block_count));
         }
 
-        let mut board: Board = Board::try_from(grid).expect("Invalid input grid");
-        // Randomly shuffle board
-        let mut rng = thread_rng();
-        for _i in 0..shuffle_round {
-            let possible_moves = board.possible_moves();
-            if let Some((id, dir)) = possible_moves.choose(&mut rng) {
-                let _ = board.move_block(*id, *dir);
-            } else {
+        Board::try_from(grid).map_err(GenerateError::Invalid)
+    }
+
+    /// Randomly move blocks for given rounds. With [`ShuffleOptions::forbid_goal`],
+    /// at most [`Board::MAX_EXTRA_SHUFFLE`] more moves are made to leave the goal
+    fn shuffle<R: Rng>(
+        &mut self,
+        rounds: usize,
+        options: ShuffleOptions,
+        rng: &mut R,
+    ) -> Result<(), GenerateError> {
+        let forbid_goal = options.forbid_goal && rounds > 0;
+        let mut previous: Option<Move> = None;
+        for round in 0..rounds + Self::MAX_EXTRA_SHUFFLE {
+            if round >= rounds && !(forbid_goal && self.is_goal()) {
                 break;
             }
+            let mut possible_moves = self.possible_moves();
+            possible_moves.retain(|&m| self.is_valid_move(m).is_ok());
+            // Moves come from a hash set, sort them so that the choice only depends on `rng`
+            possible_moves.sort_unstable();
+            if options.forbid_backtrack && possible_moves.len() > 1 {
+                if let Some((id, dir)) = previous {
+                    possible_moves.retain(|&m| m != (id, dir.inverse()));
+                }
+            }
+            match possible_moves.choose(rng) {
+                Some(&(id, dir)) => {
+                    self.move_block(id, dir)
+                        .expect("Moves are checked to be valid");
+                    previous = Some((id, dir));
+                }
+                None => break,
+            }
+        }
+        if forbid_goal && self.is_goal() {
+            return Err(GenerateError::StuckAtGoal);
         }
 
-        board
+        Ok(())
+    }
+}
+
+impl PartialEq for Board {
+    /// Boards are equal if they are in the same state, no matter how they got there.
+    /// Applied moves, see [`Board::history`] and [`Board::move_count`], are ignored
+    fn eq(&self, other: &Self) -> bool {
+        self.grid == other.grid
+            && self.state == other.state
+            && self.spec == other.spec
+            && self._possible_moves == other._possible_moves
+            && self.holes == other.holes
+            && self.zobrist_hash == other.zobrist_hash
+            && self.heuristic == other.heuristic
     }
 }
 
 impl Display for Board {
+    /// Write the grid in the same format [`Board::from_str`] parses,
+    /// the goal is not written
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let size = self.grid.size();
-        writeln!(f, "{} {}", size.x, size.y)?;
-        for row in self.grid.chunks(size.x as usize) {
-            let row = row
-                .iter()
-                .map(|v| v.to_string())
-                .collect::<Vec<_>>()
-                .join(" ");
-            writeln!(f, "{}", row)?;
+        writeln!(f, "{} {}", size.y, size.x)?;
+        write!(f, "{}", self.grid.to_grid_string())
+    }
+}
+
+/// Serialized form of [`Board`]. Only grids are stored, so that deserializing
+/// validates them like parsing does and recomputes everything else
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BoardData {
+    grid: Matrix2D<BlockId>,
+    /// The packed layout is used if missing
+    #[serde(default)]
+    goal: Option<Matrix2D<BlockId>>,
+}
+
+#[cfg(feature = "serde")]
+impl From<Board> for BoardData {
+    fn from(board: Board) -> Self {
+        let goal = Some(board.goal_grid());
+        Self {
+            grid: board.grid,
+            goal,
         }
-        Ok(())
     }
 }
 
-impl TryFrom<Matrix2D<i8>> for Board {
-    type Error = String;
+#[cfg(feature = "serde")]
+impl TryFrom<BoardData> for Board {
+    type Error = BoardError;
 
-    fn try_from(grid: Matrix2D<i8>) -> Result<Self, Self::Error> {
-        let size = grid.size();
-        // Parse holes & blocks
-        let mut blocks = HashMap::new();
-        let mut holes = HashSet::new();
-        for pos in Square::at_origin(size).row_iter() {
-            let id = grid.get(pos).expect("This query should fit inside matrix");
-            if id == &0 {
-                holes.insert(pos);
-            } else {
-                blocks.entry(*id).or_insert(vec![]).push(pos);
-            }
+    fn try_from(data: BoardData) -> Result<Self, Self::Error> {
+        match data.goal {
+            Some(goal) => Self::with_goal(data.grid, goal),
+            None => Self::try_from(data.grid),
         }
-        let blocks = Self::parse_blocks(blocks)?;
-        let state = BoardState::new(size, blocks);
-        let final_state = Self::generate_final_state(size, &state.blocks)?;
-        let _possible_moves = Self::generate_possible_moves(&holes, &grid);
+    }
+}
 
-        Ok(Board {
-            grid,
-            state,
-            final_state,
-            _possible_moves,
-            holes,
-        })
+impl TryFrom<Matrix2D<BlockId>> for Board {
+    type Error = BoardError;
+
+    fn try_from(grid: Matrix2D<BlockId>) -> Result<Self, Self::Error> {
+        let (blocks, holes) = Self::parse_grid(&grid)?;
+        let final_state = Self::generate_final_state(&grid, &blocks)?;
+        Ok(Self::new(grid, blocks, holes, final_state))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::builder::BoardBuilder;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_format_move() {
+        assert_eq!(format_move((7, Dir::Left)), "7L");
+        assert_eq!(format_move((200, Dir::Up)), "200U");
+        assert_eq!(Dir::Down.to_string(), "D");
+    }
 
     #[test]
     fn test_move_block() -> Result<(), String> {
@@ -410,13 +1828,63 @@ mod tests {
     }
 
     #[test]
-    fn test_move_out_of_range() -> Result<(), String> {
-        let mut board = "3 3\n\
-        1 1 2\n\
-        0 3 0\n\
-        0 4 4\n\
+    fn test_slide_block() -> Result<(), String> {
+        let mut board = "2 4\n\
+        1 0 0 2\n\
+        0 0 0 0\n\
+        "
+        .parse::<Board>()?;
+        assert_eq!(board.slide_block(1, Dir::Right)?, 2);
+        assert_eq!(board.slide_block(1, Dir::Down)?, 1);
+        assert!(board.slide_block(1, Dir::Down).is_err());
+        let expected = "2 4\n\
+        0 0 0 2\n\
+        0 0 1 0\n\
         "
         .parse::<Board>()?;
+
+        assert_eq!(board.grid, expected.grid);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_failed_move_leaves_board_unchanged() -> Result<(), String> {
+        let parse = || {
+            "2 3\n\
+            1 0 2\n\
+            0 0 0\n\
+            "
+            .parse::<Board>()
+        };
+        // Doctor the grid so that it disagrees with the blocks
+        let mut board = parse()?;
+        board.grid.try_fill(Vec2::new(0, 0), Vec2::new(1, 1), 2)?;
+        let doctored = board.clone();
+
+        assert!(board.move_block(1, Dir::Down).is_err());
+        assert_eq!(board, doctored);
+
+        // Doctor a block so that it lies partly outside the grid
+        let mut board = parse()?;
+        board.state.blocks[1].size = Vec2::new(2, 1);
+        let doctored = board.clone();
+
+        assert!(board.is_valid_move((2, Dir::Left)).is_ok());
+        assert!(board.move_block(2, Dir::Left).is_err());
+        assert_eq!(board, doctored);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_move_out_of_range() -> Result<(), String> {
+        let mut board = BoardBuilder::new(Vec2::new(3, 3))
+            .add_block(1, Vec2::new(0, 0), Vec2::new(2, 1))
+            .add_block(2, Vec2::new(2, 0), Vec2::new(1, 1))
+            .add_block(3, Vec2::new(1, 1), Vec2::new(1, 1))
+            .add_block(4, Vec2::new(1, 2), Vec2::new(2, 1))
+            .build()?;
         assert!(board.move_block(2, Dir::Right).is_err());
 
         Ok(())
@@ -456,7 +1924,7 @@ mod tests {
         9 0 10 0\n\
         "
         .parse::<Board>()?;
-        assert_eq!(board.final_state, reach_goal_at_init.final_state);
+        assert_eq!(board.final_state(), reach_goal_at_init.final_state());
         board.move_block(10, Dir::Left)?;
 
         assert!(board.is_goal());
@@ -515,21 +1983,1587 @@ mod tests {
     }
 
     #[test]
-    fn test_move_is_recoverable() -> Result<(), String> {
-        let mut board = "5 4\n\
-        1 2 2 3\n\
-        1 2 2 3\n\
-        4 0 5 5\n\
-        4 0 7 6\n\
-        9 10 8 6\n\
+    fn test_possible_moves_random_walk() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut board = Board::generate_with_rng(Vec2::new(8, 8), 24, 0, &mut rng)
+            .expect("Invalid generate parameters");
+        for _ in 0..256 {
+            let moves = board.neighbors().map(|(m, _)| m).collect::<Vec<_>>();
+            let &(id, dir) = moves.choose(&mut rng).expect("Board should be movable");
+            board.move_block(id, dir).expect("Move should be valid");
+
+            assert_eq!(
+                board._possible_moves,
+                Board::generate_possible_moves(&board.holes, &board.grid)
+            );
+        }
+    }
+
+    #[test]
+    fn test_possible_moves_iter() -> Result<(), String> {
+        let board = "3 3\n1 2 3\n4 0 5\n6 7 8\n".parse::<Board>()?;
+        let mut moves = board.possible_moves_iter().collect::<Vec<_>>();
+        moves.sort_unstable();
+        let mut expected = board.possible_moves();
+        expected.sort_unstable();
+        assert_eq!(moves, expected);
+        assert_eq!(moves.len(), 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_heuristic_random_walk() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut board = Board::generate_with_rng(Vec2::new(8, 8), 24, 0, &mut rng)
+            .expect("Invalid generate parameters");
+        for _ in 0..4096 {
+            let moves = board.neighbors().map(|(m, _)| m).collect::<Vec<_>>();
+            let &(id, dir) = moves.choose(&mut rng).expect("Board should be movable");
+            board.move_block(id, dir).expect("Move should be valid");
+
+            assert_eq!(board.heuristic(), board.recompute_heuristic());
+        }
+    }
+
+    #[test]
+    fn test_min_cost_assignment() {
+        let cost = vec![vec![4, 1, 3], vec![2, 0, 5], vec![3, 2, 2]];
+        assert_eq!(min_cost_assignment(&cost), 5);
+        assert_eq!(min_cost_assignment(&[vec![7]]), 7);
+        assert_eq!(min_cost_assignment(&[]), 0);
+    }
+
+    #[test]
+    fn test_heuristic_matched_swapped_blocks() -> Result<(), String> {
+        // 1 & 2 have the same shape, so swapping them costs nothing
+        let board = "2 3\n\
+        2 1 0\n\
+        3 3 0\n\
         "
         .parse::<Board>()?;
-        let original = board.clone();
 
-        board.move_block(5, Dir::Left)?;
-        assert_ne!(board, original);
-        board.move_block(5, Dir::Right)?;
-        assert_eq!(board, original);
+        assert_eq!(board.heuristic(), 2);
+        assert_eq!(board.heuristic_matched(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_heuristic_matched_is_not_greater() {
+        let mut rng = StdRng::seed_from_u64(13);
+        let mut board = Board::generate_with_rng(Vec2::new(6, 6), 12, 0, &mut rng)
+            .expect("Invalid generate parameters");
+        for _ in 0..512 {
+            let moves = board.neighbors().map(|(m, _)| m).collect::<Vec<_>>();
+            let &(id, dir) = moves.choose(&mut rng).expect("Board should be movable");
+            board.move_block(id, dir).expect("Move should be valid");
+
+            assert!(board.heuristic_matched() <= board.heuristic());
+        }
+    }
+
+    #[test]
+    fn test_pack_round_trip() {
+        let mut rng = StdRng::seed_from_u64(17);
+        for (size, block_count) in [(Vec2::new(4, 4), 6), (Vec2::new(20, 20), 64)] {
+            let board = Board::generate_with_rng(size, block_count, 64, &mut rng)
+                .expect("Invalid generate parameters");
+            let state = board.state();
+            assert_eq!(state.unpack(&state.pack()), *state);
+            let final_state = board.final_state();
+            assert_eq!(state.unpack(&final_state.pack()), *final_state);
+        }
+    }
+
+    #[test]
+    fn test_pack_distinguishes_states() -> Result<(), String> {
+        let mut board = "2 2\n1 2\n0 0\n".parse::<Board>()?;
+        let before = board.state().pack();
+        board.move_block(1, Dir::Down)?;
+        assert_ne!(board.state().pack(), before);
+        board.move_block(1, Dir::Up)?;
+        assert_eq!(board.state().pack(), before);
+
+        // Same positions on boards with other walls, shapes or sizes
+        let walled = "2 2\n1 2\n-1 0\n".parse::<Board>()?;
+        let wide = "2 2\n1 2\n1 0\n".parse::<Board>()?;
+        let larger = "3 2\n1 2\n0 0\n0 0\n".parse::<Board>()?;
+        for other in [walled, wide, larger] {
+            assert_ne!(other.state().pack(), before);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_clone_shares_spec() -> Result<(), String> {
+        let board = "2 2\n1 2\n0 0\n".parse::<Board>()?;
+        let mut cloned = board.clone();
+        cloned.move_block(1, Dir::Down)?;
+
+        assert!(Arc::ptr_eq(&board.spec, &cloned.spec));
+        assert_eq!(board.final_state(), cloned.final_state());
+        assert!(board.is_goal());
+        assert!(!cloned.is_goal());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_round_trip() -> Result<(), String> {
+        let mut rng = StdRng::seed_from_u64(19);
+        for (size, block_count) in [(Vec2::new(3, 5), 4), (Vec2::new(8, 6), 12)] {
+            for _ in 0..16 {
+                let board = Board::generate_with_rng(size, block_count, 32, &mut rng)
+                    .expect("Invalid generate parameters");
+                let parsed = board.to_string().parse::<Board>()?;
+                assert_eq!(parsed.grid, board.grid);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_pretty_string() -> Result<(), String> {
+        let board = "2 3\n1 1 0\n2 3 0\n".parse::<Board>()?;
+        assert_eq!(board.to_string(), "2 3\n1 1 0\n2 3 0\n");
+        assert_eq!(board.to_pretty_string(), "2x3\n[1, 1, 0]\n[2, 3, 0]\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_linear_conflict_in_row() -> Result<(), String> {
+        // 1 & 2 are in their goal row but inverted
+        let board = "2 3\n\
+        2 1 0\n\
+        3 3 0\n\
+        "
+        .parse::<Board>()?;
+
+        assert_eq!(board.heuristic(), 2);
+        assert_eq!(board.heuristic_linear_conflict(), 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_linear_conflict_counts_blocks_to_remove() -> Result<(), String> {
+        // 3 pairs are in conflict, but moving 1 & 3 out of the row is enough
+        let board = "2 3\n\
+        3 2 1\n\
+        0 0 0\n\
+        "
+        .parse::<Board>()?;
+
+        assert_eq!(board.heuristic(), 4);
+        assert_eq!(board.heuristic_linear_conflict(), 8);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_linear_conflict_without_conflict() -> Result<(), String> {
+        let board = "5 4\n\
+        1 2 2 3\n\
+        1 2 2 3\n\
+        4 0 5 5\n\
+        4 0 7 6\n\
+        9 10 8 6\n\
+        "
+        .parse::<Board>()?;
+
+        assert_eq!(board.heuristic_linear_conflict(), board.heuristic());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zobrist_hash_is_incremental() -> Result<(), String> {
+        let mut board = "5 4\n\
+        1 2 2 3\n\
+        1 2 2 3\n\
+        4 0 5 5\n\
+        4 0 7 6\n\
+        9 10 8 6\n\
+        "
+        .parse::<Board>()?;
+        let original_hash = board.zobrist_hash();
+        board.move_block(5, Dir::Left)?;
+        let after_move = "5 4\n\
+        1 2 2 3\n\
+        1 2 2 3\n\
+        4 5 5 0\n\
+        4 0 7 6\n\
+        9 10 8 6\n\
+        "
+        .parse::<Board>()?;
+
+        assert_ne!(board.zobrist_hash(), original_hash);
+        assert_eq!(board.zobrist_hash(), after_move.zobrist_hash());
+        board.move_block(5, Dir::Right)?;
+        assert_eq!(board.zobrist_hash(), original_hash);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zobrist_hash_collision() {
+        // Random walks from one board, so that distinct states share the same keys
+        let mut rng = StdRng::seed_from_u64(37);
+        let start = Board::generate_with_rng(Vec2::new(6, 6), 16, 0, &mut rng)
+            .expect("Invalid generate parameters");
+        let mut seen: HashMap<u64, BoardState> = HashMap::new();
+        for _ in 0..50 {
+            let mut board = start.clone();
+            for _ in 0..20000 {
+                // Possible moves come in hash order, which differs between runs
+                let mut moves = board.possible_moves();
+                moves.sort_unstable();
+                let (id, dir) = match moves.choose(&mut rng) {
+                    Some(m) => *m,
+                    None => break,
+                };
+                if board.move_block(id, dir).is_err() {
+                    continue;
+                }
+                let state = seen
+                    .entry(board.zobrist_hash())
+                    .or_insert_with(|| board.state().clone());
+                assert_eq!(state, board.state());
+            }
+        }
+        assert!(seen.len() > 1000);
+    }
+
+    #[test]
+    fn test_quick_solvability_solved() -> Result<(), String> {
+        let board = "5 4\n\
+        1 2 2 3\n\
+        1 2 2 3\n\
+        4 5 5 6\n\
+        4 7 8 6\n\
+        9 10 0 0\n\
+        "
+        .parse::<Board>()?;
+
+        assert_eq!(board.quick_solvability(), Solvability::Solved);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_quick_solvability_too_few_holes() -> Result<(), String> {
+        // The 2x2 block needs 2 holes to move
+        let board = "3 3\n\
+        2 2 1\n\
+        2 2 3\n\
+        4 5 0\n\
+        "
+        .parse::<Board>()?;
+
+        assert_eq!(board.quick_solvability(), Solvability::Unsolvable);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_quick_solvability_without_holes() -> Result<(), String> {
+        let board = "2 2\n\
+        2 1\n\
+        3 4\n\
+        "
+        .parse::<Board>()?;
+
+        assert_eq!(board.quick_solvability(), Solvability::Unsolvable);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_quick_solvability_split_by_walls() -> Result<(), String> {
+        // Block 1 belongs to the left region but starts in the right one
+        let board = "2 3\n\
+        0 -1 1\n\
+        0 -1 0\n\
+        "
+        .parse::<Board>()?;
+        assert_eq!(board.goal_grid()[Vec2::new(0, 0)], 1);
+        assert_eq!(board.quick_solvability(), Solvability::Unsolvable);
+
+        // Blocks 1 and 2 are swapped in a region without holes, while block 3 can move
+        let board = "2 4\n\
+        2 1 -1 3\n\
+        -1 -1 -1 0\n\
+        "
+        .parse::<Board>()?;
+        assert_eq!(board.quick_solvability(), Solvability::Unsolvable);
+
+        // Holes split by a block are fine, since they join once the block moves
+        let board = "1 3\n\
+        0 1 0\n\
+        "
+        .parse::<Board>()?;
+        assert!(!board.holes_connected());
+        assert_eq!(board.quick_solvability(), Solvability::Unknown);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_quick_solvability_unknown() -> Result<(), String> {
+        // Unsolvable by parity, which is beyond the cheap checks
+        let unsolvable = "2 2\n\
+        2 1\n\
+        3 0\n\
+        "
+        .parse::<Board>()?;
+        let solvable = "3 3\n\
+        1 1 2\n\
+        0 3 0\n\
+        0 4 4\n\
+        "
+        .parse::<Board>()?;
+
+        assert_eq!(unsolvable.quick_solvability(), Solvability::Unknown);
+        assert_eq!(solvable.quick_solvability(), Solvability::Unknown);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parity_solvability() -> Result<(), String> {
+        let swapped = "3 3\n\
+        2 1 3\n\
+        4 5 6\n\
+        7 8 0\n\
+        "
+        .parse::<Board>()?;
+        assert_eq!(swapped.parity_solvability(), Solvability::Unsolvable);
+        let swapped = "2 2\n\
+        2 1\n\
+        3 0\n\
+        "
+        .parse::<Board>()?;
+        assert_eq!(swapped.parity_solvability(), Solvability::Unsolvable);
+
+        // Shuffled by moves, with the hole anywhere
+        let mut rng = StdRng::seed_from_u64(31);
+        let mut board = Board::generate_with_rng(Vec2::new(4, 4), 15, 0, &mut rng)
+            .map_err(|err| err.to_string())?;
+        for _ in 0..100 {
+            let mut moves = board.possible_moves();
+            moves.sort_unstable();
+            let &(id, dir) = moves.choose(&mut rng).ok_or("No possible move")?;
+            board.move_block(id, dir)?;
+            assert_ne!(board.parity_solvability(), Solvability::Unsolvable);
+        }
+
+        // Multiple holes or larger blocks do not keep parity
+        let board = "3 3\n\
+        1 1 2\n\
+        0 3 0\n\
+        0 4 4\n\
+        "
+        .parse::<Board>()?;
+        assert_eq!(board.parity_solvability(), Solvability::Unknown);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mirrored() -> Result<(), String> {
+        let board = "3 3\n\
+        1 1 2\n\
+        0 3 0\n\
+        0 4 4\n\
+        "
+        .parse::<Board>()?;
+        let mirrored = "3 3\n\
+        2 1 1\n\
+        0 3 0\n\
+        4 4 0\n\
+        "
+        .parse::<Board>()?;
+        let size = Vec2::new(3, 3);
+
+        assert_eq!(board.state().mirrored(size), *mirrored.state());
+        assert_eq!(board.state().mirrored(size).mirrored(size), *board.state());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mirror_relabeling() -> Result<(), String> {
+        let symmetric = "4 4\n\
+        1 1 2 2\n\
+        3 3 4 4\n\
+        5 6 0 0\n\
+        7 0 0 8\n\
+        "
+        .parse::<Board>()?;
+        let asymmetric = "3 3\n\
+        1 1 2\n\
+        0 3 0\n\
+        0 4 4\n\
+        "
+        .parse::<Board>()?;
+
+        assert_eq!(
+            symmetric.mirror_relabeling(),
+            Some(vec![2, 1, 4, 3, 8, 7, 6, 5])
+        );
+        assert_eq!(asymmetric.mirror_relabeling(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_relabeled_mirror() -> Result<(), String> {
+        let board = "4 4\n\
+        1 1 2 2\n\
+        3 3 4 4\n\
+        5 6 7 8\n\
+        0 0 0 0\n\
+        "
+        .parse::<Board>()?;
+        let relabeling = board
+            .mirror_relabeling()
+            .ok_or("Goal should be symmetric")?;
+        let mut moved = board.clone();
+        moved.move_block(5, Dir::Down)?;
+        // Mirror image of the moved board, with blocks renamed
+        let mut mirrored = board.clone();
+        mirrored.move_block(8, Dir::Down)?;
+
+        let mirror = |board: &Board| board.state().mirrored(board.size()).relabeled(&relabeling);
+
+        assert!(board.is_goal());
+        assert_eq!(mirror(&board), *board.state());
+        assert_eq!(mirror(&moved), *mirrored.state());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_states_of_different_boards_differ() -> Result<(), String> {
+        // Same blocks at the same positions, but on boards of different sizes
+        let narrow = "2 2\n\
+        1 0\n\
+        0 0\n\
+        "
+        .parse::<Board>()?;
+        let wide = "2 3\n\
+        1 0 0\n\
+        0 0 0\n\
+        "
+        .parse::<Board>()?;
+        // Same size and blocks, but different walls
+        let walled = "2 3\n\
+        1 0 -1\n\
+        0 0 0\n\
+        "
+        .parse::<Board>()?;
+
+        assert_eq!(narrow.state().blocks(), wide.state().blocks());
+        assert_ne!(narrow.state(), wide.state());
+        assert_eq!(wide.state().blocks(), walled.state().blocks());
+        assert_ne!(wide.state(), walled.state());
+        assert_ne!(wide.state().canonical(), walled.state().canonical());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_canonical() -> Result<(), String> {
+        let board = "3 3\n\
+        1 2 3\n\
+        4 4 0\n\
+        5 5 0\n\
+        "
+        .parse::<Board>()?;
+        let swapped_singles = "3 3\n\
+        3 1 2\n\
+        5 5 0\n\
+        4 4 0\n\
+        "
+        .parse::<Board>()?;
+        let moved = "3 3\n\
+        1 2 0\n\
+        4 4 3\n\
+        5 5 0\n\
+        "
+        .parse::<Board>()?;
+
+        assert_ne!(board.state(), swapped_singles.state());
+        assert_eq!(
+            board.state().canonical(),
+            swapped_singles.state().canonical()
+        );
+        assert_ne!(board.state().canonical(), moved.state().canonical());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_move_is_recoverable() -> Result<(), String> {
+        let mut board = "5 4\n\
+        1 2 2 3\n\
+        1 2 2 3\n\
+        4 0 5 5\n\
+        4 0 7 6\n\
+        9 10 8 6\n\
+        "
+        .parse::<Board>()?;
+        let original = board.clone();
+
+        board.move_block(5, Dir::Left)?;
+        assert_ne!(board, original);
+        board.move_block(5, Dir::Right)?;
+        assert_eq!(board, original);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_undo_random_walk() {
+        let mut rng = thread_rng();
+        for _ in 0..8 {
+            let mut board = Board::generate(Vec2::new(6, 6), 16, 0);
+            let original = board.clone();
+            for _ in 0..1000 {
+                if let Some(&(id, dir)) = board.possible_moves().choose(&mut rng) {
+                    let _ = board.push_move(id, dir);
+                }
+            }
+            let mut history = board.history().to_vec();
+
+            while let Some(m) = board.undo() {
+                assert_eq!(Some(m), history.pop());
+            }
+            assert!(history.is_empty());
+            assert_eq!(board, original);
+            assert_eq!(board.zobrist_hash(), original.zobrist_hash());
+        }
+    }
+
+    #[test]
+    fn test_apply_moves() -> Result<(), String> {
+        let mut board = "2 3\n\
+        1 0 0\n\
+        2 0 3\n\
+        "
+        .parse::<Board>()?;
+        board.apply_moves(&[(2, Dir::Right), (2, Dir::Up), (3, Dir::Up)])?;
+
+        assert!(board.is_goal());
+        assert_eq!(board.history().len(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply() -> Result<(), String> {
+        let board = "2 3\n\
+        1 0 0\n\
+        2 0 3\n\
+        "
+        .parse::<Board>()?;
+        let original = board.clone();
+        let next = board.apply((2, Dir::Right))?;
+        let expected = "2 3\n\
+        1 0 0\n\
+        0 2 3\n\
+        "
+        .parse::<Board>()?;
+
+        assert_eq!(board, original);
+        assert_eq!(next.grid, expected.grid);
+        assert!(board.apply((2, Dir::Left)).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_neighbors_match_possible_moves() {
+        for _ in 0..16 {
+            let board = Board::generate(Vec2::new(5, 5), 8, 16);
+            let mut expected = HashMap::new();
+            for m in board.possible_moves() {
+                let mut next = board.clone();
+                if next.move_block(m.0, m.1).is_ok() {
+                    expected.insert(m, next);
+                }
+            }
+            let neighbors = board.neighbors().collect::<HashMap<_, _>>();
+
+            assert_eq!(neighbors, expected);
+        }
+    }
+
+    #[test]
+    fn test_apply_moves_rolls_back() -> Result<(), String> {
+        let mut board = "2 3\n\
+        1 0 0\n\
+        2 0 3\n\
+        "
+        .parse::<Board>()?;
+        board.push_move(3, Dir::Left)?;
+        let original = board.clone();
+        let result = board.apply_moves(&[(3, Dir::Up), (3, Dir::Right), (3, Dir::Right)]);
+
+        assert_eq!(result.map_err(|e| e.index), Err(2));
+        assert_eq!(board, original);
+        assert_eq!(board.history(), &[(3, Dir::Left)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_solution() -> Result<(), String> {
+        let board = "2 3\n\
+        1 0 0\n\
+        2 0 3\n\
+        "
+        .parse::<Board>()?;
+        let original = board.clone();
+        let solved = board
+            .verify_solution(&[(2, Dir::Right), (2, Dir::Up), (3, Dir::Up)])
+            .map_err(|e| e.to_string())?;
+        let unsolved = board
+            .verify_solution(&[(2, Dir::Right)])
+            .map_err(|e| e.to_string())?;
+
+        assert_eq!(
+            solved,
+            VerifyReport {
+                move_count: 3,
+                reached_goal: true
+            }
+        );
+        assert_eq!(
+            unsolved,
+            VerifyReport {
+                move_count: 1,
+                reached_goal: false
+            }
+        );
+        assert_eq!(board, original);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_solution_errors() -> Result<(), String> {
+        let board = "2 3\n\
+        1 0 0\n\
+        2 0 3\n\
+        "
+        .parse::<Board>()?;
+        let reason = |moves: &[Move]| {
+            board
+                .verify_solution(moves)
+                .map_err(|e| (e.index, e.reason))
+        };
+
+        assert_eq!(
+            reason(&[(2, Dir::Right), (2, Dir::Down)]),
+            Err((
+                1,
+                BoardError::OutOfRange {
+                    pos: Vec2::new(1, 2)
+                }
+            ))
+        );
+        assert_eq!(
+            reason(&[(2, Dir::Up)]),
+            Err((
+                0,
+                BoardError::Occupied {
+                    pos: Vec2::new(0, 0),
+                    by: 1
+                }
+            ))
+        );
+        assert_eq!(
+            reason(&[(3, Dir::Up), (4, Dir::Up)]),
+            Err((1, BoardError::UnknownBlock { id: 4 }))
+        );
+        assert_eq!(
+            reason(&[(BlockId::MIN, Dir::Up)]),
+            Err((0, BoardError::UnknownBlock { id: BlockId::MIN }))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_move_block_errors() -> Result<(), String> {
+        let mut board = "2 3\n\
+        1 0 -1\n\
+        2 0 3\n\
+        "
+        .parse::<Board>()?;
+
+        assert_eq!(
+            board.move_block(1, Dir::Up),
+            Err(BoardError::OutOfRange {
+                pos: Vec2::new(0, -1)
+            })
+        );
+        assert_eq!(
+            board.move_block(1, Dir::Down),
+            Err(BoardError::Occupied {
+                pos: Vec2::new(0, 1),
+                by: 2
+            })
+        );
+        assert_eq!(
+            board.move_block(3, Dir::Up),
+            Err(BoardError::Occupied {
+                pos: Vec2::new(2, 0),
+                by: WALL
+            })
+        );
+        assert_eq!(
+            board.move_block(4, Dir::Up),
+            Err(BoardError::UnknownBlock { id: 4 })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            "2\n1 0\n".parse::<Board>(),
+            Err(BoardError::Parse { line: 1, .. })
+        ));
+        assert!(matches!(
+            "2 2\n1 0\n0 x\n".parse::<Board>(),
+            Err(BoardError::Parse { line: 3, .. })
+        ));
+        assert!(matches!(
+            "2 2\n1 0 0\n0 0\n".parse::<Board>(),
+            Err(BoardError::Parse { line: 2, .. })
+        ));
+        assert_eq!(
+            "3 2\n1 0\n0 0\n".parse::<Board>(),
+            Err(BoardError::RowCount {
+                expected_rows: 3,
+                found_rows: 2
+            })
+        );
+        assert_eq!(
+            "1 2\n1 0\n0 0\n".parse::<Board>().map_err(String::from),
+            Err("Invalid line 3: Expect 1 rows, found more".to_string())
+        );
+        assert_eq!(
+            "2 2\n1 0\n0 1\n".parse::<Board>(),
+            Err(BoardError::InvalidBlockShape { id: 1 })
+        );
+        assert_eq!(
+            "2 2\n-2 0\n0 0\n".parse::<Board>(),
+            Err(BoardError::InvalidId(-2))
+        );
+        assert_eq!(
+            "2 3\n1 1 0\n1 1 0\n\n0 0 0\n0 1 1\n".parse::<Board>(),
+            Err(BoardError::InvalidGoal(
+                "Block 1 has size Vec2(2, 2) on board but Vec2(2, 1) in goal".to_string()
+            ))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() -> Result<(), String> {
+        let boards = [
+            Board::generate_with_rng(Vec2::new(6, 5), 10, 32, &mut StdRng::seed_from_u64(23))
+                .expect("Invalid generate parameters"),
+            "2 3\n1 0 -1\n2 0 0\n\n0 2 -1\n0 0 1\n".parse::<Board>()?,
+        ];
+        for board in boards {
+            let json = serde_json::to_string(&board).map_err(|e| e.to_string())?;
+            assert_eq!(
+                serde_json::from_str::<Board>(&json).map_err(|e| e.to_string())?,
+                board
+            );
+            let bytes = serde_cbor::to_vec(&board).map_err(|e| e.to_string())?;
+            assert_eq!(
+                serde_cbor::from_slice::<Board>(&bytes).map_err(|e| e.to_string())?,
+                board
+            );
+
+            let state = board.state();
+            let json = serde_json::to_string(state).map_err(|e| e.to_string())?;
+            assert_eq!(
+                &serde_json::from_str::<BoardState>(&json).map_err(|e| e.to_string())?,
+                state
+            );
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_without_goal() -> Result<(), String> {
+        let json = r#"{"grid":{"store":[2,0,1,1],"size":{"x":2,"y":2}}}"#;
+        let board = serde_json::from_str::<Board>(json).map_err(|e| e.to_string())?;
+        assert_eq!(board, "2 2\n2 0\n1 1\n".parse::<Board>()?);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_rejects_invalid_grid() {
+        // Block 1 is split into two parts, as if another block overlapped it
+        let json = r#"{"grid":{"store":[1,2,2,1],"size":{"x":2,"y":2}}}"#;
+        let err = serde_json::from_str::<Board>(json).unwrap_err();
+        assert!(err.to_string().contains("block 1 cannot form a rectangle"));
+        // Too few cells for the size
+        let json = r#"{"grid":{"store":[1,0,0],"size":{"x":2,"y":2}}}"#;
+        assert!(serde_json::from_str::<Board>(json).is_err());
+    }
+
+    #[test]
+    fn test_diff_follows_moves() {
+        let mut rng = StdRng::seed_from_u64(29);
+        let mut board = Board::generate_with_rng(Vec2::new(5, 5), 8, 0, &mut rng)
+            .expect("Invalid generate parameters");
+        assert_eq!(board.diff(&board), Ok(vec![]));
+        for _ in 0..64 {
+            let (&(id, dir), next) = board
+                .neighbors()
+                .collect::<Vec<_>>()
+                .choose(&mut rng)
+                .map(|(m, next)| (m, next.clone()))
+                .expect("Board should be movable");
+
+            let diffs = board.diff(&next).expect("Boards should be comparable");
+            assert_eq!(diffs.len(), 1);
+            let diff = diffs[0];
+            assert_eq!(diff.id, id);
+            assert_eq!(diff.from + dir.to_vec2(), diff.to);
+            board = next;
+        }
+    }
+
+    #[test]
+    fn test_diff_incompatible() -> Result<(), String> {
+        let board = "2 2\n1 0\n0 0\n".parse::<Board>()?;
+        let wider = "2 3\n1 0 0\n0 0 0\n".parse::<Board>()?;
+        let larger = "2 2\n1 1\n0 0\n".parse::<Board>()?;
+
+        assert!(matches!(
+            board.diff(&wider),
+            Err(BoardError::Incompatible(_))
+        ));
+        assert!(matches!(
+            board.diff(&larger),
+            Err(BoardError::Incompatible(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_goal() -> Result<(), String> {
+        let mut board = "2 3\n\
+        1 0 0\n\
+        2 0 3\n\
+        \n\
+        0 0 1\n\
+        2 0 3\n\
+        "
+        .parse::<Board>()?;
+        let packed = "2 3\n\
+        1 0 0\n\
+        2 0 3\n\
+        "
+        .parse::<Board>()?;
+
+        assert!(!board.is_goal());
+        assert_eq!(board.heuristic(), 2);
+        assert_eq!(packed.heuristic(), 3);
+        assert_ne!(board.final_state(), packed.final_state());
+        board.move_block(1, Dir::Right)?;
+        board.move_block(1, Dir::Right)?;
+        assert!(board.is_goal());
+        assert_eq!(board.heuristic(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_non_contiguous_ids() -> Result<(), String> {
+        let text = "2 3\n9 0 2\n5 5 0\n";
+        let mut board = text.parse::<Board>()?;
+        assert_eq!(
+            board.blocks().iter().map(Block::id).collect::<Vec<_>>(),
+            [2, 5, 9]
+        );
+        assert_eq!(board.to_string(), text);
+        // Blocks are packed in id order in the goal
+        assert_eq!(board.goal_grid().to_grid_string(), "2 5 5\n9 0 0\n");
+        assert_eq!(board.heuristic(), board.recompute_heuristic());
+
+        assert_eq!(
+            board.move_block(3, Dir::Up),
+            Err(BoardError::UnknownBlock { id: 3 })
+        );
+        board.move_block(9, Dir::Right)?;
+        assert_eq!(board.to_string(), "2 3\n0 9 2\n5 5 0\n");
+        assert!(board.possible_moves().contains(&(2, Dir::Down)));
+        assert_eq!(board.to_string().parse::<Board>()?.grid(), board.grid());
+
+        let (normalized, mapping) = board.normalize_ids();
+        assert_eq!(normalized.to_string(), "2 3\n0 1 2\n3 3 0\n");
+        assert_eq!(mapping, HashMap::from([(9, 1), (2, 2), (5, 3)]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_normalize_ids() -> Result<(), String> {
+        let board = "2 3\n\
+        3 0 1\n\
+        2 2 0\n\
+        "
+        .parse::<Board>()?;
+        let (normalized, mapping) = board.normalize_ids();
+        assert_eq!(normalized.grid().to_grid_string(), "1 0 2\n3 3 0\n");
+        assert_eq!(mapping, HashMap::from([(3, 1), (1, 2), (2, 3)]));
+        // The goal is the same layout, only relabeled
+        assert_eq!(normalized.goal_grid().to_grid_string(), "2 3 3\n1 0 0\n");
+        assert_eq!(normalized.heuristic(), board.heuristic());
+        assert_eq!(
+            normalized.to_string_with_goal().parse::<Board>()?,
+            normalized
+        );
+
+        let (twice, mapping) = normalized.normalize_ids();
+        assert_eq!(twice, normalized);
+        assert!(mapping.iter().all(|(old, new)| old == new));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_goal_is_validated() -> Result<(), String> {
+        let initial = "2 3\n1 0 0\n2 2 0\n";
+        assert!(Board::parse_with_goal(initial, "0 2 2\n0 0 1\n").is_ok());
+        // Block 2 changes its shape
+        assert!(Board::parse_with_goal(initial, "1 2 0\n0 2 0\n").is_err());
+        // Block 3 is missing on the board
+        assert!(Board::parse_with_goal(initial, "1 0 3\n2 2 0\n").is_err());
+        let goal = "3 3\n1 0 0\n2 2 0\n0 0 0\n".parse::<Matrix2D<BlockId>>()?;
+        assert!(Board::with_goal(initial.parse()?, goal).is_err());
+        // Trailing blank lines are not a goal
+        let board = format!("{}\n\n", initial).parse::<Board>()?;
+        assert_eq!(board.final_state().blocks()[1].pos(), Vec2::new(1, 0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_retarget() -> Result<(), String> {
+        let mut board = "2 3\n1 0 0\n2 0 3\n".parse::<Board>()?;
+        let other = board.clone();
+        let packed = board.goal().clone();
+        let goal = "2 3\n0 0 1\n2 0 3\n\n0 0 1\n2 0 3\n".parse::<Board>()?;
+
+        board.retarget(goal.state().clone())?;
+        assert_eq!(board.goal(), goal.state());
+        assert_eq!(board.goal_grid(), *goal.grid());
+        assert_eq!(board.heuristic(), 2);
+        assert_eq!(board.heuristic(), board.recompute_heuristic());
+        // Clones share the spec, but retargeting one leaves the others alone
+        assert_eq!(other.goal(), &packed);
+        board.move_block(1, Dir::Right)?;
+        board.move_block(1, Dir::Right)?;
+        assert!(board.is_goal());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_retarget_is_validated() -> Result<(), String> {
+        let mut board = "2 3\n1 0 0\n2 2 0\n".parse::<Board>()?;
+        let goal = board.goal().clone();
+        let invalid_goal = |input: &str| -> Result<BoardError, String> {
+            let other = input.parse::<Board>()?;
+            let mut board = board.clone();
+            board
+                .retarget(other.state().clone())
+                .err()
+                .ok_or_else(|| format!("Goal should be rejected:\n{}", input))
+        };
+
+        // Different size, block count, shape and walls
+        assert!(matches!(
+            invalid_goal("3 3\n1 0 0\n2 2 0\n0 0 0\n")?,
+            BoardError::InvalidGoal(_)
+        ));
+        assert!(matches!(
+            invalid_goal("2 3\n1 0 3\n2 2 0\n")?,
+            BoardError::InvalidGoal(_)
+        ));
+        assert!(matches!(
+            invalid_goal("2 3\n1 2 0\n0 2 0\n")?,
+            BoardError::InvalidGoal(_)
+        ));
+        assert!(matches!(
+            invalid_goal("2 3\n1 0 -1\n2 2 0\n")?,
+            BoardError::InvalidGoal(_)
+        ));
+        // A failed retarget leaves the goal unchanged
+        assert_eq!(board.goal(), &goal);
+        board.retarget(goal.clone())?;
+        assert_eq!(board.goal(), &goal);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dir_transforms() {
+        for dir in Dir::ALL {
+            let vec = dir.to_vec2();
+            assert_eq!(dir.rotate90().to_vec2(), Vec2::new(-vec.y, vec.x));
+            assert_eq!(dir.mirror_h().to_vec2(), Vec2::new(-vec.x, vec.y));
+            assert_eq!(dir.mirror_v().to_vec2(), Vec2::new(vec.x, -vec.y));
+            assert_eq!(dir.rotate90().rotate90(), dir.inverse());
+        }
+    }
+
+    #[test]
+    fn test_transforms() -> Result<(), String> {
+        let board = "2 3\n\
+        1 1 0\n\
+        2 -1 3\n\
+        \n\
+        0 1 1\n\
+        3 -1 2\n\
+        "
+        .parse::<Board>()?;
+
+        let rotated = board.rotated90();
+        assert_eq!(rotated.size(), Vec2::new(2, 3));
+        assert_eq!(rotated.to_string(), "3 2\n2 1\n-1 1\n3 0\n");
+        assert_eq!(rotated.blocks()[0].size(), Vec2::new(1, 2));
+        assert_eq!(rotated.goal_grid().to_vec(), vec![3, 0, -1, 1, 2, 1]);
+        assert_eq!(rotated.heuristic(), board.heuristic());
+        assert_eq!(board.mirrored_h().to_string(), "2 3\n0 1 1\n3 -1 2\n");
+        assert_eq!(board.mirrored_v().to_string(), "2 3\n2 -1 3\n1 1 0\n");
+        assert_eq!(
+            board.rotated90().rotated90(),
+            board.mirrored_h().mirrored_v()
+        );
+        assert_eq!(board.mirrored_h().mirrored_h(), board);
+        assert_eq!(
+            (0..4).fold(board.clone(), |board, _| board.rotated90()),
+            board
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_crop_and_embed() -> Result<(), String> {
+        let board = "4 5\n\
+        0 0 0 0 0\n\
+        0 1 1 0 0\n\
+        0 2 0 0 0\n\
+        0 0 0 0 0\n\
+        \n\
+        0 0 0 0 0\n\
+        0 0 1 1 0\n\
+        0 2 0 0 0\n\
+        0 0 0 0 0\n\
+        "
+        .parse::<Board>()?;
+
+        let cropped = board.crop_to_content();
+        assert_eq!(cropped.to_string(), "2 3\n1 1 0\n2 0 0\n");
+        assert_eq!(cropped.goal_grid().to_vec(), vec![0, 1, 1, 2, 0, 0]);
+        assert_eq!(cropped.crop_to_content(), cropped);
+        assert_eq!(cropped.embed(Vec2::new(5, 4), Vec2::new(1, 1))?, board);
+        assert!(matches!(
+            cropped.embed(Vec2::new(5, 4), Vec2::new(3, 0)),
+            Err(BoardError::OutOfRange { .. })
+        ));
+        assert!(matches!(
+            cropped.embed(Vec2::new(5, 4), Vec2::new(0, -1)),
+            Err(BoardError::OutOfRange { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_large_board() -> Result<(), String> {
+        let size = 100;
+        let mut input = format!("{} {}\n", size, size);
+        for y in 0..size {
+            let row = (0..size)
+                .map(|x| {
+                    if (x, y) == (size - 1, size - 1) {
+                        "1"
+                    } else {
+                        "0"
+                    }
+                })
+                .collect::<Vec<_>>();
+            input.push_str(&row.join(" "));
+            input.push('\n');
+        }
+        let mut board = input.parse::<Board>()?;
+        assert_eq!(board.size(), Vec2::new(size, size));
+        assert_eq!(board.heuristic(), 2 * (size as i32 - 1));
+        assert_eq!(board.to_string().parse::<Board>()?, board);
+
+        board.move_block(1, Dir::Up)?;
+        let far = Vec2::new(size - 1, size - 2);
+        assert_eq!(board.grid().get(far), Some(&1));
+        assert_eq!(board.blocks()[0].pos(), far);
+        assert!(board.move_block(1, Dir::Right).is_err());
+        let state = board.state();
+        assert_eq!(state.unpack(&state.pack()), *state);
+
+        let generated = Board::generate(Vec2::new(size, size), 20, 50);
+        assert_eq!(generated.size(), Vec2::new(size, size));
+        assert_eq!(generated.blocks().len(), 20);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_move_at_max_coordinate() -> Result<(), String> {
+        let width = i16::MAX;
+        let mut cells = vec![0; width as usize];
+        cells[width as usize - 1] = 1;
+        let grid = Matrix2D::from_vec(Vec2::new(width, 1), cells)?;
+        let mut board = Board::try_from(grid)?;
+        assert_eq!(board.blocks()[0].pos(), Vec2::new(width - 1, 0));
+        assert!(!board.possible_moves().contains(&(1, Dir::Right)));
+
+        // The cell right of the block is not representable
+        assert_eq!(
+            board.move_block(1, Dir::Right),
+            Err(BoardError::OutOfRange {
+                pos: Vec2::new(i16::MAX, 0)
+            })
+        );
+        board.move_block(1, Dir::Left)?;
+        board.move_block(1, Dir::Right)?;
+        assert_eq!(board.blocks()[0].pos(), Vec2::new(width - 1, 0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_holes_connected() -> Result<(), String> {
+        // The hole at the center is surrounded by blocks
+        let separated = "3 4\n\
+        1 2 3 0\n\
+        4 0 5 6\n\
+        7 8 9 10\n\
+        "
+        .parse::<Board>()?;
+        let adjacent = "3 4\n\
+        1 2 3 4\n\
+        5 0 0 6\n\
+        7 8 9 10\n\
+        "
+        .parse::<Board>()?;
+        let full = "1 2\n1 2\n".parse::<Board>()?;
+
+        assert!(!separated.holes_connected());
+        assert!(adjacent.holes_connected());
+        assert!(full.holes_connected());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_analysis_metrics() -> Result<(), String> {
+        let board = "3 4
+\
+        1 1 2 0\n\
+        1 1 3 0\n\
+        4 5 5 -1\n\
+        "
+        .parse::<Board>()?;
+        let histogram = board.shape_histogram();
+        assert_eq!(
+            histogram.into_iter().collect::<Vec<_>>(),
+            [
+                (Vec2::new(1, 1), 3),
+                (Vec2::new(2, 1), 1),
+                (Vec2::new(2, 2), 1)
+            ]
+        );
+        // Only 2R and 3R, other blocks are surrounded
+        assert_eq!(board.branching_factor(), board.possible_moves().len());
+        assert_eq!(board.branching_factor(), 2);
+        assert_eq!(board.hole_count(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_comments() -> Result<(), String> {
+        let plain = "2 3\n1 0 0\n2 0 3\n\n0 0 1\n2 0 3\n".parse::<Board>()?;
+        let commented = "# Provenance: hand-made\n\
+        \n\
+        2 3\n\
+        1 0 0\n\
+        # Block 2 never moves\n\
+        2 0 3\n\
+        # Goal\n\
+        0 0 1\n\
+        \n\
+        2 0 3\n\
+        "
+        .parse::<Board>()?;
+
+        assert_eq!(commented, plain);
+        assert_eq!(commented.goal(), plain.goal());
+        // Rows right after the grid are not a goal
+        assert!(matches!(
+            "2 3\n1 0 0\n2 0 3\n0 0 1\n2 0 3\n".parse::<Board>(),
+            Err(BoardError::Parse { line: 4, .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_string_with_comment() -> Result<(), String> {
+        let board = "2 2\n1 0\n0 0\n".parse::<Board>()?;
+        let output = board.to_string_with_comment("seed 42\nsize 2x2");
+
+        assert_eq!(output, "# seed 42\n# size 2x2\n2 2\n1 0\n0 0\n");
+        assert!(!board.to_string().contains('#'));
+        assert_eq!(output.parse::<Board>()?, board);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_large_block_id() -> Result<(), String> {
+        // 255 blocks on a 16x16 board, the only hole is right of block 200
+        let mut input = "16 16\n".to_string();
+        for row in 0..16 {
+            let line = (0..16)
+                .map(|col| match row * 16 + col {
+                    200 => 0,
+                    i if i < 200 => i + 1,
+                    i => i,
+                })
+                .map(|id: i32| id.to_string())
+                .collect::<Vec<_>>()
+                .join(" ");
+            input.push_str(&line);
+            input.push('\n');
+        }
+        let mut board = input.parse::<Board>()?;
+        let original = board.clone();
+
+        assert_eq!(board.state().blocks().len(), 255);
+        assert!(board.possible_moves().contains(&(200, Dir::Right)));
+        board.push_move(200, Dir::Right)?;
+        assert_eq!(board.state().blocks()[199].pos(), Vec2::new(8, 12));
+        assert_ne!(board.zobrist_hash(), original.zobrist_hash());
+        board.undo();
+        assert_eq!(board, original);
+        assert_eq!(board.to_string().parse::<Board>()?, board);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_long_blocks() -> Result<(), String> {
+        let board = "4 4\n\
+        1 1 1 0\n\
+        2 2 2 3\n\
+        2 2 2 3\n\
+        2 2 2 3\n\
+        "
+        .parse::<Board>()?;
+        let blocks = board.state().blocks();
+
+        assert_eq!(blocks[0].size(), Vec2::new(3, 1));
+        assert_eq!(blocks[1].size(), Vec2::new(3, 3));
+        assert_eq!(blocks[1].pos(), Vec2::new(0, 1));
+        assert_eq!(blocks[2].size(), Vec2::new(1, 3));
+        assert_eq!(blocks[2].pos(), Vec2::new(3, 1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_non_rectangle_block() {
+        assert!("2 2\n1 1\n1 0\n".parse::<Board>().is_err());
+        assert!("1 3\n1 0 1\n".parse::<Board>().is_err());
+    }
+
+    #[test]
+    fn test_move_long_block() -> Result<(), String> {
+        let mut board = "2 4\n\
+        0 1 1 1\n\
+        0 0 0 0\n\
+        "
+        .parse::<Board>()?;
+        assert!(!board.is_goal());
+        assert!(board.move_block(1, Dir::Right).is_err());
+        board.move_block(1, Dir::Left)?;
+
+        assert!(board.is_goal());
+        assert_eq!(board.state().blocks()[0].pos(), Vec2::new(0, 0));
+        board.move_block(1, Dir::Down)?;
+        assert!(board.move_block(1, Dir::Down).is_err());
+        assert_eq!(board.possible_moves().len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_generate_with_shapes() {
+        let shapes = [Vec2::new(3, 1), Vec2::new(1, 3)];
+        let board = Board::generate_with_shapes(Vec2::new(5, 5), 6, 8, &shapes);
+
+        assert!(!board.state().blocks().is_empty());
+        for block in board.state().blocks() {
+            assert!(shapes.contains(&block.size()));
+        }
+    }
+
+    #[test]
+    fn test_generate_with_seed() -> Result<(), String> {
+        let generate = |seed| {
+            Board::generate_with_rng(Vec2::new(6, 6), 16, 32, &mut StdRng::seed_from_u64(seed))
+                .map(|board| board.to_string())
+        };
+
+        assert_eq!(generate(7)?, generate(7)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_generate_full_board() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..32 {
+            let board = Board::generate_with_rng(Vec2::new(4, 4), 16, 0, &mut rng)
+                .expect("16 blocks should fit into a 4x4 board");
+            let ids = board
+                .state()
+                .blocks()
+                .iter()
+                .map(Block::id)
+                .collect::<Vec<_>>();
+
+            assert_eq!(ids, (1..=16).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_generate_exact_block_count() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for block_count in 1..=16 {
+            let board = Board::generate_with_rng(Vec2::new(4, 4), block_count, 0, &mut rng)
+                .expect("Blocks should fit into the board");
+
+            assert_eq!(board.state().blocks().len(), block_count as usize);
+        }
+    }
+
+    #[test]
+    fn test_generate_errors() {
+        let mut rng = StdRng::seed_from_u64(7);
+
+        assert_eq!(
+            Board::generate_with_rng(Vec2::new(0, 4), 4, 0, &mut rng),
+            Err(GenerateError::InvalidSize(Vec2::new(0, 4)))
+        );
+        assert_eq!(
+            Board::generate_with_shapes_and_rng(
+                Vec2::new(4, 4),
+                4,
+                0,
+                &[],
+                ShuffleOptions::default(),
+                &mut rng
+            ),
+            Err(GenerateError::NoBlockSizes)
+        );
+        // A full board cannot leave its goal
+        assert_eq!(
+            Board::generate_with_rng(Vec2::new(4, 4), 16, 8, &mut rng),
+            Err(GenerateError::StuckAtGoal)
+        );
+        assert_eq!(
+            Board::generate_with_rng(Vec2::new(4, 4), 17, 0, &mut rng),
+            Err(GenerateError::TooManyBlocks(17))
+        );
+    }
+
+    #[test]
+    fn test_walls() -> Result<(), String> {
+        let mut board = "2 2\n\
+        -1 0\n\
+        1 0\n\
+        "
+        .parse::<Board>()?;
+
+        assert_eq!(board.hole_count(), 2);
+        assert_eq!(board.possible_moves(), vec![(1, Dir::Right)]);
+        // The wall keeps its cell in the goal, so block 1 is packed after it
+        assert_eq!(board.final_state().blocks()[0].pos(), Vec2::new(1, 0));
+        assert_eq!(board.heuristic(), 2);
+        let e = board
+            .verify_solution(&[(1, Dir::Up)])
+            .map_err(|e| e.to_string());
+        assert_eq!(
+            e,
+            Err("Invalid move 0: Invalid move, Vec2(0, 0) is a wall".to_string())
+        );
+        assert!(board.to_string().contains("-1 0\n1 0"));
+
+        board.move_block(1, Dir::Right)?;
+        board.move_block(1, Dir::Up)?;
+        assert!(board.is_goal());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_goal_walls_must_match() {
+        let initial = "2 2\n-1 0\n1 0\n";
+        assert!(Board::parse_with_goal(initial, "-1 1\n0 0\n").is_ok());
+        assert!(Board::parse_with_goal(initial, "0 1\n0 -1\n").is_err());
+    }
+
+    #[test]
+    fn test_block_cells() -> Result<(), String> {
+        let block = Block::from_positions(
+            1,
+            &[
+                Vec2::new(2, 1),
+                Vec2::new(3, 1),
+                Vec2::new(2, 2),
+                Vec2::new(3, 2),
+            ],
+        )?;
+        let cells = block.cells().collect::<Vec<_>>();
+
+        assert_eq!(
+            cells,
+            vec![
+                Vec2::new(2, 1),
+                Vec2::new(3, 1),
+                Vec2::new(2, 2),
+                Vec2::new(3, 2)
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_accessors_match_grid() {
+        let mut rng = thread_rng();
+        let mut board = Board::generate(Vec2::new(6, 5), 12, 0);
+        for _ in 0..200 {
+            if let Some(&(id, dir)) = board.possible_moves().choose(&mut rng) {
+                let _ = board.move_block(id, dir);
+            }
+            let grid = board.grid();
+            let size = board.size();
+
+            assert_eq!(grid.size(), size);
+            assert_eq!(board.id_grid(), &**grid);
+            let mut covered = 0;
+            for block in board.blocks() {
+                for cell in block.cells() {
+                    assert_eq!(grid.get(cell), Some(&block.id()));
+                    covered += 1;
+                }
+            }
+            for hole in board.holes() {
+                assert_eq!(grid.get(hole), Some(&0));
+            }
+            assert_eq!(
+                covered + board.holes().count(),
+                size.x as usize * size.y as usize
+            );
+        }
+    }
+
+    #[test]
+    fn test_move_count() -> Result<(), String> {
+        let mut board = "2 3\n1 0 0\n2 2 0\n".parse::<Board>()?;
+        assert_eq!(board.move_count(), 0);
+
+        board.move_block(1, Dir::Right)?;
+        assert_eq!(board.move_count(), 1);
+        // Failed moves are not counted
+        assert!(board.move_block(1, Dir::Up).is_err());
+        assert!(board.move_block(2, Dir::Up).is_err());
+        assert!(board.move_block(9, Dir::Left).is_err());
+        assert_eq!(board.move_count(), 1);
+        assert_eq!(board.slide_block(1, Dir::Right)?, 1);
+        assert_eq!(board.move_count(), 2);
+
+        board.push_move(1, Dir::Left)?;
+        assert_eq!(board.move_count(), 3);
+        board.undo();
+        assert_eq!(board.move_count(), 2);
+        board.undo();
+        assert_eq!(board.move_count(), 2);
+
+        board.reset_move_count();
+        assert_eq!(board.move_count(), 0);
+        assert!(board
+            .apply_moves(&[(1, Dir::Left), (2, Dir::Down)])
+            .is_err());
+        assert_eq!(board.move_count(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_undo_after_reset_move_count() -> Result<(), String> {
+        let mut board = "2 3
+1 0 0
+2 2 0
+"
+        .parse::<Board>()?;
+        board.push_move(1, Dir::Right)?;
+        board.reset_move_count();
+
+        assert_eq!(board.undo(), Some((1, Dir::Right)));
+        assert_eq!(board.move_count(), 0);
+        assert_eq!(board.grid, "2 3\n1 0 0\n2 2 0\n".parse::<Board>()?.grid);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_undo_without_history() -> Result<(), String> {
+        let mut board = BoardBuilder::new(Vec2::new(2, 2))
+            .add_block(1, Vec2::new(0, 0), Vec2::new(1, 1))
+            .add_block(2, Vec2::new(1, 1), Vec2::new(1, 1))
+            .build()?;
+        board.move_block(1, Dir::Right)?;
+        let moved = board.clone();
+
+        assert_eq!(board.undo(), None);
+        assert_eq!(board, moved);
 
         Ok(())
     }
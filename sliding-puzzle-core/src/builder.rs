@@ -0,0 +1,195 @@
+use crate::{
+    board::{BlockId, Board},
+    error::BoardError,
+    matrix::Matrix2D,
+    vec2::{Square, Vec2},
+};
+use std::collections::BTreeMap;
+
+/// Build a board from blocks instead of a text grid
+#[derive(Debug, Clone)]
+pub struct BoardBuilder {
+    size: Vec2,
+    /// (id, position, size) of blocks, in the order they are added
+    blocks: Vec<(BlockId, Vec2, Vec2)>,
+    /// Goal positions of blocks, the packed layout is used if empty
+    goal: BTreeMap<BlockId, Vec2>,
+}
+
+impl BoardBuilder {
+    /// Start an empty board of given size, cells without blocks are holes
+    pub fn new(size: Vec2) -> Self {
+        Self {
+            size,
+            blocks: vec![],
+            goal: BTreeMap::new(),
+        }
+    }
+
+    /// Place a block whose top-left cell is at `pos`. Ids must be positive but may skip numbers
+    pub fn add_block(mut self, id: BlockId, pos: Vec2, size: Vec2) -> Self {
+        self.blocks.push((id, pos, size));
+        self
+    }
+
+    /// Set where the top-left cell of a block should be in the goal.
+    /// If it is set for any block, it must be set for all of them
+    pub fn set_goal_position(mut self, id: BlockId, pos: Vec2) -> Self {
+        self.goal.insert(id, pos);
+        self
+    }
+
+    /// Validate the blocks and build the board
+    pub fn build(&self) -> Result<Board, BoardError> {
+        let mut sizes = BTreeMap::new();
+        for &(id, _, size) in &self.blocks {
+            if id <= 0 {
+                return Err(BoardError::InvalidId(id));
+            }
+            if sizes.insert(id, size).is_some() {
+                return Err(BoardError::DuplicateId(id));
+            }
+        }
+        let grid = self.fill(self.blocks.iter().copied())?;
+        if self.goal.is_empty() {
+            return Board::try_from(grid);
+        }
+
+        if let Some(&id) = self.goal.keys().find(|id| !sizes.contains_key(id)) {
+            return Err(BoardError::UnknownGoalId(id));
+        }
+        if let Some(&id) = sizes.keys().find(|id| !self.goal.contains_key(id)) {
+            return Err(BoardError::MissingGoal(id));
+        }
+        let goal = self.fill(self.goal.iter().map(|(&id, &pos)| (id, pos, sizes[&id])))?;
+        Board::with_goal(grid, goal)
+    }
+
+    /// Fill blocks given as (id, position, size) into an empty grid
+    fn fill(
+        &self,
+        blocks: impl Iterator<Item = (BlockId, Vec2, Vec2)>,
+    ) -> Result<Matrix2D<BlockId>, BoardError> {
+        let mut grid = Matrix2D::fill(self.size, 0);
+        for (id, pos, size) in blocks {
+            let cells = Square::new(pos, size);
+            for cell in cells.row_iter() {
+                match grid.get(cell) {
+                    None => return Err(BoardError::OutOfBounds(id)),
+                    Some(&0) => {}
+                    Some(&with) => {
+                        return Err(BoardError::Overlap {
+                            pos: cell,
+                            id,
+                            with,
+                        })
+                    }
+                }
+            }
+            grid.try_fill(pos, size, id)
+                .map_err(|_| BoardError::OutOfBounds(id))?;
+        }
+        Ok(grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_matches_parsed() -> Result<(), String> {
+        let parsed = "3 3\n\
+        1 1 2\n\
+        0 3 0\n\
+        0 4 4\n\
+        "
+        .parse::<Board>()?;
+        let built = BoardBuilder::new(Vec2::new(3, 3))
+            .add_block(1, Vec2::new(0, 0), Vec2::new(2, 1))
+            .add_block(2, Vec2::new(2, 0), Vec2::new(1, 1))
+            .add_block(3, Vec2::new(1, 1), Vec2::new(1, 1))
+            .add_block(4, Vec2::new(1, 2), Vec2::new(2, 1))
+            .build()?;
+
+        assert_eq!(built, parsed);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_with_goal_matches_parsed() -> Result<(), String> {
+        let parsed = "2 3\n\
+        1 0 2\n\
+        0 0 0\n\
+        \n\
+        0 0 0\n\
+        2 0 1\n\
+        "
+        .parse::<Board>()?;
+        let built = BoardBuilder::new(Vec2::new(3, 2))
+            .add_block(1, Vec2::new(0, 0), Vec2::new(1, 1))
+            .add_block(2, Vec2::new(2, 0), Vec2::new(1, 1))
+            .set_goal_position(1, Vec2::new(2, 1))
+            .set_goal_position(2, Vec2::new(0, 1))
+            .build()?;
+
+        assert_eq!(built, parsed);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_errors() {
+        let builder = BoardBuilder::new(Vec2::new(3, 2));
+        let one = Vec2::new(1, 1);
+
+        assert_eq!(
+            builder.clone().add_block(0, Vec2::new(0, 0), one).build(),
+            Err(BoardError::InvalidId(0))
+        );
+        assert_eq!(
+            builder
+                .clone()
+                .add_block(1, Vec2::new(0, 0), one)
+                .add_block(1, Vec2::new(1, 0), one)
+                .build(),
+            Err(BoardError::DuplicateId(1))
+        );
+        assert_eq!(
+            builder
+                .clone()
+                .add_block(1, Vec2::new(2, 0), Vec2::new(2, 1))
+                .build(),
+            Err(BoardError::OutOfBounds(1))
+        );
+        assert_eq!(
+            builder
+                .clone()
+                .add_block(1, Vec2::new(0, 0), Vec2::new(2, 1))
+                .add_block(2, Vec2::new(1, 0), one)
+                .build(),
+            Err(BoardError::Overlap {
+                pos: Vec2::new(1, 0),
+                id: 2,
+                with: 1
+            })
+        );
+        assert_eq!(
+            builder
+                .clone()
+                .add_block(1, Vec2::new(0, 0), one)
+                .set_goal_position(2, Vec2::new(0, 0))
+                .build(),
+            Err(BoardError::UnknownGoalId(2))
+        );
+        assert_eq!(
+            builder
+                .add_block(1, Vec2::new(0, 0), one)
+                .add_block(2, Vec2::new(1, 0), one)
+                .set_goal_position(1, Vec2::new(0, 1))
+                .build(),
+            Err(BoardError::MissingGoal(2))
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use crate::{
+    board::{BlockId, WALL},
+    vec2::Vec2,
+};
+use std::fmt::Display;
+
+/// Why building, parsing or moving a board fails
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardError {
+    /// Block ids must be positive
+    InvalidId(BlockId),
+    /// The id is added more than once
+    DuplicateId(BlockId),
+    /// The block does not fit inside the board
+    OutOfBounds(BlockId),
+    /// Two blocks cover the same cell
+    Overlap {
+        /// The covered cell
+        pos: Vec2,
+        /// Id of the block added later
+        id: BlockId,
+        /// Id of the block already covering that cell
+        with: BlockId,
+    },
+    /// A goal position is set for an id which is not added
+    UnknownGoalId(BlockId),
+    /// Goal positions are set for some blocks but not this one
+    MissingGoal(BlockId),
+    /// The cell is outside the board
+    OutOfRange {
+        /// The cell outside the board
+        pos: Vec2,
+    },
+    /// A move would overlap another block or a wall
+    Occupied {
+        /// The overlapped cell
+        pos: Vec2,
+        /// Id of the block occupying that cell
+        by: BlockId,
+    },
+    /// A filled area covers a cell which is already filled
+    Covered {
+        /// The covered cell
+        pos: Vec2,
+    },
+    /// No block has this id
+    UnknownBlock { id: BlockId },
+    /// Cells of the block cannot form a rectangle
+    InvalidBlockShape { id: BlockId },
+    /// The input text is malformed
+    Parse {
+        /// 1-based line number, the size line is line 1
+        line: usize,
+        msg: String,
+    },
+    /// The input text has fewer rows than its size line declares
+    RowCount {
+        expected_rows: usize,
+        found_rows: usize,
+    },
+    /// The text cannot be parsed as a [`Vec2`]
+    InvalidVec2 { input: String, msg: String },
+    /// The number of cells does not match the size
+    SizeMismatch { expected: usize, got: usize },
+    /// Blocks cannot be packed into a goal layout on a board of this size
+    CannotPack { size: Vec2 },
+    /// The goal does not fit the board, e.g. its size or block shapes differ
+    InvalidGoal(String),
+    /// Two boards cannot be compared since their sizes or block shapes differ
+    Incompatible(String),
+    /// The board is inconsistent in another way
+    Invalid(String),
+}
+
+impl Display for BoardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BoardError::InvalidId(id) => write!(f, "Invalid block id {}", id),
+            BoardError::DuplicateId(id) => write!(f, "Block {} is added twice", id),
+            BoardError::OutOfBounds(id) => write!(f, "Block {} is out of the board", id),
+            BoardError::Overlap { pos, id, with } => {
+                write!(f, "Block {} overlaps block {} at {}", id, with, pos)
+            }
+            BoardError::UnknownGoalId(id) => write!(f, "Goal of unknown block {}", id),
+            BoardError::MissingGoal(id) => write!(f, "Block {} has no goal position", id),
+            BoardError::OutOfRange { pos } => write!(f, "{} is out of range", pos),
+            BoardError::Occupied { pos, by: WALL } => {
+                write!(f, "Invalid move, {} is a wall", pos)
+            }
+            BoardError::Occupied { pos, by } => {
+                write!(f, "Invalid move, {} has occupied by {}", pos, by)
+            }
+            BoardError::Covered { pos } => write!(f, "{} has already been filled", pos),
+            BoardError::UnknownBlock { id } => write!(f, "id {} not found", id),
+            BoardError::InvalidBlockShape { id } => {
+                write!(f, "Positions of block {} cannot form a rectangle", id)
+            }
+            BoardError::Parse { line, msg } => write!(f, "Invalid line {}: {}", line, msg),
+            BoardError::RowCount {
+                expected_rows,
+                found_rows,
+            } => write!(f, "Expect {} rows, found {}", expected_rows, found_rows),
+            BoardError::InvalidVec2 { input, msg } => {
+                write!(f, "Invalid vector {:?}: {}", input, msg)
+            }
+            BoardError::SizeMismatch { expected, got } => {
+                write!(f, "Invalid cell count. expect {}, got {}", expected, got)
+            }
+            BoardError::CannotPack { size } => write!(
+                f,
+                "Cannot fit those blocks into board with size {}x{}",
+                size.y, size.x
+            ),
+            BoardError::InvalidGoal(reason) => write!(f, "Invalid goal: {}", reason),
+            BoardError::Incompatible(reason) => write!(f, "Incompatible boards: {}", reason),
+            BoardError::Invalid(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl std::error::Error for BoardError {}
+
+impl From<BoardError> for String {
+    fn from(e: BoardError) -> Self {
+        e.to_string()
+    }
+}
@@ -0,0 +1,122 @@
+use crate::vec2::Vec2;
+
+/// Set of hole positions on a board, stored as a bitset keyed by row-major cell index
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct HoleSet {
+    /// Width of the board, used to convert between positions and indices
+    width: i16,
+    bits: Vec<u64>,
+    len: usize,
+}
+
+impl HoleSet {
+    /// Create an empty set for a board of given size
+    pub fn new(size: Vec2) -> Self {
+        let cell_count = size.x.max(0) as usize * size.y.max(0) as usize;
+        Self {
+            width: size.x,
+            bits: vec![0; cell_count.div_ceil(64)],
+            len: 0,
+        }
+    }
+
+    fn index(&self, pos: Vec2) -> usize {
+        debug_assert!(pos.x >= 0 && pos.x < self.width && pos.y >= 0);
+        pos.y as usize * self.width as usize + pos.x as usize
+    }
+
+    /// Add a position, return whether it was not in the set
+    pub fn insert(&mut self, pos: Vec2) -> bool {
+        let index = self.index(pos);
+        let (word, mask) = (index / 64, 1 << (index % 64));
+        let is_new = self.bits[word] & mask == 0;
+        self.bits[word] |= mask;
+        self.len += is_new as usize;
+        is_new
+    }
+
+    /// Remove a position, return whether it was in the set
+    pub fn remove(&mut self, pos: Vec2) -> bool {
+        let index = self.index(pos);
+        let (word, mask) = (index / 64, 1 << (index % 64));
+        let was_present = self.bits[word] & mask != 0;
+        self.bits[word] &= !mask;
+        self.len -= was_present as usize;
+        was_present
+    }
+
+    /// Check whether a position is in the set
+    pub fn contains(&self, pos: Vec2) -> bool {
+        let index = self.index(pos);
+        self.bits
+            .get(index / 64)
+            .is_some_and(|word| word & (1 << (index % 64)) != 0)
+    }
+
+    /// Number of positions in the set
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Iterate over positions in row-major order
+    pub fn iter(&self) -> impl Iterator<Item = Vec2> + '_ {
+        let width = self.width as usize;
+        self.bits
+            .iter()
+            .enumerate()
+            .flat_map(move |(word_index, &word)| {
+                let mut remain = word;
+                std::iter::from_fn(move || {
+                    if remain == 0 {
+                        return None;
+                    }
+                    let bit = remain.trailing_zeros() as usize;
+                    remain &= remain - 1;
+                    let index = word_index * 64 + bit;
+                    Some(Vec2::new((index % width) as i16, (index / width) as i16))
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_remove() {
+        let mut holes = HoleSet::new(Vec2::new(9, 9));
+        assert!(holes.insert(Vec2::new(8, 8)));
+        assert!(holes.insert(Vec2::new(1, 0)));
+        assert!(!holes.insert(Vec2::new(1, 0)));
+        assert_eq!(holes.len(), 2);
+        assert!(holes.contains(Vec2::new(8, 8)));
+        assert!(!holes.contains(Vec2::new(0, 1)));
+
+        assert!(holes.remove(Vec2::new(8, 8)));
+        assert!(!holes.remove(Vec2::new(8, 8)));
+        assert_eq!(holes.len(), 1);
+        assert!(!holes.contains(Vec2::new(8, 8)));
+    }
+
+    #[test]
+    fn test_iter_in_row_major_order() {
+        let mut holes = HoleSet::new(Vec2::new(10, 10));
+        let positions = [
+            Vec2::new(9, 9),
+            Vec2::new(3, 6),
+            Vec2::new(0, 0),
+            Vec2::new(4, 6),
+        ];
+        for pos in positions {
+            holes.insert(pos);
+        }
+        let mut expected = positions.to_vec();
+        expected.sort_by_key(|pos| (pos.y, pos.x));
+        assert_eq!(holes.iter().collect::<Vec<_>>(), expected);
+    }
+}
@@ -1,7 +1,19 @@
 mod board;
+mod builder;
+mod error;
+mod hole_set;
 mod matrix;
+pub mod render;
 mod vec2;
+mod zobrist;
 
-pub use board::{Board, BoardState, Dir, Move};
-pub use matrix::Matrix2D;
+pub use board::{
+    format_move, ApplyError, Block, BlockDiff, BlockId, Board, BoardState, CanonicalState, Dir,
+    GenerateError, Move, PackedState, ShuffleOptions, Solvability, VerifyError, VerifyReport, WALL,
+};
+pub use builder::BoardBuilder;
+pub use error::BoardError;
+pub use hole_set::HoleSet;
+pub use matrix::{Matrix2D, MatrixView};
+pub use render::RenderStyle;
 pub use vec2::Vec2;
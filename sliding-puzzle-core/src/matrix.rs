@@ -1,20 +1,54 @@
-use crate::vec2::{Square, Vec2};
+use crate::{
+    error::BoardError,
+    vec2::{Square, Vec2},
+};
 use std::{
-    fmt::Debug,
-    ops::{Deref, DerefMut},
+    collections::HashSet,
+    fmt::{Debug, Display},
+    ops::{Deref, DerefMut, Index, IndexMut},
     str::FromStr,
 };
 
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "MatrixData<T>")
+)]
 pub struct Matrix2D<T> {
     store: Vec<T>,
     size: Vec2,
 }
 
+/// Unchecked fields of [`Matrix2D`], validated by [`Matrix2D::from_vec`] when deserializing
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct MatrixData<T> {
+    store: Vec<T>,
+    size: Vec2,
+}
+
+#[cfg(feature = "serde")]
+impl<T> TryFrom<MatrixData<T>> for Matrix2D<T> {
+    type Error = BoardError;
+
+    fn try_from(data: MatrixData<T>) -> Result<Self, Self::Error> {
+        Self::from_vec(data.size, data.store)
+    }
+}
+
 impl<T> Matrix2D<T>
 where
     T: Clone,
 {
+    /// Get the matrix flipped over its diagonal, so the size is swapped
+    pub fn transposed(&self) -> Self {
+        Self {
+            size: Vec2::new(self.size.y, self.size.x),
+            store: self.columns().flatten().cloned().collect(),
+        }
+    }
+
     /// Create a matrix filled with given value
     pub fn fill(size: Vec2, value: T) -> Self {
         Self {
@@ -24,15 +58,15 @@ where
     }
 
     /// Try fill given area with given value, return error if the area is out of range
-    pub fn try_fill(&mut self, anchor: Vec2, size: Vec2, value: T) -> Result<(), String> {
+    pub fn try_fill(&mut self, anchor: Vec2, size: Vec2, value: T) -> Result<(), BoardError> {
         let square = Square::new(anchor, size);
         // Check the fillin area is not out of range
-        if square.row_iter().any(|pos| self.get(pos).is_none()) {
-            return Err("Fill area out of range".to_string());
+        if let Some(pos) = square.row_iter().find(|pos| self.get(*pos).is_none()) {
+            return Err(BoardError::OutOfRange { pos });
         }
         // Fillin
         for pos in square.row_iter() {
-            *self.get_mut(pos).unwrap() = value.clone();
+            self[pos] = value.clone();
         }
 
         Ok(())
@@ -49,21 +83,19 @@ where
         anchor: Vec2,
         size: Vec2,
         value: T,
-    ) -> Result<(), String> {
+    ) -> Result<(), BoardError> {
         let square = Square::new(anchor, size);
         // Check there is not overwriting
         for pos in square.row_iter() {
             match self.get(pos) {
-                Some(value) if value != &T::default() => {
-                    return Err("Fill area covers non-default value".to_string())
-                }
-                None => return Err("Fill area out of range".to_string()),
+                Some(value) if value != &T::default() => return Err(BoardError::Covered { pos }),
+                None => return Err(BoardError::OutOfRange { pos }),
                 _ => {}
             }
         }
         // Fillin
         for pos in square.row_iter() {
-            *self.get_mut(pos).unwrap() = value.clone();
+            self[pos] = value.clone();
         }
 
         Ok(())
@@ -81,6 +113,117 @@ impl<T> Matrix2D<T> {
         pos.x >= 0 && pos.x < self.size.x && pos.y >= 0 && pos.y < self.size.y
     }
 
+    /// Iterate over positions of cells in row-major order
+    pub fn positions(&self) -> impl Iterator<Item = Vec2> {
+        let size = self.size;
+        (0..size.y).flat_map(move |y| (0..size.x).map(move |x| Vec2::new(x, y)))
+    }
+
+    /// Iterate over cells with their positions in row-major order
+    pub fn iter_positions(&self) -> impl Iterator<Item = (Vec2, &T)> {
+        self.positions().zip(self.store.iter())
+    }
+
+    /// Iterate over mutable cells with their positions in row-major order
+    pub fn iter_positions_mut(&mut self) -> impl Iterator<Item = (Vec2, &mut T)> {
+        self.positions().zip(self.store.iter_mut())
+    }
+
+    /// Iterate over rows from top to bottom
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.store.chunks(self.size.x.max(1) as usize)
+    }
+
+    /// Iterate over cells of column `x` from top to bottom, which is empty if `x` is out of range
+    pub fn column(&self, x: i16) -> impl Iterator<Item = &T> {
+        let width = self.size.x as usize;
+        let cells = if (0..self.size.x).contains(&x) {
+            &self.store[x as usize..]
+        } else {
+            &[]
+        };
+        cells.iter().step_by(width.max(1))
+    }
+
+    /// Iterate over columns from left to right
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.size.x).map(move |x| self.column(x))
+    }
+
+    /// Get positions connected to `start` horizontally or vertically through cells matching
+    /// `predicate`, including `start` itself. Empty if `start` is outside or does not match
+    pub fn flood_fill(&self, start: Vec2, predicate: impl Fn(&T) -> bool) -> HashSet<Vec2> {
+        let mut region = HashSet::new();
+        let mut stack = vec![start];
+        while let Some(pos) = stack.pop() {
+            if region.contains(&pos) || !self.get(pos).is_some_and(&predicate) {
+                continue;
+            }
+            region.insert(pos);
+            for offset in [(0, -1), (0, 1), (-1, 0), (1, 0)] {
+                stack.push(Vec2::new(pos.x + offset.0, pos.y + offset.1));
+            }
+        }
+        region
+    }
+
+    /// Split cells matching `predicate` into connected regions, see [`Matrix2D::flood_fill`].
+    /// Regions are ordered by their first cell in row-major order
+    pub fn components(&self, predicate: impl Fn(&T) -> bool) -> Vec<HashSet<Vec2>> {
+        let mut components: Vec<HashSet<Vec2>> = vec![];
+        for (pos, value) in self.iter_positions() {
+            if predicate(value) && components.iter().all(|region| !region.contains(&pos)) {
+                components.push(self.flood_fill(pos, &predicate));
+            }
+        }
+        components
+    }
+
+    /// Borrow a rectangular region at `anchor`, `None` if it does not fit inside the matrix
+    pub fn view(&self, anchor: Vec2, size: Vec2) -> Option<MatrixView<'_, T>> {
+        let corner = anchor + size - Vec2::new(1, 1);
+        let fits = size.x > 0 && size.y > 0 && self.is_inside(&anchor) && self.is_inside(&corner);
+        fits.then_some(MatrixView {
+            matrix: self,
+            anchor,
+            size,
+        })
+    }
+
+    /// Get a cell without bounds checking, see [`Matrix2D::get`] for the safe version.
+    ///
+    /// # Safety
+    ///
+    /// `pos` must be inside the matrix
+    pub unsafe fn get_unchecked(&self, pos: Vec2) -> &T {
+        debug_assert!(self.is_inside(&pos), "{} is out of range", pos);
+        self.store
+            .get_unchecked(pos.y as usize * self.size.x as usize + pos.x as usize)
+    }
+
+    /// Get a mutable cell without bounds checking, see [`Matrix2D::get_mut`] for the safe version.
+    ///
+    /// # Safety
+    ///
+    /// `pos` must be inside the matrix
+    pub unsafe fn get_unchecked_mut(&mut self, pos: Vec2) -> &mut T {
+        debug_assert!(self.is_inside(&pos), "{} is out of range", pos);
+        self.store
+            .get_unchecked_mut(pos.y as usize * self.size.x as usize + pos.x as usize)
+    }
+
+    /// Swap two cells, fail if either of them is outside the matrix
+    pub fn swap(&mut self, a: Vec2, b: Vec2) -> Result<(), BoardError> {
+        for pos in [a, b] {
+            if !self.is_inside(&pos) {
+                return Err(BoardError::OutOfRange { pos });
+            }
+        }
+        let (a, b) = (self.index_of(a), self.index_of(b));
+        self.store.swap(a, b);
+        Ok(())
+    }
+
     /// Get a immutable reference of cell
     pub fn get(&self, pos: Vec2) -> Option<&T> {
         if !self.is_inside(&pos) {
@@ -100,35 +243,153 @@ impl<T> Matrix2D<T> {
     }
 
     /// Create matrix from given vector
-    pub fn from_vec(size: Vec2, vec: Vec<T>) -> Result<Self, String> {
+    pub fn from_vec(size: Vec2, vec: Vec<T>) -> Result<Self, BoardError> {
         let expect_size = size.x as usize * size.y as usize;
         if expect_size != vec.len() {
-            return Err(format!(
-                "Invalid vector size. expect {}, got {}",
-                expect_size,
-                vec.len()
-            ));
+            return Err(BoardError::SizeMismatch {
+                expected: expect_size,
+                got: vec.len(),
+            });
         }
 
         Ok(Self { size, store: vec })
     }
 
-    fn parse_size(line: &str) -> Result<Vec2, String> {
+    fn parse_size(line_number: usize, line: &str) -> Result<Vec2, BoardError> {
+        let size_error = |msg: String| BoardError::Parse {
+            line: line_number,
+            msg,
+        };
         let size = line.split_whitespace().collect::<Vec<_>>();
         if size.len() != 2 {
-            return Err("First line should be the board row & column size".to_string());
+            return Err(size_error(
+                "First line should be the board row & column size".to_string(),
+            ));
         }
         let size = size
             .into_iter()
             .map(|s| {
-                s.parse::<i8>()
-                    .map_err(|e| format!("Failed to parse size: {}", e))
+                s.parse::<i16>()
+                    .map_err(|e| size_error(format!("Failed to parse size: {}", e)))
             })
             .collect::<Result<Vec<_>, _>>()?;
         Ok(Vec2::new(size[1], size[0]))
     }
 }
 
+/// Read-only rectangular region of a [`Matrix2D`], see [`Matrix2D::view`].
+/// Positions are local to the region, so its top-left cell is at (0, 0)
+#[derive(Debug, Clone, Copy)]
+pub struct MatrixView<'a, T> {
+    matrix: &'a Matrix2D<T>,
+    anchor: Vec2,
+    size: Vec2,
+}
+
+impl<'a, T> MatrixView<'a, T> {
+    /// Size of the region
+    pub fn size(&self) -> Vec2 {
+        self.size
+    }
+
+    /// Get a cell by its local position
+    pub fn get(&self, pos: Vec2) -> Option<&'a T> {
+        if pos.x < 0 || pos.x >= self.size.x || pos.y < 0 || pos.y >= self.size.y {
+            return None;
+        }
+        self.matrix.get(self.anchor + pos)
+    }
+
+    /// Iterate over cells with their local positions in row-major order
+    pub fn iter_positions(&self) -> impl Iterator<Item = (Vec2, &'a T)> {
+        let (matrix, anchor, size) = (self.matrix, self.anchor, self.size);
+        (0..size.y).flat_map(move |y| {
+            (0..size.x).map(move |x| {
+                let pos = Vec2::new(x, y);
+                (pos, &matrix[anchor + pos])
+            })
+        })
+    }
+}
+
+impl<'a, T> MatrixView<'a, T>
+where
+    T: Clone,
+{
+    /// Copy the region into a new matrix
+    pub fn to_owned(&self) -> Matrix2D<T> {
+        Matrix2D {
+            size: self.size,
+            store: self.iter_positions().map(|(_, v)| v.clone()).collect(),
+        }
+    }
+}
+
+impl<T> Matrix2D<T>
+where
+    T: Clone,
+{
+    /// Copy a rectangular region at `anchor` into a new matrix, see [`Matrix2D::view`]
+    pub fn copy_region(&self, anchor: Vec2, size: Vec2) -> Option<Matrix2D<T>> {
+        self.view(anchor, size).map(|view| view.to_owned())
+    }
+}
+
+impl<T> Matrix2D<T>
+where
+    T: PartialEq,
+{
+    /// Find the first position of `value` in row-major order
+    pub fn find(&self, value: &T) -> Option<Vec2> {
+        self.iter_positions()
+            .find(|(_, cell)| *cell == value)
+            .map(|(pos, _)| pos)
+    }
+
+    /// Find all positions of `value` in row-major order
+    pub fn find_all(&self, value: &T) -> Vec<Vec2> {
+        self.iter_positions()
+            .filter(|(_, cell)| *cell == value)
+            .map(|(pos, _)| pos)
+            .collect()
+    }
+
+    /// Count cells equal to `value`
+    pub fn count(&self, value: &T) -> usize {
+        self.store.iter().filter(|cell| *cell == value).count()
+    }
+}
+
+impl<T> Matrix2D<T> {
+    /// Row-major index of a position, panic if it is outside the matrix
+    fn index_of(&self, pos: Vec2) -> usize {
+        if !self.is_inside(&pos) {
+            panic!(
+                "Position {} is out of range for matrix of size {}x{}",
+                pos, self.size.y, self.size.x
+            );
+        }
+        pos.y as usize * self.size.x as usize + pos.x as usize
+    }
+}
+
+impl<T> Index<Vec2> for Matrix2D<T> {
+    type Output = T;
+
+    /// Get a cell, see [`Matrix2D::get`] for the fallible version
+    fn index(&self, pos: Vec2) -> &Self::Output {
+        &self.store[self.index_of(pos)]
+    }
+}
+
+impl<T> IndexMut<Vec2> for Matrix2D<T> {
+    /// Get a mutable cell, see [`Matrix2D::get_mut`] for the fallible version
+    fn index_mut(&mut self, pos: Vec2) -> &mut Self::Output {
+        let index = self.index_of(pos);
+        &mut self.store[index]
+    }
+}
+
 impl<T> Deref for Matrix2D<T> {
     type Target = [T];
 
@@ -143,49 +404,132 @@ impl<T> DerefMut for Matrix2D<T> {
     }
 }
 
+impl<T> Matrix2D<T>
+where
+    T: Display,
+{
+    /// Rows on separate lines with cells separated by a single space, without alignment
+    pub fn to_grid_string(&self) -> String {
+        let mut output = String::new();
+        for row in self.rows() {
+            let row = row
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(" ");
+            output.push_str(&row);
+            output.push('\n');
+        }
+        output
+    }
+}
+
+impl<T> Display for Matrix2D<T>
+where
+    T: Display,
+{
+    /// Rows on separate lines, cells are right-aligned to the widest one
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cells = self.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        let width = cells.iter().map(String::len).max().unwrap_or(0);
+        for row in cells.chunks(self.size.x.max(1) as usize) {
+            let row = row
+                .iter()
+                .map(|cell| format!("{:>width$}", cell, width = width))
+                .collect::<Vec<_>>()
+                .join(" ");
+            writeln!(f, "{}", row)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether a line of board text is neither blank nor a `#` comment
+pub(crate) fn is_content_line(line: &str) -> bool {
+    let line = line.trim();
+    !line.is_empty() && !line.starts_with('#')
+}
+
+/// Lines of board text with their 1-based line numbers, blank lines and comments are skipped
+fn content_lines(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line))
+        .filter(|(_, line)| is_content_line(line))
+}
+
 impl<T> FromStr for Matrix2D<T>
 where
     T: FromStr,
     <T as FromStr>::Err: Debug,
 {
-    type Err = String;
+    type Err = BoardError;
 
+    /// Parse a size line of row & column count followed by rows.
+    /// Blank lines and comment lines starting with `#` are skipped anywhere
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let mut input = input.lines();
-        let line = input
-            .next()
-            .ok_or_else(|| "Missing first line".to_string())?;
-        let size = Self::parse_size(line)?;
+        let mut input = content_lines(input);
+        let (size_line, line) = input.next().ok_or_else(|| BoardError::Parse {
+            line: 1,
+            msg: "Missing first line".to_string(),
+        })?;
+        let size = Self::parse_size(size_line, line)?;
 
         if size.x <= 0 || size.y <= 0 {
-            return Err("Either row or column size should >= 0".to_string());
+            return Err(BoardError::Parse {
+                line: size_line,
+                msg: "Either row or column size should >= 0".to_string(),
+            });
         }
 
-        let mut id_grid = Vec::with_capacity(size.x as usize * size.y as usize);
-        for (row_i, line) in input.into_iter().take(size.y as usize).enumerate() {
+        // The size line is unchecked, so cells are only allocated for what is actually read.
+        // Sizes larger than that are rejected by the row checks below
+        let lines = input.by_ref().take(size.y as usize).collect::<Vec<_>>();
+        let found_rows = lines.len();
+        let tokens = lines
+            .iter()
+            .map(|(_, line)| line.split_whitespace().count())
+            .sum::<usize>();
+        let mut id_grid = Vec::with_capacity(tokens.min(size.x as usize * size.y as usize));
+        for (line_number, line) in lines {
             let row = line
                 .split_whitespace()
                 .map(|v| {
-                    v.parse::<T>()
-                        .map_err(|e| format!("Failed to parse block id: {:?}", e))
+                    v.parse::<T>().map_err(|e| BoardError::Parse {
+                        line: line_number,
+                        msg: format!("Failed to parse block id: {:?}", e),
+                    })
                 })
                 .collect::<Result<Vec<_>, _>>()?;
             if row.len() != size.x as usize {
-                return Err(format!(
-                    "Invalid line {}: expect {} block, got {}",
-                    row_i,
-                    size.x,
-                    row.len(),
-                ));
+                return Err(BoardError::Parse {
+                    line: line_number,
+                    msg: format!("expect {} block, got {}", size.x, row.len()),
+                });
             }
             id_grid.extend(row);
         }
+        if found_rows < size.y as usize {
+            return Err(BoardError::RowCount {
+                expected_rows: size.y as usize,
+                found_rows,
+            });
+        }
+        if let Some((line_number, _)) = input.next() {
+            return Err(BoardError::Parse {
+                line: line_number,
+                msg: format!("Expect {} rows, found more", size.y),
+            });
+        }
         Matrix2D::from_vec(size, id_grid)
     }
 }
 
+#[cfg(test)]
 mod tests {
-    use crate::{matrix::Matrix2D, vec2::Vec2};
+    use crate::{error::BoardError, matrix::Matrix2D, vec2::Vec2};
+    use std::collections::HashSet;
 
     #[test]
     fn test_eq() {
@@ -221,6 +565,352 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_index() {
+        // 3 columns and 2 rows
+        let mut mat = Matrix2D::from_vec(Vec2::new(3, 2), vec![0, 1, 2, 3, 4, 5]).unwrap();
+
+        assert_eq!(mat[Vec2::new(2, 0)], 2);
+        assert_eq!(mat[Vec2::new(0, 1)], 3);
+        assert_eq!(mat[Vec2::new(2, 1)], 5);
+        mat[Vec2::new(1, 1)] = 9;
+        assert_eq!(mat.get(Vec2::new(1, 1)), Some(&9));
+        assert_eq!(mat.to_vec(), vec![0, 1, 2, 3, 9, 5]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Position Vec2(0, 2) is out of range for matrix of size 2x3")]
+    fn test_index_out_of_range() {
+        let mat = Matrix2D::fill(Vec2::new(3, 2), 0);
+        let _ = mat[Vec2::new(0, 2)];
+    }
+
+    #[test]
+    #[should_panic(expected = "Position Vec2(-1, 0) is out of range for matrix of size 2x3")]
+    fn test_index_mut_out_of_range() {
+        let mut mat = Matrix2D::fill(Vec2::new(3, 2), 0);
+        mat[Vec2::new(-1, 0)] = 1;
+    }
+
+    #[test]
+    fn test_iter_positions() {
+        let mut mat = Matrix2D::from_vec(Vec2::new(3, 2), vec![0, 1, 2, 3, 4, 5]).unwrap();
+        let positions = mat.positions().collect::<Vec<_>>();
+
+        assert_eq!(
+            positions,
+            vec![
+                Vec2::new(0, 0),
+                Vec2::new(1, 0),
+                Vec2::new(2, 0),
+                Vec2::new(0, 1),
+                Vec2::new(1, 1),
+                Vec2::new(2, 1),
+            ]
+        );
+        // Same order as rows of the grid, which is sorted by (y, x)
+        assert!(positions
+            .windows(2)
+            .all(|w| (w[0].y, w[0].x) < (w[1].y, w[1].x)));
+        for (pos, value) in mat.iter_positions() {
+            assert_eq!(mat[pos], *value);
+        }
+        for (pos, value) in mat.iter_positions_mut() {
+            *value = pos.x * 10 + pos.y;
+        }
+        assert_eq!(mat.to_vec(), vec![0, 10, 20, 1, 11, 21]);
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        // 3 columns and 2 rows
+        let mat = Matrix2D::from_vec(Vec2::new(3, 2), vec![1, 2, 3, 4, 5, 6]).unwrap();
+
+        assert_eq!(mat.rows().collect::<Vec<_>>(), vec![&[1, 2, 3], &[4, 5, 6]]);
+        assert_eq!(mat.column(0).collect::<Vec<_>>(), vec![&1, &4]);
+        assert_eq!(mat.column(2).collect::<Vec<_>>(), vec![&3, &6]);
+        assert_eq!(mat.column(3).count(), 0);
+        assert_eq!(mat.column(-1).count(), 0);
+        assert_eq!(
+            mat.columns()
+                .map(|column| column.copied().collect::<Vec<_>>())
+                .collect::<Vec<_>>(),
+            vec![vec![1, 4], vec![2, 5], vec![3, 6]]
+        );
+    }
+
+    #[test]
+    fn test_transposed() {
+        let mat = Matrix2D::from_vec(Vec2::new(3, 2), vec![1, 2, 3, 4, 5, 6]).unwrap();
+        let transposed = mat.transposed();
+
+        assert_eq!(transposed.size(), Vec2::new(2, 3));
+        assert_eq!(transposed.to_vec(), vec![1, 4, 2, 5, 3, 6]);
+        for pos in mat.positions() {
+            assert_eq!(mat[pos], transposed[Vec2::new(pos.y, pos.x)]);
+        }
+        assert_eq!(transposed.transposed(), mat);
+    }
+
+    #[test]
+    fn test_display() {
+        let mat = Matrix2D::from_vec(Vec2::new(3, 2), vec![1, 12, 0, -1, 3, 12]).unwrap();
+
+        assert_eq!(mat.to_string(), " 1 12  0\n-1  3 12\n");
+        assert_eq!(mat.to_grid_string(), "1 12 0\n-1 3 12\n");
+        let single_digit = Matrix2D::from_vec(Vec2::new(2, 1), vec![1, 2]).unwrap();
+        assert_eq!(single_digit.to_string(), "1 2\n");
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        let mat = Matrix2D::from_vec(
+            Vec2::new(4, 3),
+            vec![
+                0, 0, 1, 0, //
+                1, 0, 1, 0, //
+                0, 1, 1, 0, //
+            ],
+        )
+        .unwrap();
+        let is_zero = |v: &i32| *v == 0;
+
+        let region = mat.flood_fill(Vec2::new(0, 0), is_zero);
+        assert_eq!(
+            region,
+            HashSet::from([Vec2::new(0, 0), Vec2::new(1, 0), Vec2::new(1, 1)])
+        );
+        assert!(mat.flood_fill(Vec2::new(2, 0), is_zero).is_empty());
+        assert!(mat.flood_fill(Vec2::new(4, 0), is_zero).is_empty());
+
+        let components = mat.components(is_zero);
+        assert_eq!(
+            components.iter().map(HashSet::len).collect::<Vec<_>>(),
+            vec![3, 3, 1]
+        );
+        assert!(components[1].contains(&Vec2::new(3, 2)));
+        assert_eq!(components[2], HashSet::from([Vec2::new(0, 2)]));
+    }
+
+    #[test]
+    fn test_try_fill_errors() {
+        let mut mat = Matrix2D::fill(Vec2::new(2, 2), 0);
+
+        assert_eq!(
+            mat.try_fill(Vec2::new(1, 0), Vec2::new(2, 1), 1),
+            Err(BoardError::OutOfRange {
+                pos: Vec2::new(2, 0)
+            })
+        );
+        assert_eq!(mat.try_fill(Vec2::new(0, 0), Vec2::new(1, 2), 1), Ok(()));
+        assert_eq!(
+            mat.try_fill_without_cover(Vec2::new(0, 1), Vec2::new(2, 1), 2),
+            Err(BoardError::Covered {
+                pos: Vec2::new(0, 1)
+            })
+        );
+        assert_eq!(
+            Matrix2D::from_vec(Vec2::new(2, 2), vec![0; 3]),
+            Err(BoardError::SizeMismatch {
+                expected: 4,
+                got: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_row_count() {
+        let parse = |input: &str| input.parse::<Matrix2D<i16>>();
+
+        let short = parse("5 2\n1 0\n0 0\n2 2\n");
+        assert_eq!(
+            short,
+            Err(BoardError::RowCount {
+                expected_rows: 5,
+                found_rows: 3
+            })
+        );
+        assert_eq!(short.unwrap_err().to_string(), "Expect 5 rows, found 3");
+        let long = parse("2 2\n1 0\n0 0\n2 2\n");
+        assert_eq!(
+            long,
+            Err(BoardError::Parse {
+                line: 4,
+                msg: "Expect 2 rows, found more".to_string()
+            })
+        );
+        assert_eq!(
+            long.unwrap_err().to_string(),
+            "Invalid line 4: Expect 2 rows, found more"
+        );
+        // Trailing blank lines and whitespace are fine
+        assert!(parse("2 2\n1 0\n0 0\n\n  \n").is_ok());
+    }
+
+    #[test]
+    fn test_parse_huge_size() {
+        let parse = |input: &str| input.parse::<Matrix2D<i16>>();
+
+        // Rejected without allocating cells for the declared size
+        assert_eq!(
+            parse("32767 32767\n1\n"),
+            Err(BoardError::Parse {
+                line: 2,
+                msg: "expect 32767 block, got 1".to_string()
+            })
+        );
+        assert_eq!(
+            parse("32767 1\n1\n1\n"),
+            Err(BoardError::RowCount {
+                expected_rows: 32767,
+                found_rows: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_comments() {
+        let commented = "# From the level editor\n\
+        \n\
+        2 3\n\
+        # First row\n\
+        1 1 0\n\
+        \n\
+          # Indented comment\n\
+        2 0 3\n\
+        # Trailing comment\n\
+        ";
+        let mat = commented.parse::<Matrix2D<i16>>();
+
+        assert_eq!(mat, "2 3\n1 1 0\n2 0 3\n".parse::<Matrix2D<i16>>());
+        // Line numbers count skipped lines
+        assert_eq!(
+            "# Comment\n2 2\n\n1 0\n0 x\n".parse::<Matrix2D<i16>>(),
+            Err(BoardError::Parse {
+                line: 5,
+                msg: "Failed to parse block id: ParseIntError { kind: InvalidDigit }".to_string()
+            })
+        );
+        assert!(matches!(
+            "\n# Comment\n2\n".parse::<Matrix2D<i16>>(),
+            Err(BoardError::Parse { line: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn test_find_and_count() {
+        let mat = Matrix2D::from_vec(Vec2::new(3, 2), vec![1, 0, 2, 0, 2, 0]).unwrap();
+
+        assert_eq!(mat.find(&2), Some(Vec2::new(2, 0)));
+        assert_eq!(mat.find(&3), None);
+        assert_eq!(
+            mat.find_all(&0),
+            vec![Vec2::new(1, 0), Vec2::new(0, 1), Vec2::new(2, 1)]
+        );
+        assert_eq!(mat.find_all(&3), vec![]);
+        assert_eq!(mat.count(&0), 3);
+        assert_eq!(mat.count(&1), 1);
+        assert_eq!(mat.count(&3), 0);
+    }
+
+    #[test]
+    fn test_swap() {
+        let mut mat = Matrix2D::from_vec(Vec2::new(3, 2), vec![1, 2, 3, 4, 5, 6]).unwrap();
+
+        assert_eq!(mat.swap(Vec2::new(0, 0), Vec2::new(2, 1)), Ok(()));
+        assert_eq!(mat.to_vec(), vec![6, 2, 3, 4, 5, 1]);
+        assert_eq!(mat.swap(Vec2::new(1, 0), Vec2::new(1, 0)), Ok(()));
+        assert_eq!(
+            mat.swap(Vec2::new(1, 0), Vec2::new(3, 0)),
+            Err(BoardError::OutOfRange {
+                pos: Vec2::new(3, 0)
+            })
+        );
+        assert_eq!(
+            mat.swap(Vec2::new(0, -1), Vec2::new(0, 0)),
+            Err(BoardError::OutOfRange {
+                pos: Vec2::new(0, -1)
+            })
+        );
+        assert_eq!(mat.to_vec(), vec![6, 2, 3, 4, 5, 1]);
+    }
+
+    #[test]
+    fn test_view() {
+        // 5 columns and 4 rows, each cell stores 10 * y + x
+        let mut mat = Matrix2D::from_vec(
+            Vec2::new(5, 4),
+            (0..4)
+                .flat_map(|y| (0..5).map(move |x| 10 * y + x))
+                .collect(),
+        )
+        .unwrap();
+        let view = mat.view(Vec2::new(1, 2), Vec2::new(3, 2)).unwrap();
+
+        assert_eq!(view.size(), Vec2::new(3, 2));
+        assert_eq!(view.get(Vec2::new(0, 0)), Some(&21));
+        assert_eq!(view.get(Vec2::new(2, 1)), Some(&33));
+        assert_eq!(view.get(Vec2::new(3, 0)), None);
+        assert_eq!(view.get(Vec2::new(0, -1)), None);
+        assert_eq!(
+            view.iter_positions().collect::<Vec<_>>(),
+            vec![
+                (Vec2::new(0, 0), &21),
+                (Vec2::new(1, 0), &22),
+                (Vec2::new(2, 0), &23),
+                (Vec2::new(0, 1), &31),
+                (Vec2::new(1, 1), &32),
+                (Vec2::new(2, 1), &33),
+            ]
+        );
+
+        let copy = view.to_owned();
+        assert_eq!(copy.size(), Vec2::new(3, 2));
+        assert_eq!(copy.to_vec(), vec![21, 22, 23, 31, 32, 33]);
+        mat[Vec2::new(1, 2)] = -1;
+        assert_eq!(copy[Vec2::new(0, 0)], 21);
+        assert_eq!(
+            mat.copy_region(Vec2::new(1, 2), Vec2::new(1, 1))
+                .unwrap()
+                .to_vec(),
+            vec![-1]
+        );
+    }
+
+    #[test]
+    fn test_view_out_of_range() {
+        let mat = Matrix2D::fill(Vec2::new(5, 4), 0);
+
+        assert!(mat.view(Vec2::new(0, 0), Vec2::new(5, 4)).is_some());
+        assert!(mat.view(Vec2::new(1, 0), Vec2::new(5, 4)).is_none());
+        assert!(mat.view(Vec2::new(4, 3), Vec2::new(1, 2)).is_none());
+        assert!(mat.view(Vec2::new(-1, 0), Vec2::new(2, 2)).is_none());
+        assert!(mat.view(Vec2::new(0, 0), Vec2::new(0, 2)).is_none());
+        assert!(mat.copy_region(Vec2::new(3, 3), Vec2::new(3, 1)).is_none());
+    }
+
+    #[test]
+    fn test_get_unchecked() {
+        let mut mat = Matrix2D::from_vec(Vec2::new(3, 2), vec![1, 2, 3, 4, 5, 6]).unwrap();
+
+        for pos in mat.positions() {
+            // SAFETY: positions of the matrix are inside
+            assert_eq!(Some(unsafe { mat.get_unchecked(pos) }), mat.get(pos));
+        }
+        // SAFETY: (2, 1) is the last cell of the matrix
+        unsafe { *mat.get_unchecked_mut(Vec2::new(2, 1)) = 9 };
+        assert_eq!(mat.to_vec(), vec![1, 2, 3, 4, 5, 9]);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "Vec2(3, 0) is out of range")]
+    fn test_get_unchecked_is_checked_in_debug() {
+        let mat = Matrix2D::fill(Vec2::new(3, 2), 0);
+        // The debug assertion fires before the cell is read
+        let _ = unsafe { mat.get_unchecked(Vec2::new(3, 0)) };
+    }
+
     #[test]
     fn test_get_out_of_range() {
         let mat = Matrix2D::fill(Vec2::new(2, 2), 7);
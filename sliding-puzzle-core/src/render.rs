@@ -0,0 +1,128 @@
+//! Draw boards for humans
+
+use crate::board::{BlockId, WALL};
+use crate::matrix::Matrix2D;
+
+#[cfg(feature = "svg")]
+pub mod svg;
+
+/// Foreground colors assigned to blocks, indexed by id
+const PALETTE: [u8; 12] = [31, 32, 33, 34, 35, 36, 91, 92, 93, 94, 95, 96];
+
+const RESET: &str = "\x1b[0m";
+
+/// How [`Board::render`](crate::Board::render) draws the grid
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenderStyle {
+    /// Width of each cell, auto-sized to the widest id if `None`
+    pub width: Option<usize>,
+    /// Print holes as `.` instead of `0`
+    pub dot_holes: bool,
+    /// Wrap each block in an ANSI color, the same id always gets the same color
+    pub color: bool,
+}
+
+impl RenderStyle {
+    /// Plain output, the same as the aligned grid
+    pub fn plain() -> Self {
+        Self::default()
+    }
+
+    /// Colored output with dotted holes, for a terminal
+    pub fn colored() -> Self {
+        Self {
+            width: None,
+            dot_holes: true,
+            color: true,
+        }
+    }
+
+    /// Colored if the output is a terminal and `NO_COLOR` is not set, plain otherwise
+    pub fn for_terminal(is_terminal: bool) -> Self {
+        if is_terminal && std::env::var_os("NO_COLOR").is_none() {
+            Self::colored()
+        } else {
+            Self::plain()
+        }
+    }
+
+    fn cell(&self, id: BlockId) -> String {
+        if id == 0 && self.dot_holes {
+            ".".to_string()
+        } else {
+            id.to_string()
+        }
+    }
+
+    fn color_of(id: BlockId) -> Option<u8> {
+        if id == 0 || id == WALL {
+            return None;
+        }
+        Some(PALETTE[(id.unsigned_abs() as usize - 1) % PALETTE.len()])
+    }
+}
+
+/// Rows on separate lines, cells right-aligned and separated by a space
+pub(crate) fn render(grid: &Matrix2D<BlockId>, style: RenderStyle) -> String {
+    let width = style.width.unwrap_or_else(|| {
+        grid.iter()
+            .map(|&id| style.cell(id).len())
+            .max()
+            .unwrap_or(0)
+    });
+    let mut output = String::new();
+    for row in grid.rows() {
+        let row = row
+            .iter()
+            .map(|&id| {
+                let cell = format!("{:>width$}", style.cell(id), width = width);
+                match RenderStyle::color_of(id).filter(|_| style.color) {
+                    Some(color) => format!("\x1b[{}m{}{}", color, cell, RESET),
+                    None => cell,
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        output.push_str(&row);
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vec2::Vec2;
+
+    fn grid() -> Matrix2D<BlockId> {
+        Matrix2D::from_vec(Vec2::new(3, 2), vec![1, 10, 0, 100, 0, 1]).unwrap()
+    }
+
+    #[test]
+    fn test_render_plain() {
+        assert_eq!(
+            render(&grid(), RenderStyle::plain()),
+            "  1  10   0\n100   0   1\n"
+        );
+        let style = RenderStyle {
+            dot_holes: true,
+            ..RenderStyle::plain()
+        };
+        assert_eq!(render(&grid(), style), "  1  10   .\n100   .   1\n");
+        let style = RenderStyle {
+            width: Some(4),
+            ..RenderStyle::plain()
+        };
+        assert_eq!(render(&grid(), style), "   1   10    0\n 100    0    1\n");
+    }
+
+    #[test]
+    fn test_render_colored() {
+        let grid = Matrix2D::from_vec(Vec2::new(3, 1), vec![1, 0, 13]).unwrap();
+        // Ids sharing a palette slot share a color, holes are not colored
+        assert_eq!(
+            render(&grid, RenderStyle::colored()),
+            "\x1b[31m 1\x1b[0m  . \x1b[31m13\x1b[0m\n"
+        );
+    }
+}
@@ -0,0 +1,207 @@
+//! Draw boards as SVG images, e.g. for figures in a report
+
+use crate::board::{BlockId, Board, Move, VerifyError, WALL};
+use std::fmt::Write;
+
+/// Layout of the SVG image
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SvgOptions {
+    /// Side length of a cell in pixels
+    pub cell_size: u32,
+    /// Space around the board in pixels
+    pub margin: u32,
+    /// How long each frame of a solution is shown, in seconds
+    pub frame_secs: f64,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            cell_size: 40,
+            margin: 4,
+            frame_secs: 1.0,
+        }
+    }
+}
+
+/// Stable fill color of a block, the same id always gets the same color
+fn fill_of(id: BlockId) -> String {
+    // Golden angle spreads consecutive ids around the hue circle
+    let hue = (id as i32 * 137).rem_euclid(360);
+    format!("hsl({}, 60%, 70%)", hue)
+}
+
+fn open_svg(board: &Board, options: &SvgOptions) -> String {
+    let size = board.size();
+    let width = size.x as u32 * options.cell_size + 2 * options.margin;
+    let height = size.y as u32 * options.cell_size + 2 * options.margin;
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+        w = width,
+        h = height
+    )
+}
+
+/// Blocks as labeled rounded rectangles, holes and walls as one rectangle per cell
+fn write_cells(board: &Board, options: &SvgOptions, output: &mut String) {
+    let cell = options.cell_size as i32;
+    let origin = |v: i16| options.margin as i32 + v as i32 * cell;
+    let width = board.size().x as usize;
+    // Boards may have more cells than i16 holds, only each coordinate fits
+    let coord = |v: usize| i16::try_from(v).expect("Cell should be inside the board");
+    for (index, &id) in board.id_grid().iter().enumerate() {
+        let (x, y) = (coord(index % width), coord(index / width));
+        let class = match id {
+            0 => "hole",
+            WALL => "wall",
+            _ => continue,
+        };
+        let fill = if id == 0 { "none" } else { "#444" };
+        writeln!(
+            output,
+            "<rect class=\"{}\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" stroke=\"#ccc\"/>",
+            class,
+            origin(x),
+            origin(y),
+            cell,
+            cell,
+            fill
+        )
+        .unwrap();
+    }
+    for block in board.blocks() {
+        let (pos, block_size) = (block.pos(), block.size());
+        let (x, y) = (origin(pos.x), origin(pos.y));
+        let (width, height) = (block_size.x as i32 * cell, block_size.y as i32 * cell);
+        writeln!(
+            output,
+            "<rect class=\"block\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\" fill=\"{}\" stroke=\"#333\"/>",
+            x + 1,
+            y + 1,
+            width - 2,
+            height - 2,
+            cell / 6,
+            fill_of(block.id())
+        )
+        .unwrap();
+        writeln!(
+            output,
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\" font-family=\"sans-serif\" font-size=\"{}\">{}</text>",
+            x + width / 2,
+            y + height / 2,
+            cell / 2,
+            block.id()
+        )
+        .unwrap();
+    }
+}
+
+/// Draw the current state of `board`
+pub fn board_to_svg(board: &Board, options: &SvgOptions) -> String {
+    let mut output = open_svg(board, options);
+    write_cells(board, options, &mut output);
+    output.push_str("</svg>\n");
+    output
+}
+
+/// Draw `board` before and after each move, one `<g class="frame">` per step.
+/// Frames are shown one after another by SMIL, and the last one stays.
+/// Fail at the first illegal move
+pub fn solution_to_svg(
+    board: &Board,
+    moves: &[Move],
+    options: &SvgOptions,
+) -> Result<String, VerifyError> {
+    let mut board = board.clone();
+    let mut output = open_svg(&board, options);
+    for step in 0..=moves.len() {
+        if step > 0 {
+            let (id, dir) = moves[step - 1];
+            board.move_block(id, dir).map_err(|reason| VerifyError {
+                index: step - 1,
+                reason,
+            })?;
+        }
+        writeln!(
+            output,
+            "<g id=\"frame-{}\" class=\"frame\" visibility=\"hidden\">",
+            step
+        )
+        .unwrap();
+        let fill = if step == moves.len() {
+            "freeze"
+        } else {
+            "remove"
+        };
+        writeln!(
+            output,
+            "<set attributeName=\"visibility\" to=\"visible\" begin=\"{}s\" dur=\"{}s\" fill=\"{}\"/>",
+            step as f64 * options.frame_secs,
+            options.frame_secs,
+            fill
+        )
+        .unwrap();
+        write_cells(&board, options, &mut output);
+        output.push_str("</g>\n");
+    }
+    output.push_str("</svg>\n");
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{board::Dir, vec2::Vec2};
+
+    fn count(svg: &str, pattern: &str) -> usize {
+        svg.matches(pattern).count()
+    }
+
+    #[test]
+    fn test_board_to_svg() -> Result<(), String> {
+        let board = "2 3\n1 1 0\n2 3 0\n".parse::<Board>()?;
+        let svg = board_to_svg(&board, &SvgOptions::default());
+        assert!(svg
+            .starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"128\" height=\"88\""));
+        assert!(svg.ends_with("</svg>\n"));
+        // One rectangle per block and per hole, which together cover every cell
+        assert_eq!(count(&svg, "<rect class=\"block\""), board.blocks().len());
+        assert_eq!(count(&svg, "<rect class=\"hole\""), 2);
+        assert_eq!(count(&svg, "<rect "), 5);
+        assert_eq!(count(&svg, "<text "), 3);
+        assert!(svg.contains(">1</text>"));
+
+        let larger =
+            "4 6\n1 1 0 0 0 0\n2 3 0 0 0 0\n0 0 0 0 0 0\n0 0 0 0 0 0\n".parse::<Board>()?;
+        let svg = board_to_svg(&larger, &SvgOptions::default());
+        assert!(svg.contains("width=\"248\" height=\"168\""));
+        assert_eq!(count(&svg, "<rect "), 3 + 20);
+
+        // More cells than i16 holds
+        let huge = Board::generate(Vec2::new(200, 200), 1, 0);
+        let svg = board_to_svg(&huge, &SvgOptions::default());
+        assert!(!svg.contains("=\"-"));
+        let last = SvgOptions::default().margin + 199 * SvgOptions::default().cell_size;
+        assert!(svg.contains(&format!("x=\"{}\" y=\"{}\"", last, last)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solution_to_svg() -> Result<(), String> {
+        let board = "2 3\n0 0 0\n0 2 1\n".parse::<Board>()?;
+        let moves = [(2, Dir::Up), (1, Dir::Left)];
+        let svg =
+            solution_to_svg(&board, &moves, &SvgOptions::default()).map_err(|e| e.to_string())?;
+        assert_eq!(count(&svg, "<g id=\"frame-"), 3);
+        assert_eq!(count(&svg, "<set "), 3);
+        assert_eq!(count(&svg, "fill=\"freeze\""), 1);
+        assert_eq!(count(&svg, "<rect "), 3 * 6);
+
+        let illegal = [(2, Dir::Up), (2, Dir::Down), (2, Dir::Down)];
+        let error = solution_to_svg(&board, &illegal, &SvgOptions::default()).unwrap_err();
+        assert_eq!(error.index, 2);
+
+        Ok(())
+    }
+}
@@ -1,22 +1,153 @@
-use std::{fmt::Display, ops::Add};
+use crate::error::BoardError;
+use std::{
+    fmt::Display,
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+    str::FromStr,
+};
 
 /// A (x, y) vector
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vec2 {
-    pub x: i8,
-    pub y: i8,
+    pub x: i16,
+    pub y: i16,
 }
 
 impl Vec2 {
-    pub fn new(x: i8, y: i8) -> Self {
+    pub const fn new(x: i16, y: i16) -> Self {
         Self { x, y }
     }
+
+    /// Add component-wise, `None` if either component overflows
+    pub fn checked_add(self, rhs: Vec2) -> Option<Vec2> {
+        Some(Vec2::new(
+            self.x.checked_add(rhs.x)?,
+            self.y.checked_add(rhs.y)?,
+        ))
+    }
+
+    /// Add component-wise, components are clamped to the bounds of `i16` instead of overflowing
+    pub fn saturating_add(self, rhs: Vec2) -> Vec2 {
+        Vec2::new(self.x.saturating_add(rhs.x), self.y.saturating_add(rhs.y))
+    }
+
+    /// Sum of absolute differences of both components
+    pub fn manhattan(self, other: Vec2) -> i32 {
+        (self.x as i32 - other.x as i32).abs() + (self.y as i32 - other.y as i32).abs()
+    }
+
+    /// Maximum of absolute differences of both components
+    pub fn chebyshev(self, other: Vec2) -> i32 {
+        std::cmp::max(
+            (self.x as i32 - other.x as i32).abs(),
+            (self.y as i32 - other.y as i32).abs(),
+        )
+    }
+}
+
+/// Implement a component-wise binary operator for both `Vec2` and `&Vec2` receivers.
+/// Overflow is caught by a debug assertion, see [`Vec2::checked_add`] for a checked version
+macro_rules! impl_op {
+    ($op:ident, $method:ident, $checked:ident, $assign_op:ident, $assign_method:ident) => {
+        impl $op for Vec2 {
+            type Output = Vec2;
+            fn $method(self, rhs: Self) -> Self::Output {
+                debug_assert!(
+                    self.x.$checked(rhs.x).is_some() && self.y.$checked(rhs.y).is_some(),
+                    "{} {} {} overflows",
+                    self,
+                    stringify!($method),
+                    rhs
+                );
+                Vec2::new(self.x.$method(rhs.x), self.y.$method(rhs.y))
+            }
+        }
+
+        impl $op for &Vec2 {
+            type Output = Vec2;
+            fn $method(self, rhs: Self) -> Self::Output {
+                (*self).$method(*rhs)
+            }
+        }
+
+        impl $assign_op for Vec2 {
+            fn $assign_method(&mut self, rhs: Self) {
+                *self = (*self).$method(rhs);
+            }
+        }
+
+        impl $assign_op<&Vec2> for Vec2 {
+            fn $assign_method(&mut self, rhs: &Vec2) {
+                *self = (*self).$method(*rhs);
+            }
+        }
+    };
 }
 
-impl Add for &Vec2 {
+impl_op!(Add, add, checked_add, AddAssign, add_assign);
+impl_op!(Sub, sub, checked_sub, SubAssign, sub_assign);
+
+impl Neg for Vec2 {
+    type Output = Vec2;
+    fn neg(self) -> Self::Output {
+        Vec2::new(-self.x, -self.y)
+    }
+}
+
+impl Neg for &Vec2 {
+    type Output = Vec2;
+    fn neg(self) -> Self::Output {
+        -*self
+    }
+}
+
+impl Mul<i16> for Vec2 {
     type Output = Vec2;
-    fn add(self, rhs: Self) -> Self::Output {
-        Vec2::new(self.x + rhs.x, self.y + rhs.y)
+    fn mul(self, rhs: i16) -> Self::Output {
+        Vec2::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl Mul<i16> for &Vec2 {
+    type Output = Vec2;
+    fn mul(self, rhs: i16) -> Self::Output {
+        *self * rhs
+    }
+}
+
+impl From<(i16, i16)> for Vec2 {
+    fn from((x, y): (i16, i16)) -> Self {
+        Vec2::new(x, y)
+    }
+}
+
+impl From<Vec2> for (i16, i16) {
+    fn from(vec: Vec2) -> Self {
+        (vec.x, vec.y)
+    }
+}
+
+impl FromStr for Vec2 {
+    type Err = BoardError;
+
+    /// Parse `x,y` or `xXy` like `4,2` and `4x2`, whitespace around numbers is allowed
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let error = |msg: String| BoardError::InvalidVec2 {
+            input: input.to_string(),
+            msg,
+        };
+        let parts = input.split([',', 'x']).collect::<Vec<_>>();
+        if parts.len() != 2 {
+            return Err(error(
+                "expect 2 numbers delimited by a comma or x, e.g. 4,2".to_string(),
+            ));
+        }
+        let parse = |part: &str, name: &str| {
+            part.trim()
+                .parse::<i16>()
+                .map_err(|e| error(format!("cannot parse {} from {:?}: {}", name, part, e)))
+        };
+        Ok(Vec2::new(parse(parts[0], "x")?, parse(parts[1], "y")?))
     }
 }
 
@@ -41,9 +172,10 @@ impl Square {
         Self { offset, size }
     }
 
-    #[must_use]
-    pub fn at_origin(size: Vec2) -> Self {
-        Self::new(Vec2::new(0, 0), size)
+    /// Check whether the position is inside the square
+    pub fn contains(&self, pos: &Vec2) -> bool {
+        (self.offset.x..self.offset.x + self.size.x).contains(&pos.x)
+            && (self.offset.y..self.offset.y + self.size.y).contains(&pos.y)
     }
 
     pub fn row_iter(&self) -> impl Iterator<Item = Vec2> + '_ {
@@ -63,6 +195,109 @@ impl Square {
 mod tests {
     use super::*;
 
+    #[test]
+    // References are taken on purpose to test those impls
+    #[allow(clippy::op_ref)]
+    fn test_ops() {
+        let a = Vec2::new(3, -2);
+        let b = Vec2::new(-5, 4);
+
+        assert_eq!(a + b, Vec2::new(-2, 2));
+        assert_eq!(&a + &b, a + b);
+        assert_eq!(a - b, Vec2::new(8, -6));
+        assert_eq!(&a - &b, a - b);
+        assert_eq!(-a, Vec2::new(-3, 2));
+        assert_eq!(-&b, Vec2::new(5, -4));
+        assert_eq!(a * 3, Vec2::new(9, -6));
+        assert_eq!(&b * -2, Vec2::new(10, -8));
+
+        let mut c = a;
+        c += b;
+        assert_eq!(c, a + b);
+        c -= &b;
+        assert_eq!(c, a);
+        c += &b;
+        c -= b;
+        assert_eq!(c, a);
+    }
+
+    #[test]
+    fn test_distances() {
+        let a = Vec2::new(3, -2);
+        let b = Vec2::new(-5, 4);
+
+        assert_eq!(a.manhattan(b), 14);
+        assert_eq!(b.manhattan(a), 14);
+        assert_eq!(a.chebyshev(b), 8);
+        assert_eq!(a.manhattan(a), 0);
+        assert_eq!(a.chebyshev(a), 0);
+
+        // Distances between extreme values do not overflow
+        let min = Vec2::new(i16::MIN, i16::MIN);
+        let max = Vec2::new(i16::MAX, i16::MAX);
+        assert_eq!(min.manhattan(max), 2 * (u16::MAX as i32));
+        assert_eq!(max.chebyshev(min), u16::MAX as i32);
+        assert_eq!(max - Vec2::new(i16::MAX, 0), Vec2::new(0, i16::MAX));
+        assert_eq!(-max, Vec2::new(-i16::MAX, -i16::MAX));
+    }
+
+    #[test]
+    fn test_checked_and_saturating_add() {
+        let max = Vec2::new(i16::MAX, 0);
+        let right = Vec2::new(1, 0);
+        assert_eq!(max.checked_add(right), None);
+        assert_eq!(max.checked_add(-right), Some(Vec2::new(i16::MAX - 1, 0)));
+        assert_eq!(Vec2::new(0, i16::MIN).checked_add(Vec2::new(0, -1)), None);
+
+        assert_eq!(max.saturating_add(right), max);
+        assert_eq!(
+            Vec2::new(-3, i16::MIN).saturating_add(Vec2::new(-2, -1)),
+            Vec2::new(-5, i16::MIN)
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "overflows")]
+    fn test_add_overflow_panics() {
+        let _ = Vec2::new(i16::MAX, 0) + Vec2::new(1, 0);
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("4,2".parse(), Ok(Vec2::new(4, 2)));
+        assert_eq!("4x2".parse(), Ok(Vec2::new(4, 2)));
+        assert_eq!(" 100 , -3 ".parse(), Ok(Vec2::new(100, -3)));
+        assert_eq!(" 7 x 8".parse(), Ok(Vec2::new(7, 8)));
+
+        let error = |input: &str| input.parse::<Vec2>().unwrap_err().to_string();
+        assert_eq!(
+            error("4"),
+            "Invalid vector \"4\": expect 2 numbers delimited by a comma or x, e.g. 4,2"
+        );
+        assert_eq!(
+            error("4,2,1"),
+            "Invalid vector \"4,2,1\": expect 2 numbers delimited by a comma or x, e.g. 4,2"
+        );
+        assert_eq!(
+            error("a,2"),
+            "Invalid vector \"a,2\": cannot parse x from \"a\": invalid digit found in string"
+        );
+        assert_eq!(
+            error("4,40000"),
+            "Invalid vector \"4,40000\": cannot parse y from \"40000\": \
+            number too large to fit in target type"
+        );
+        assert!("".parse::<Vec2>().is_err());
+    }
+
+    #[test]
+    fn test_tuple_conversion() {
+        assert_eq!(Vec2::from((3, -1)), Vec2::new(3, -1));
+        let tuple: (i16, i16) = Vec2::new(3, -1).into();
+        assert_eq!(tuple, (3, -1));
+    }
+
     #[test]
     fn test_row_iter() {
         let squ = Square::new(Vec2::new(2, 2), Vec2::new(2, 2));
@@ -85,4 +320,11 @@ mod tests {
         ];
         assert_eq!(squ.col_iter().collect::<Vec<_>>(), expected);
     }
+    #[test]
+    fn test_contains() {
+        let squ = Square::new(Vec2::new(2, 2), Vec2::new(2, 1));
+        assert!(squ.contains(&Vec2::new(3, 2)));
+        assert!(!squ.contains(&Vec2::new(2, 3)));
+        assert!(!squ.contains(&Vec2::new(4, 2)));
+    }
 }
@@ -0,0 +1,55 @@
+use crate::vec2::Vec2;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Fixed seed, so that boards with the same size & block count share the same keys
+const SEED: u64 = 0x5eed_b10c;
+
+/// Random keys for each (block, position) pair, used to hash board states
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct ZobristTable {
+    /// Size of the board
+    size: Vec2,
+    /// Keys stored in block-major order, blocks are indexed by their position in id order
+    keys: Vec<u64>,
+}
+
+impl ZobristTable {
+    pub fn new(size: Vec2, block_count: usize) -> Self {
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let cell_count = size.x as usize * size.y as usize;
+        let keys = (0..block_count * cell_count).map(|_| rng.gen()).collect();
+        Self { size, keys }
+    }
+
+    /// Get the key of the block at `block_index` placed at given position
+    pub fn key(&self, block_index: usize, pos: Vec2) -> u64 {
+        let cell_count = self.size.x as usize * self.size.y as usize;
+        let index = pos.y as usize * self.size.x as usize + pos.x as usize;
+        self.keys[block_index * cell_count + index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_size_share_keys() {
+        let a = ZobristTable::new(Vec2::new(4, 3), 5);
+        let b = ZobristTable::new(Vec2::new(4, 3), 5);
+
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_keys_are_distinct() {
+        let table = ZobristTable::new(Vec2::new(4, 3), 5);
+        let mut keys = table.keys.clone();
+        keys.sort_unstable();
+        keys.dedup();
+
+        assert_eq!(keys.len(), table.keys.len());
+        assert_ne!(table.key(0, Vec2::new(0, 0)), table.key(1, Vec2::new(0, 0)));
+        assert_ne!(table.key(0, Vec2::new(0, 0)), table.key(0, Vec2::new(1, 0)));
+    }
+}
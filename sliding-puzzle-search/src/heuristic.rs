@@ -0,0 +1,67 @@
+use sliding_puzzle_core::Board;
+
+/// Estimate how many moves are needed to reach the goal
+pub trait Heuristic {
+    fn estimate(&self, board: &Board) -> i32;
+}
+
+/// Sum of Manhattan distances of blocks, see [`Board::heuristic`]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ManhattanHeuristic;
+
+impl Heuristic for ManhattanHeuristic {
+    fn estimate(&self, board: &Board) -> i32 {
+        board.heuristic()
+    }
+}
+
+/// Manhattan distances plus linear conflicts, see [`Board::heuristic_linear_conflict`]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LinearConflictHeuristic;
+
+impl Heuristic for LinearConflictHeuristic {
+    fn estimate(&self, board: &Board) -> i32 {
+        board.heuristic_linear_conflict()
+    }
+}
+
+/// Manhattan distances with same-shape blocks interchangeable, see [`Board::heuristic_matched`]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MatchedHeuristic;
+
+impl Heuristic for MatchedHeuristic {
+    fn estimate(&self, board: &Board) -> i32 {
+        board.heuristic_matched()
+    }
+}
+
+/// Always estimate 0, which makes informed searches uninformed
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ZeroHeuristic;
+
+impl Heuristic for ZeroHeuristic {
+    fn estimate(&self, _board: &Board) -> i32 {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate() -> Result<(), String> {
+        let board = "2 3\n\
+        2 1 0\n\
+        3 3 0\n\
+        "
+        .parse::<Board>()?;
+
+        assert_eq!(ManhattanHeuristic.estimate(&board), 2);
+        assert_eq!(LinearConflictHeuristic.estimate(&board), 4);
+        assert_eq!(MatchedHeuristic.estimate(&board), 0);
+        assert_eq!(ZeroHeuristic.estimate(&board), 0);
+
+        Ok(())
+    }
+}
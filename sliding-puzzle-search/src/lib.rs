@@ -1 +1,5 @@
+pub mod heuristic;
+pub mod prune;
 pub mod search;
+pub mod solver;
+pub mod trace;
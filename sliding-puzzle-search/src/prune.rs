@@ -0,0 +1,148 @@
+use sliding_puzzle_core::{Block, Board, Dir, WALL};
+
+/// Check whether the goal can never be reached from this board.
+///
+/// A block is frozen if no move can ever free it: in every direction it is blocked by
+/// the edge, a wall or another frozen block, or its side perpendicular to the move is
+/// longer than the hole count. The board is a dead end if a frozen block is not at its
+/// goal position, which covers blocks jammed into a corner as well as oversized blocks.
+///
+/// Every move can be undone, so all boards reachable from one another are either dead
+/// ends or not. Searches checking each node thus never expand any node of a dead end,
+/// but expand the same nodes as without the check otherwise.
+pub fn is_dead_end(board: &Board) -> bool {
+    let blocks = board.blocks();
+    let goals = board.final_state().blocks();
+    let frozen = frozen_blocks(board);
+
+    blocks
+        .iter()
+        .zip(goals)
+        .zip(frozen)
+        .any(|((block, goal), frozen)| frozen && block.pos() != goal.pos())
+}
+
+/// Which blocks can never move, indexed by the position of blocks in id order.
+///
+/// Start from all blocks and release those which may move in some direction until no
+/// more can be released, so the remaining ones only block each other
+fn frozen_blocks(board: &Board) -> Vec<bool> {
+    let blocks = board.blocks();
+    let mut frozen = vec![true; blocks.len()];
+    let mut released = true;
+    while released {
+        released = false;
+        for (index, block) in blocks.iter().enumerate() {
+            if frozen[index]
+                && Dir::ALL
+                    .iter()
+                    .any(|&dir| may_move(board, block, dir, &frozen))
+            {
+                frozen[index] = false;
+                released = true;
+            }
+        }
+    }
+    frozen
+}
+
+/// Whether the block may move in the direction some time,
+/// assuming blocks marked in `frozen` never move
+fn may_move(board: &Board, block: &Block, dir: Dir, frozen: &[bool]) -> bool {
+    let grid = board.grid();
+    let blocks = board.blocks();
+    let mut front = 0;
+    for cell in block.cells().map(|cell| cell + dir.to_vec2()) {
+        let id = match grid.get(cell) {
+            Some(&id) => id,
+            None => return false,
+        };
+        if id == block.id() {
+            continue;
+        }
+        if id == WALL {
+            return false;
+        }
+        if id != 0 {
+            let index = blocks
+                .binary_search_by_key(&id, Block::id)
+                .expect("Blocks should be sorted by id");
+            if frozen[index] {
+                return false;
+            }
+        }
+        front += 1;
+    }
+    front <= board.hole_count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_immobile_block_not_at_goal() -> Result<(), String> {
+        // The 2x2 block needs 2 holes to move
+        let board = "3 3\n\
+        2 2 1\n\
+        2 2 3\n\
+        4 5 0\n\
+        "
+        .parse::<Board>()?;
+
+        assert!(is_dead_end(&board));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_immobile_block_at_goal() -> Result<(), String> {
+        let board = "3 3\n\
+        1 2 2\n\
+        3 2 2\n\
+        4 0 5\n\
+        "
+        .parse::<Board>()?;
+
+        assert!(!is_dead_end(&board));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_jammed_in_corner() -> Result<(), String> {
+        // Block 1 is walled in at the bottom right corner, although there are plenty of holes
+        let board = "3 3\n\
+        2 0 0\n\
+        0 -1 -1\n\
+        0 -1 1\n\
+        "
+        .parse::<Board>()?;
+        assert!(is_dead_end(&board));
+
+        // Blocks 1 and 2 lock each other in the corner
+        let board = "3 3\n\
+        0 0 -1\n\
+        0 -1 2\n\
+        0 -1 1\n\
+        "
+        .parse::<Board>()?;
+        assert!(is_dead_end(&board));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mobile_blocks() -> Result<(), String> {
+        let board = "3 3\n\
+        1 1 2\n\
+        0 3 0\n\
+        0 4 4\n\
+        "
+        .parse::<Board>()?;
+
+        assert!(!is_dead_end(&board));
+
+        Ok(())
+    }
+}
@@ -1,174 +1,3701 @@
+use crate::{
+    heuristic::{Heuristic, LinearConflictHeuristic, ManhattanHeuristic},
+    prune,
+    trace::{TraceCollector, TraceEntry, TraceEvent},
+};
 use log::{debug, trace};
-use sliding_puzzle_core::{Board, BoardState, Dir, Move};
-use std::collections::HashSet;
+use rayon::prelude::*;
+use sliding_puzzle_core::{
+    Block, BlockId, Board, CanonicalState, Dir, Move, PackedState, RenderStyle, Solvability,
+};
+use std::{
+    cmp::Ordering,
+    collections::{hash_map::DefaultHasher, BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::{Hash, Hasher},
+    sync::{
+        atomic::{self, AtomicBool, AtomicU32},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// Statistics of a search
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SearchStats {
+    /// Number of nodes whose children are generated, summed over all iterations
+    pub nodes_expanded: u64,
+    /// Maximum number of moves from the initial state to a visited node
+    pub max_depth: u32,
+    /// Number of deepening iterations
+    pub iterations: u32,
+    /// Wall time spent by the search
+    pub duration: Duration,
+}
+
+/// Limits of a search, the search stops once any of them is reached
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SearchLimits {
+    /// Maximum number of expanded nodes
+    pub max_nodes: Option<u64>,
+    /// Maximum wall time
+    pub max_duration: Option<Duration>,
+}
+
+/// Result of a search which might be stopped early
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchOutcome {
+    Solved(Vec<Move>),
+    NoSolution,
+    LimitReached,
+    Cancelled,
+    /// Proved unsolvable without searching, see [`Board::quick_solvability`]
+    Unsolvable,
+}
+
+impl From<Option<Vec<Move>>> for SearchOutcome {
+    fn from(moves: Option<Vec<Move>>) -> Self {
+        match moves {
+            Some(moves) => SearchOutcome::Solved(moves),
+            None => SearchOutcome::NoSolution,
+        }
+    }
+}
+
+impl SearchOutcome {
+    /// Get the solution, if any
+    pub fn into_moves(self) -> Option<Vec<Move>> {
+        match self {
+            SearchOutcome::Solved(moves) => Some(moves),
+            _ => None,
+        }
+    }
+}
+
+/// How many expansions between two checks of the clock or the cancellation flag,
+/// which are too slow to check every node
+const CHECK_INTERVAL: u64 = 4096;
+
+/// Track whether a search has exceeded its limits or been cancelled
+struct Budget {
+    limits: SearchLimits,
+    cancel: Arc<AtomicBool>,
+    start: Instant,
+    exceeded: bool,
+    cancelled: bool,
+}
+
+impl Budget {
+    fn new(limits: SearchLimits, cancel: Arc<AtomicBool>) -> Self {
+        Self {
+            limits,
+            cancel,
+            start: Instant::now(),
+            exceeded: false,
+            cancelled: false,
+        }
+    }
+
+    /// Whether the search should stop
+    fn is_stopped(&self) -> bool {
+        self.exceeded || self.cancelled
+    }
+
+    /// Check the limits before expanding a node, return false if the search should stop
+    fn try_expand(&mut self, nodes_expanded: u64) -> bool {
+        if let Some(max_nodes) = self.limits.max_nodes {
+            if nodes_expanded >= max_nodes {
+                self.exceeded = true;
+            }
+        }
+        if nodes_expanded.is_multiple_of(CHECK_INTERVAL) {
+            if let Some(max_duration) = self.limits.max_duration {
+                if self.start.elapsed() >= max_duration {
+                    self.exceeded = true;
+                }
+            }
+            if self.cancel.load(atomic::Ordering::Relaxed) {
+                self.cancelled = true;
+            }
+        }
+        !self.is_stopped()
+    }
+}
+
+/// Progress of an iterative deepening search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgressEvent {
+    /// Depth limit of IDDFS, or f-limit of IDA*
+    pub limit: i32,
+    /// Number of expanded nodes in current iteration
+    pub nodes_expanded: u64,
+    /// Time since the search started
+    pub elapsed: Duration,
+}
+
+/// How many expansions between two progress reports inside an iteration
+const PROGRESS_INTERVAL: u64 = 100_000;
+
+/// Report progress at iteration boundaries and every [`PROGRESS_INTERVAL`] expansions
+struct Progress<'a> {
+    on_progress: &'a mut dyn FnMut(ProgressEvent),
+    start: Instant,
+    limit: i32,
+    /// Number of expanded nodes before current iteration
+    base_nodes: u64,
+}
+
+impl<'a> Progress<'a> {
+    fn new(on_progress: &'a mut dyn FnMut(ProgressEvent)) -> Self {
+        Self {
+            on_progress,
+            start: Instant::now(),
+            limit: 0,
+            base_nodes: 0,
+        }
+    }
+
+    fn begin_iteration(&mut self, limit: i32, nodes_expanded: u64) {
+        self.limit = limit;
+        self.base_nodes = nodes_expanded;
+    }
+
+    fn report(&mut self, nodes_expanded: u64) {
+        (self.on_progress)(ProgressEvent {
+            limit: self.limit,
+            nodes_expanded: nodes_expanded - self.base_nodes,
+            elapsed: self.start.elapsed(),
+        });
+    }
+
+    /// Called after each expansion, only report once in a while
+    fn tick(&mut self, nodes_expanded: u64) {
+        if nodes_expanded.is_multiple_of(PROGRESS_INTERVAL) {
+            self.report(nodes_expanded);
+        }
+    }
+}
 
 /// IDDFS
 pub fn iddfs(board: Board) -> Option<Vec<Move>> {
+    iddfs_with_stats(board).0
+}
+
+/// IDDFS, also return statistics of the search
+pub fn iddfs_with_stats(board: Board) -> (Option<Vec<Move>>, SearchStats) {
+    let (outcome, stats) = iddfs_limited_with_stats(board, SearchLimits::default());
+    (outcome.into_moves(), stats)
+}
+
+/// IDDFS which stops once any of the limits is reached
+pub fn iddfs_limited(board: Board, limits: SearchLimits) -> SearchOutcome {
+    iddfs_limited_with_stats(board, limits).0
+}
+
+/// IDDFS which stops once any of the limits is reached, also return statistics of the search
+pub fn iddfs_limited_with_stats(
+    board: Board,
+    limits: SearchLimits,
+) -> (SearchOutcome, SearchStats) {
+    iddfs_cancellable_with_stats(board, limits, Default::default())
+}
+
+/// IDDFS which stops once `cancel` is set
+pub fn iddfs_cancellable(board: Board, cancel: Arc<AtomicBool>) -> SearchOutcome {
+    iddfs_cancellable_with_stats(board, SearchLimits::default(), cancel).0
+}
+
+/// IDDFS which stops once `cancel` is set or any of the limits is reached,
+/// also return statistics of the search
+pub fn iddfs_cancellable_with_stats(
+    board: Board,
+    limits: SearchLimits,
+    cancel: Arc<AtomicBool>,
+) -> (SearchOutcome, SearchStats) {
+    iddfs_search(
+        board,
+        limits,
+        &SearchOptions::default(),
+        &SearchConstraints::default(),
+        cancel,
+        &mut |_| {},
+    )
+}
+
+/// IDDFS with options
+pub fn iddfs_with_options(board: Board, options: &SearchOptions) -> Option<Vec<Move>> {
+    iddfs_with_options_and_stats(board, options).0
+}
+
+/// IDDFS with options, also return statistics of the search
+pub fn iddfs_with_options_and_stats(
+    board: Board,
+    options: &SearchOptions,
+) -> (Option<Vec<Move>>, SearchStats) {
+    let (outcome, stats) = iddfs_search(
+        board,
+        SearchLimits::default(),
+        options,
+        &SearchConstraints::default(),
+        Default::default(),
+        &mut |_| {},
+    );
+    (outcome.into_moves(), stats)
+}
+
+/// IDDFS which never moves blocks forbidden by `constraints`
+pub fn iddfs_with_constraints(board: Board, constraints: &SearchConstraints) -> Option<Vec<Move>> {
+    iddfs_search(
+        board,
+        SearchLimits::default(),
+        &SearchOptions::default(),
+        constraints,
+        Default::default(),
+        &mut |_| {},
+    )
+    .0
+    .into_moves()
+}
+
+/// IDDFS which calls `on_progress` periodically
+pub fn iddfs_with_progress<F: FnMut(ProgressEvent)>(
+    board: Board,
+    mut on_progress: F,
+) -> Option<Vec<Move>> {
+    iddfs_search(
+        board,
+        SearchLimits::default(),
+        &SearchOptions::default(),
+        &SearchConstraints::default(),
+        Default::default(),
+        &mut on_progress,
+    )
+    .0
+    .into_moves()
+}
+
+/// IDDFS with all knobs: never moves blocks forbidden by `constraints`, stops once `cancel`
+/// is set or any of the limits is reached, and calls `on_progress` periodically.
+/// Also return statistics of the search
+pub fn iddfs_search(
+    board: Board,
+    limits: SearchLimits,
+    options: &SearchOptions,
+    constraints: &SearchConstraints,
+    cancel: Arc<AtomicBool>,
+    on_progress: &mut dyn FnMut(ProgressEvent),
+) -> (SearchOutcome, SearchStats) {
+    let mut search = DfsSearch::new(limits, cancel, on_progress);
+    search.constraints = constraints.clone();
+    search.prune_dead_ends = options.prune_dead_ends;
     let mut limit = 1;
+    let mut known_states = 0;
 
-    loop {
+    let outcome = loop {
         debug!("limit: {}", limit);
-        match dfs(&mut board.clone(), limit, &mut Default::default()) {
-            Ok(mut moves) => {
-                moves.reverse();
-                return Some(moves);
-            }
-            Err(remain_limit) => {
-                if remain_limit > 0 {
-                    return None;
+        search.stats.iterations += 1;
+        search
+            .progress
+            .begin_iteration(limit as i32, search.stats.nodes_expanded);
+        let result = search.depth_limited(&board, limit);
+        search.progress.report(search.stats.nodes_expanded);
+        match result {
+            DepthLimitedResult::Found(moves) => break SearchOutcome::Solved(moves),
+            _ if search.budget.cancelled => break SearchOutcome::Cancelled,
+            _ if search.budget.exceeded => break SearchOutcome::LimitReached,
+            DepthLimitedResult::Exhausted => break SearchOutcome::NoSolution,
+            DepthLimitedResult::Cutoff => {
+                // Every reachable state has been expanded if a deeper limit finds no new one
+                if search.fruitless.len() == known_states {
+                    break SearchOutcome::NoSolution;
                 }
+                known_states = search.fruitless.len();
             }
         }
         limit += 1;
-    }
+    };
+    search.stats.duration = search.budget.start.elapsed();
+
+    (outcome, search.stats)
+}
+
+/// Result of [`depth_limited`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DepthLimitedResult {
+    /// Moves to reach the goal
+    Found(Vec<Move>),
+    /// No solution within the limit, but deeper ones may exist
+    Cutoff,
+    /// No solution at any depth
+    Exhausted,
 }
 
-fn dfs(board: &mut Board, limit: i32, visited: &mut HashSet<BoardState>) -> Result<Vec<Move>, i32> {
+/// Depth-limited DFS, which searches solutions with at most `limit` moves.
+/// The solution found is not necessarily the shortest one
+pub fn depth_limited(board: &Board, limit: u32) -> DepthLimitedResult {
+    let mut on_progress = |_| {};
+    DfsSearch::new(
+        SearchLimits::default(),
+        Default::default(),
+        &mut on_progress,
+    )
+    .depth_limited(board, limit)
+}
+
+/// Number of moves in the prefixes distributed by [`iddfs_parallel`]
+const PARALLEL_PREFIX_LEN: u32 = 2;
+
+/// IDDFS which distributes all 2-move prefixes across `threads` workers.
+/// Workers share the depth limit of each iteration, and the remaining workers are
+/// cancelled once any of them finds a solution.
+pub fn iddfs_parallel(board: Board, threads: usize) -> Option<Vec<Move>> {
     if board.is_goal() {
-        return Ok(vec![]);
-    }
-    if limit <= 0 {
-        return Err(0);
+        return Some(vec![]);
     }
-    if visited.get(board.state()).is_some() {
-        return Err(limit);
-    } else {
-        visited.insert(board.state().clone());
-    }
-
-    let mut remain_limit = limit;
-    for (id, dir) in board.possible_moves() {
-        if let Err(e) = board.move_block(id, dir) {
-            trace!("{} {:?}", e, (id, dir));
+    let mut prefixes = vec![];
+    for (id, dir) in board.possible_moves_iter() {
+        let mut first_board = board.clone();
+        if first_board.move_block(id, dir).is_err() {
             continue;
         }
-        match dfs(board, limit - 1, visited) {
-            Ok(mut moves) => {
-                moves.push((id, dir));
-                return Ok(moves);
+        if first_board.is_goal() {
+            return Some(vec![(id, dir)]);
+        }
+        for (second_id, second_dir) in first_board.possible_moves() {
+            let mut second_board = first_board.clone();
+            if second_board.move_block(second_id, second_dir).is_err()
+                || second_board.state() == board.state()
+            {
+                continue;
             }
-            Err(_remain_limit) => {
-                remain_limit = std::cmp::min(remain_limit, _remain_limit);
+            prefixes.push((vec![(id, dir), (second_id, second_dir)], second_board));
+        }
+    }
+    if let Some((prefix, _)) = prefixes.iter().find(|(_, board)| board.is_goal()) {
+        return Some(prefix.clone());
+    }
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .expect("Failed to build thread pool");
+    let limit = AtomicU32::new(PARALLEL_PREFIX_LEN + 1);
+    // Fruitless states of each prefix, kept across iterations like `iddfs`
+    let mut tables = vec![HashMap::new(); prefixes.len()];
+    let mut known_states = 0;
+
+    pool.install(|| loop {
+        debug!("limit: {}", limit.load(atomic::Ordering::Relaxed));
+        let found = Arc::new(AtomicBool::new(false));
+        let results = prefixes
+            .par_iter()
+            .zip(std::mem::take(&mut tables).into_par_iter())
+            .map(|((prefix, board), fruitless)| {
+                let mut on_progress = |_| {};
+                let mut search =
+                    DfsSearch::new(SearchLimits::default(), found.clone(), &mut on_progress);
+                search.fruitless = fruitless;
+                let remain = limit.load(atomic::Ordering::Relaxed) - PARALLEL_PREFIX_LEN;
+                let result = match search.depth_limited(board, remain) {
+                    DepthLimitedResult::Found(moves) => {
+                        found.store(true, atomic::Ordering::Relaxed);
+                        DepthLimitedResult::Found([prefix.clone(), moves].concat())
+                    }
+                    result => result,
+                };
+                (result, search.fruitless)
+            })
+            .collect::<Vec<_>>();
+
+        let mut exhausted = true;
+        let mut best: Option<Vec<Move>> = None;
+        for (result, fruitless) in results {
+            tables.push(fruitless);
+            match result {
+                DepthLimitedResult::Found(moves) => {
+                    if best.as_ref().is_none_or(|best| moves.len() < best.len()) {
+                        best = Some(moves);
+                    }
+                }
+                DepthLimitedResult::Cutoff => exhausted = false,
+                DepthLimitedResult::Exhausted => {}
             }
         }
-        assert!(board.move_block(id, dir.inverse()).is_ok());
+        if best.is_some() {
+            return best;
+        }
+        let states = tables.iter().map(HashMap::len).sum();
+        if exhausted || states == known_states {
+            return None;
+        }
+        known_states = states;
+        limit.fetch_add(1, atomic::Ordering::Relaxed);
+    })
+}
+
+/// Stack frame of [`DfsSearch`]
+struct DfsFrame {
+    /// Moves to try from this node
+    moves: Vec<Move>,
+    /// Index of the next move to try, the move before it is currently applied
+    next: usize,
+    /// Number of single steps taken by the move currently applied
+    steps: usize,
+    limit: i32,
+    remain_limit: i32,
+}
+
+impl DfsFrame {
+    /// The move applied to reach the child currently being searched
+    fn current_move(&self) -> Move {
+        self.moves[self.next - 1]
     }
+}
 
-    visited.remove(board.state());
-    Err(remain_limit)
+/// Depth-limited DFS
+struct DfsSearch<'a> {
+    /// Hashes of states on current path
+    visited: HashSet<u64>,
+    /// Deepest limit at which a state was proven fruitless. Kept across iterations
+    /// so that states are only re-expanded with a deeper limit
+    fruitless: HashMap<PackedState, i32>,
+    constraints: SearchConstraints,
+    /// Skip nodes which provably cannot reach the goal, see [`SearchOptions::prune_dead_ends`]
+    prune_dead_ends: bool,
+    stack: Vec<DfsFrame>,
+    /// Move lists of popped frames, reused by new frames so that entering a node does not allocate
+    spare_moves: Vec<Vec<Move>>,
+    stats: SearchStats,
+    budget: Budget,
+    progress: Progress<'a>,
 }
 
-/// IDA*
-pub fn idastar(board: Board) -> Option<Vec<Move>> {
-    let mut f_limit = board.heuristic();
-    loop {
-        match _idastar(&mut board.clone(), 0, f_limit, &mut Default::default()) {
+impl<'a> DfsSearch<'a> {
+    fn new(
+        limits: SearchLimits,
+        cancel: Arc<AtomicBool>,
+        on_progress: &'a mut dyn FnMut(ProgressEvent),
+    ) -> Self {
+        Self {
+            visited: HashSet::new(),
+            fruitless: HashMap::new(),
+            constraints: SearchConstraints::default(),
+            prune_dead_ends: false,
+            stack: vec![],
+            spare_moves: vec![],
+            stats: SearchStats::default(),
+            budget: Budget::new(limits, cancel),
+            progress: Progress::new(on_progress),
+        }
+    }
+
+    /// Search under the depth limit, see [`depth_limited`]
+    fn depth_limited(&mut self, board: &Board, limit: u32) -> DepthLimitedResult {
+        let limit = i32::try_from(limit).unwrap_or(i32::MAX);
+        match self.run(&mut board.clone(), limit) {
             Ok(mut moves) => {
                 moves.reverse();
-                return Some(moves);
+                DepthLimitedResult::Found(moves)
             }
-            Err(new_limit) => {
-                if new_limit <= f_limit {
-                    return None;
-                } else {
-                    f_limit = new_limit;
+            Err(remain_limit) if remain_limit > 0 => DepthLimitedResult::Exhausted,
+            Err(_) => DepthLimitedResult::Cutoff,
+        }
+    }
+
+    /// Search under the depth limit, return moves in reverse order if the goal is found,
+    /// otherwise return the remaining limit.
+    ///
+    /// The recursion is driven by an explicit stack, so the depth is bounded only by heap memory.
+    fn run(&mut self, board: &mut Board, limit: i32) -> Result<Vec<Move>, i32> {
+        self.spare_moves
+            .extend(self.stack.drain(..).map(|frame| frame.moves));
+        self.visited.clear();
+        let mut returned = self.enter(board, limit);
+
+        loop {
+            if self.budget.is_stopped() {
+                return Err(0);
+            }
+            if let Some(result) = returned.take() {
+                let frame = match self.stack.last_mut() {
+                    Some(frame) => frame,
+                    None => return result.map(|_| vec![]),
+                };
+                match result {
+                    Ok(()) => {
+                        return Ok(self
+                            .stack
+                            .iter()
+                            .rev()
+                            .map(DfsFrame::current_move)
+                            .collect());
+                    }
+                    Err(remain_limit) => {
+                        frame.remain_limit = std::cmp::min(frame.remain_limit, remain_limit);
+                    }
+                }
+                for _ in 0..frame.steps {
+                    board.undo();
+                }
+            }
+
+            let frame = self.stack.last_mut().expect("Stack should not be empty");
+            if let Some(&(id, dir)) = frame.moves.get(frame.next) {
+                frame.next += 1;
+                match self.constraints.semantics.apply(board, (id, dir)) {
+                    Ok(steps) => frame.steps = steps,
+                    Err(e) => {
+                        trace!("{} {:?}", e, (id, dir));
+                        continue;
+                    }
                 }
+                let limit = frame.limit - 1;
+                returned = self.enter(board, limit);
+            } else {
+                let frame = self.stack.pop().expect("Stack should not be empty");
+                self.visited.remove(&board.zobrist_hash());
+                self.fruitless.insert(board.state().pack(), frame.limit);
+                returned = Some(Err(frame.remain_limit));
+                self.spare_moves.push(frame.moves);
             }
         }
     }
+
+    /// Enter a node, return the result if it can be decided immediately,
+    /// otherwise push a new frame onto the stack
+    fn enter(&mut self, board: &Board, limit: i32) -> Option<Result<(), i32>> {
+        self.stats.max_depth = std::cmp::max(self.stats.max_depth, self.stack.len() as u32);
+        if board.is_goal() {
+            return Some(Ok(()));
+        }
+        if limit <= 0 {
+            return Some(Err(0));
+        }
+        let hash = board.zobrist_hash();
+        if self.visited.contains(&hash) {
+            return Some(Err(limit));
+        }
+        // No deeper limit helps a dead end, so it is exhausted like a revisited state
+        if self.prune_dead_ends && prune::is_dead_end(board) {
+            return Some(Err(limit));
+        }
+        if matches!(self.fruitless.get(&board.state().pack()), Some(&proven) if proven >= limit) {
+            return Some(Err(0));
+        }
+        if !self.budget.try_expand(self.stats.nodes_expanded) {
+            return Some(Err(0));
+        }
+        self.visited.insert(hash);
+
+        self.stats.nodes_expanded += 1;
+        self.progress.tick(self.stats.nodes_expanded);
+        let mut moves = self.spare_moves.pop().unwrap_or_default();
+        self.constraints.moves_into(board, &mut moves);
+        self.stack.push(DfsFrame {
+            moves,
+            next: 0,
+            steps: 0,
+            limit,
+            remain_limit: limit,
+        });
+        None
+    }
 }
 
-fn _idastar(
-    board: &mut Board,
-    g_value: i32,
-    mut f_limit: i32,
-    visited: &mut HashSet<BoardState>,
-) -> Result<Vec<Move>, i32> {
-    if board.is_goal() {
-        return Ok(vec![]);
+/// What a single move does to the moved block
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MoveSemantics {
+    /// The block moves by one cell
+    #[default]
+    SingleStep,
+    /// The block keeps moving until it hits something, see [`Board::slide_block`]
+    SlideToStop,
+}
+
+impl MoveSemantics {
+    /// Apply a move by [`Board::push_move`], return how many steps were pushed
+    fn apply(self, board: &mut Board, (id, dir): Move) -> Result<usize, String> {
+        board.push_move(id, dir)?;
+        let mut steps = 1;
+        if self == MoveSemantics::SlideToStop {
+            while board.push_move(id, dir).is_ok() {
+                steps += 1;
+            }
+        }
+        Ok(steps)
     }
-    if visited.get(board.state()).is_some() {
-        return Err(f_limit);
-    } else {
-        visited.insert(board.state().clone());
+
+    /// Whether applying the moves to the board reaches the goal
+    fn solves(self, board: &Board, moves: &[Move]) -> bool {
+        let mut board = board.clone();
+        moves.iter().all(|&m| self.apply(&mut board, m).is_ok()) && board.is_goal()
     }
 
-    for (id, dir) in board.possible_moves() {
-        if let Err(e) = board.move_block(id, dir) {
-            trace!("{} {:?}", e, (id, dir));
-            continue;
-        }
-        let f_value = g_value + board.heuristic();
-        if f_value < f_limit {
-            if let Ok(mut moves) = _idastar(board, g_value + 1, f_limit, visited) {
-                moves.push((id, dir));
-                return Ok(moves);
+    /// Scale a single-step estimate. A slide moves a block by at most the board's longest
+    /// side minus one, so dividing by that keeps the estimate admissible
+    fn scale(self, board: &Board, estimate: i32) -> i32 {
+        match self {
+            MoveSemantics::SingleStep => estimate,
+            MoveSemantics::SlideToStop => {
+                let size = board.size();
+                let max_slide = std::cmp::max(std::cmp::max(size.x, size.y) as i32 - 1, 1);
+                (estimate + max_slide - 1) / max_slide
             }
         }
-        f_limit = std::cmp::max(f_limit, f_value);
-        assert!(board.move_block(id, dir.inverse()).is_ok());
     }
+}
 
-    visited.remove(board.state());
-    Err(f_limit)
+/// Restrictions on which moves searches may take
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SearchConstraints {
+    /// Ids of blocks which must never move
+    pub frozen: HashSet<BlockId>,
+    /// What a move does, only supported by IDDFS and IDA*
+    pub semantics: MoveSemantics,
 }
 
-pub fn manual(mut board: Board) -> Option<Vec<Move>> {
-    use std::io;
+impl SearchConstraints {
+    /// Whether the move is allowed
+    pub fn allows(&self, (id, _): Move) -> bool {
+        !self.frozen.contains(&id)
+    }
 
-    let input = io::stdin();
-    let mut buffer = String::new();
-    let mut moves = vec![];
+    /// Possible moves of the board which are allowed
+    pub fn moves(&self, board: &Board) -> Vec<Move> {
+        let mut moves = vec![];
+        self.moves_into(board, &mut moves);
+        moves
+    }
 
-    eprintln!("{}", board);
-    loop {
-        eprintln!("Enter a move: ");
-        eprintln!("Possible values are: {:?}", board.possible_moves());
-        let bytes = input.read_line(&mut buffer).expect("Read move fail");
-        if bytes == 0 {
-            break;
+    /// Replace contents of `moves` with possible moves of the board which are allowed,
+    /// so that searches can reuse the buffer instead of allocating at every node
+    pub fn moves_into(&self, board: &Board, moves: &mut Vec<Move>) {
+        moves.clear();
+        moves.extend(board.possible_moves_iter().filter(|&m| self.allows(m)));
+    }
+}
+
+/// Options to tweak the behavior of searches
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SearchOptions {
+    /// Expand children in ascending order of their heuristic value
+    pub order_children: bool,
+    /// Skip every node which provably cannot reach the goal, see [`prune::is_dead_end`].
+    /// Only used by IDDFS and IDA*
+    pub prune_dead_ends: bool,
+    /// Close a state and its mirror image together when the goal is mirror symmetric,
+    /// see [`Board::mirror_relabeling`]. Only used by A*
+    pub prune_mirrors: bool,
+    /// Treat blocks with the same shape as interchangeable, see [`BoardState::canonical`].
+    /// IDA* first searches with visited states and the goal compared in canonical form,
+    /// and searches again without merging if that solution ends with such blocks swapped,
+    /// so the solution is still optimal and reaches the exact goal. Only used by IDA*
+    pub merge_equivalent_blocks: bool,
+}
+
+/// IDA*
+pub fn idastar(board: Board) -> Option<Vec<Move>> {
+    idastar_with(board, &ManhattanHeuristic)
+}
+
+/// IDA* guided by given heuristic
+pub fn idastar_with<H: Heuristic + ?Sized>(board: Board, heuristic: &H) -> Option<Vec<Move>> {
+    idastar_with_options(board, heuristic, &SearchOptions::default())
+}
+
+/// IDA* guided by given heuristic, with extra search options
+pub fn idastar_with_options<H: Heuristic + ?Sized>(
+    board: Board,
+    heuristic: &H,
+    options: &SearchOptions,
+) -> Option<Vec<Move>> {
+    idastar_with_stats(board, heuristic, options).0
+}
+
+/// IDA* guided by given heuristic, also return statistics of the search
+pub fn idastar_with_stats<H: Heuristic + ?Sized>(
+    board: Board,
+    heuristic: &H,
+    options: &SearchOptions,
+) -> (Option<Vec<Move>>, SearchStats) {
+    let (outcome, stats) =
+        idastar_cancellable_with_stats(board, heuristic, options, Default::default());
+    (outcome.into_moves(), stats)
+}
+
+/// IDA* which stops once `cancel` is set
+pub fn idastar_cancellable(board: Board, cancel: Arc<AtomicBool>) -> SearchOutcome {
+    idastar_cancellable_with_stats(board, &ManhattanHeuristic, &Default::default(), cancel).0
+}
+
+/// IDA* guided by given heuristic which stops once `cancel` is set,
+/// also return statistics of the search
+pub fn idastar_cancellable_with_stats<H: Heuristic + ?Sized>(
+    board: Board,
+    heuristic: &H,
+    options: &SearchOptions,
+    cancel: Arc<AtomicBool>,
+) -> (SearchOutcome, SearchStats) {
+    idastar_search(
+        board,
+        heuristic,
+        options,
+        &SearchConstraints::default(),
+        cancel,
+        &mut |_| {},
+        None,
+    )
+}
+
+/// IDA* which never moves blocks forbidden by `constraints`
+pub fn idastar_with_constraints(
+    board: Board,
+    constraints: &SearchConstraints,
+) -> Option<Vec<Move>> {
+    idastar_search(
+        board,
+        &ManhattanHeuristic,
+        &SearchOptions::default(),
+        constraints,
+        Default::default(),
+        &mut |_| {},
+        None,
+    )
+    .0
+    .into_moves()
+}
+
+/// IDA* which calls `on_progress` periodically
+pub fn idastar_with_progress<F: FnMut(ProgressEvent)>(
+    board: Board,
+    mut on_progress: F,
+) -> Option<Vec<Move>> {
+    idastar_search(
+        board,
+        &ManhattanHeuristic,
+        &SearchOptions::default(),
+        &SearchConstraints::default(),
+        Default::default(),
+        &mut on_progress,
+        None,
+    )
+    .0
+    .into_moves()
+}
+
+/// IDA* which records visited nodes to `collector`, see [`TraceCollector::write_dot`]
+pub fn idastar_traced(board: Board, collector: &mut TraceCollector) -> Option<Vec<Move>> {
+    idastar_search(
+        board,
+        &ManhattanHeuristic,
+        &SearchOptions::default(),
+        &SearchConstraints::default(),
+        Default::default(),
+        &mut |_| {},
+        Some(collector),
+    )
+    .0
+    .into_moves()
+}
+
+/// IDA* with all knobs: guided by given heuristic, never moves blocks forbidden by
+/// `constraints`, stops once `cancel` is set, calls `on_progress` periodically,
+/// and records visited nodes to `trace` if any. Also return statistics of the search.
+///
+/// Under constraints, entered states are kept across iterations like the fruitless states
+/// of IDDFS, so that the search also ends on boards the constraints make unsolvable
+pub fn idastar_search<H: Heuristic + ?Sized>(
+    board: Board,
+    heuristic: &H,
+    options: &SearchOptions,
+    constraints: &SearchConstraints,
+    cancel: Arc<AtomicBool>,
+    on_progress: &mut dyn FnMut(ProgressEvent),
+    trace: Option<&mut TraceCollector>,
+) -> (SearchOutcome, SearchStats) {
+    let start = Instant::now();
+    let mut search = IdaStarSearch::new(heuristic, options, &cancel, on_progress);
+    search.constraints = constraints.clone();
+    search.trace = trace;
+    if *constraints != SearchConstraints::default() {
+        search.reached = Some(HashMap::new());
+    }
+    let mut outcome = None;
+    if options.merge_equivalent_blocks {
+        search.canonical_goal = Some(board.final_state().canonical());
+        outcome = match search.deepen(&board, &cancel) {
+            // The merged search may stop at the goal with same-shape blocks swapped,
+            // which is only a solution if no blocks are actually swapped
+            SearchOutcome::Solved(moves) if !constraints.semantics.solves(&board, &moves) => None,
+            outcome => Some(outcome),
+        };
+        search.canonical_goal = None;
+        // States were entered with the canonical goal and visited set, so start over
+        if let Some(reached) = &mut search.reached {
+            reached.clear();
+            search.cut_off.clear();
         }
-        match parse_cmd(buffer.trim()) {
-            Ok((id, dir)) => {
-                if let Err(e) = board.move_block(id, dir) {
-                    eprintln!("{}", e);
+    }
+    let outcome = outcome.unwrap_or_else(|| search.deepen(&board, &cancel));
+    search.stats.duration = start.elapsed();
+
+    (outcome, search.stats)
+}
+
+/// Stack frame of [`IdaStarSearch`]
+struct IdaStarFrame {
+    /// Moves to try from this node
+    moves: Vec<Move>,
+    /// Index of the next move to try, the move before it is currently applied
+    next: usize,
+    /// Number of single steps taken by the move currently applied
+    steps: usize,
+    g_value: i32,
+    f_limit: i32,
+    /// Minimum f-value exceeding the f-limit in the subtree, `i32::MAX` if there is none
+    next_f_limit: i32,
+}
+
+impl IdaStarFrame {
+    /// The move applied to reach the child currently being searched
+    fn current_move(&self) -> Move {
+        self.moves[self.next - 1]
+    }
+}
+
+/// Depth-first part of IDA*
+struct IdaStarSearch<'a, H: ?Sized> {
+    heuristic: &'a H,
+    options: &'a SearchOptions,
+    cancelled: &'a AtomicBool,
+    /// Hashes of states on current path
+    visited: HashSet<u64>,
+    stack: Vec<IdaStarFrame>,
+    /// Move lists of popped frames, reused by new frames so that entering a node does not allocate
+    spare_moves: Vec<Vec<Move>>,
+    /// Scratch space of [`ordered_moves_into`]
+    scored_moves: Vec<(i32, Move)>,
+    stats: SearchStats,
+    progress: Progress<'a>,
+    /// Set if blocks with the same shape are interchangeable,
+    /// see [`SearchOptions::merge_equivalent_blocks`]
+    canonical_goal: Option<CanonicalState>,
+    constraints: SearchConstraints,
+    /// Visited nodes are recorded if set
+    trace: Option<&'a mut TraceCollector>,
+    /// States entered in any iteration with the most f-limit left then, kept if set.
+    /// Constraints may make the goal unreachable although the board looks solvable,
+    /// which is proven once no state is left to reach. Like the fruitless states of IDDFS,
+    /// a state is not entered again with less f-limit left
+    reached: Option<HashMap<PackedState, i32>>,
+    /// States cut off which have never been entered, kept with `reached`
+    cut_off: HashSet<PackedState>,
+}
+
+impl<'a, H: Heuristic + ?Sized> IdaStarSearch<'a, H> {
+    fn new(
+        heuristic: &'a H,
+        options: &'a SearchOptions,
+        cancelled: &'a AtomicBool,
+        on_progress: &'a mut dyn FnMut(ProgressEvent),
+    ) -> Self {
+        Self {
+            heuristic,
+            options,
+            cancelled,
+            visited: HashSet::new(),
+            stack: vec![],
+            spare_moves: vec![],
+            scored_moves: vec![],
+            stats: SearchStats::default(),
+            progress: Progress::new(on_progress),
+            canonical_goal: None,
+            constraints: SearchConstraints::default(),
+            trace: None,
+            reached: None,
+            cut_off: HashSet::new(),
+        }
+    }
+
+    /// Record the node if tracing, it is reached by the current move of the top frame
+    fn record(&mut self, board: &Board, event: TraceEvent) {
+        if self.trace.is_none() {
+            return;
+        }
+        let heuristic = self.estimate(board);
+        if let Some(trace) = self.trace.as_deref_mut() {
+            trace.record(TraceEntry {
+                depth: self.stack.len() as u32,
+                applied: self.stack.last().map(IdaStarFrame::current_move),
+                heuristic,
+                event,
+            });
+        }
+    }
+
+    /// Heuristic value of the board, scaled to the move semantics
+    fn estimate(&self, board: &Board) -> i32 {
+        self.constraints
+            .semantics
+            .scale(board, self.heuristic.estimate(board))
+    }
+
+    /// Whether the board reaches the goal, which ignores block ids if `canonical_goal` is set
+    fn is_goal(&self, board: &Board) -> bool {
+        match &self.canonical_goal {
+            Some(goal) => board.state().canonical() == *goal,
+            None => board.is_goal(),
+        }
+    }
+
+    /// Key of the board in the visited set
+    fn visited_key(&self, board: &Board) -> u64 {
+        match self.canonical_goal {
+            Some(_) => {
+                let mut hasher = DefaultHasher::new();
+                board.state().canonical().hash(&mut hasher);
+                hasher.finish()
+            }
+            None => board.zobrist_hash(),
+        }
+    }
+
+    /// Deepen the f-limit from the heuristic value of the board until the search ends
+    fn deepen(&mut self, board: &Board, cancel: &AtomicBool) -> SearchOutcome {
+        let mut f_limit = self.estimate(board);
+        loop {
+            debug!("f_limit: {}", f_limit);
+            self.stats.iterations += 1;
+            self.progress
+                .begin_iteration(f_limit, self.stats.nodes_expanded);
+            let result = self.run(&mut board.clone(), 0, f_limit);
+            self.progress.report(self.stats.nodes_expanded);
+            match result {
+                Ok(mut moves) => {
+                    moves.reverse();
+                    return SearchOutcome::Solved(moves);
+                }
+                Err(_) if cancel.load(atomic::Ordering::Relaxed) => {
+                    return SearchOutcome::Cancelled
+                }
+                Err(new_limit) => {
+                    if new_limit == i32::MAX || self.exhausted() {
+                        return SearchOutcome::NoSolution;
+                    } else {
+                        f_limit = new_limit;
+                    }
                 }
-                moves.push((id, dir));
             }
-            Err(e) => {
-                eprintln!("Invalid command: {}", e);
-                continue;
+        }
+    }
+
+    /// Whether every state reachable from the board has been entered, checked after an
+    /// iteration without solution.
+    ///
+    /// Children of an entered state are entered, on the current path, dead ends or cut off.
+    /// So if all states cut off have been entered, no more state can be reached.
+    fn exhausted(&mut self) -> bool {
+        match &self.reached {
+            Some(reached) => {
+                self.cut_off.retain(|state| !reached.contains_key(state));
+                self.cut_off.is_empty()
             }
+            None => false,
         }
-        eprintln!("{}", board);
-        if board.is_goal() {
-            eprintln!("Reach goal");
-            break;
+    }
+
+    /// Search under the f-limit, return moves in reverse order if the goal is found,
+    /// otherwise return the f-limit for next iteration, which is the minimum f-value
+    /// exceeding current one, or `i32::MAX` if no node exceeds it.
+    ///
+    /// The recursion is driven by an explicit stack, so the depth is bounded only by heap memory.
+    fn run(&mut self, board: &mut Board, g_value: i32, f_limit: i32) -> Result<Vec<Move>, i32> {
+        self.spare_moves
+            .extend(self.stack.drain(..).map(|frame| frame.moves));
+        let mut returned = self.enter(board, g_value, f_limit);
+
+        loop {
+            if let Some(result) = returned.take() {
+                let frame = match self.stack.last_mut() {
+                    Some(frame) => frame,
+                    None => return result.map(|_| vec![]),
+                };
+                if result.is_ok() {
+                    return Ok(self
+                        .stack
+                        .iter()
+                        .rev()
+                        .map(IdaStarFrame::current_move)
+                        .collect());
+                }
+                if let Err(next_f_limit) = result {
+                    frame.next_f_limit = std::cmp::min(frame.next_f_limit, next_f_limit);
+                }
+                for _ in 0..frame.steps {
+                    board.undo();
+                }
+            }
+
+            let frame = self.stack.last_mut().expect("Stack should not be empty");
+            if let Some(&(id, dir)) = frame.moves.get(frame.next) {
+                frame.next += 1;
+                let steps = match self.constraints.semantics.apply(board, (id, dir)) {
+                    Ok(steps) => steps,
+                    Err(e) => {
+                        trace!("{} {:?}", e, (id, dir));
+                        continue;
+                    }
+                };
+                frame.steps = steps;
+                let g_value = frame.g_value + 1;
+                let f_limit = frame.f_limit;
+                let f_value = g_value + self.estimate(board);
+                if f_value <= f_limit {
+                    returned = self.enter(board, g_value, f_limit);
+                } else {
+                    let frame = self.stack.last_mut().expect("Stack should not be empty");
+                    frame.next_f_limit = std::cmp::min(frame.next_f_limit, f_value);
+                    self.record(board, TraceEvent::Pruned);
+                    if self.reached.is_some() {
+                        self.cut_off.insert(board.state().pack());
+                    }
+                    for _ in 0..steps {
+                        board.undo();
+                    }
+                }
+            } else {
+                let frame = self.stack.pop().expect("Stack should not be empty");
+                let key = self.visited_key(board);
+                self.visited.remove(&key);
+                returned = Some(Err(frame.next_f_limit));
+                self.spare_moves.push(frame.moves);
+            }
         }
-        buffer.clear();
     }
 
-    Some(moves)
+    /// Enter a node, return the result if it can be decided immediately,
+    /// otherwise push a new frame onto the stack
+    fn enter(&mut self, board: &mut Board, g_value: i32, f_limit: i32) -> Option<Result<(), i32>> {
+        self.stats.max_depth = std::cmp::max(self.stats.max_depth, self.stack.len() as u32);
+        if self.is_goal(board) {
+            self.record(board, TraceEvent::Expanded);
+            return Some(Ok(()));
+        }
+        if self.stats.nodes_expanded.is_multiple_of(CHECK_INTERVAL)
+            && self.cancelled.load(atomic::Ordering::Relaxed)
+        {
+            return Some(Err(i32::MAX));
+        }
+        if self.options.prune_dead_ends && prune::is_dead_end(board) {
+            self.record(board, TraceEvent::Pruned);
+            return Some(Err(i32::MAX));
+        }
+        if !self.visited.insert(self.visited_key(board)) {
+            self.record(board, TraceEvent::Pruned);
+            // Only the canonical form of the state is on the current path
+            if self.reached.is_some() && self.canonical_goal.is_some() {
+                self.cut_off.insert(board.state().pack());
+            }
+            return Some(Err(i32::MAX));
+        }
+        if let Some(reached) = &mut self.reached {
+            let remain = f_limit - g_value;
+            let most_remain = reached.entry(board.state().pack()).or_insert(i32::MIN);
+            if *most_remain >= remain {
+                let key = self.visited_key(board);
+                self.visited.remove(&key);
+                self.record(board, TraceEvent::Pruned);
+                // The next f-limit of its subtree is unknown but exceeds the current one
+                return Some(Err(f_limit + 1));
+            }
+            *most_remain = remain;
+        }
+        self.record(board, TraceEvent::Expanded);
+
+        let mut moves = self.spare_moves.pop().unwrap_or_default();
+        if self.options.order_children {
+            ordered_moves_into(board, self.heuristic, &mut self.scored_moves, &mut moves);
+            moves.retain(|&m| self.constraints.allows(m));
+        } else {
+            self.constraints.moves_into(board, &mut moves);
+        }
+        self.stats.nodes_expanded += 1;
+        self.progress.tick(self.stats.nodes_expanded);
+        self.stack.push(IdaStarFrame {
+            moves,
+            next: 0,
+            steps: 0,
+            g_value,
+            f_limit,
+            next_f_limit: i32::MAX,
+        });
+        None
+    }
 }
 
-fn parse_cmd(cmd: &str) -> Result<Move, String> {
-    let dir = cmd.chars().last().ok_or("Empty command")?;
-    let dir = match dir {
-        'U' => Dir::Up,
-        'D' => Dir::Down,
-        'L' => Dir::Left,
-        'R' => Dir::Right,
-        _ => return Err(format!("Invalid direction: {}", dir)),
-    };
+/// Get valid moves sorted by the heuristic value of resulting children
+fn ordered_moves<H: Heuristic + ?Sized>(board: &mut Board, heuristic: &H) -> Vec<Move> {
+    let mut moves = vec![];
+    ordered_moves_into(board, heuristic, &mut vec![], &mut moves);
+    moves
+}
 
-    let id = {
-        let mut chars = cmd.chars();
-        chars.next_back();
-        chars
-            .as_str()
-            .parse::<i8>()
-            .map_err(|e| format!("Invalid id: {}", e))?
+/// Replace contents of `moves` with valid moves sorted by the heuristic value of resulting
+/// children. `scored` is scratch space, so that reusing both buffers does not allocate
+fn ordered_moves_into<H: Heuristic + ?Sized>(
+    board: &mut Board,
+    heuristic: &H,
+    scored: &mut Vec<(i32, Move)>,
+    moves: &mut Vec<Move>,
+) {
+    moves.clear();
+    moves.extend(board.possible_moves_iter());
+    scored.clear();
+    for &(id, dir) in moves.iter() {
+        if let Err(e) = board.move_block(id, dir) {
+            trace!("{} {:?}", e, (id, dir));
+            continue;
+        }
+        scored.push((heuristic.estimate(board), (id, dir)));
+        assert!(board.move_block(id, dir.inverse()).is_ok());
+    }
+    scored.sort_unstable();
+    moves.clear();
+    moves.extend(scored.iter().map(|&(_, m)| m));
+}
+
+/// Default bound of [`hint`]
+pub const HINT_MAX_DEPTH: usize = 64;
+
+/// IDA* which gives up once the f-limit exceeds `max_depth`
+pub fn idastar_bounded(board: Board, max_depth: usize) -> SearchOutcome {
+    let options = SearchOptions::default();
+    let cancelled = AtomicBool::new(false);
+    let mut on_progress = |_| {};
+    let mut search =
+        IdaStarSearch::new(&ManhattanHeuristic, &options, &cancelled, &mut on_progress);
+    let mut f_limit = ManhattanHeuristic.estimate(&board);
+    loop {
+        if f_limit as usize > max_depth {
+            return SearchOutcome::LimitReached;
+        }
+        match search.run(&mut board.clone(), 0, f_limit) {
+            Ok(mut moves) => {
+                moves.reverse();
+                return SearchOutcome::Solved(moves);
+            }
+            Err(i32::MAX) => return SearchOutcome::NoSolution,
+            Err(new_limit) => f_limit = new_limit,
+        }
+    }
+}
+
+/// The first move of an optimal solution, searched by IDA* bounded by [`HINT_MAX_DEPTH`].
+/// Return `None` if the board is already solved or no solution is found within the bound
+pub fn hint(board: &Board) -> Option<Move> {
+    idastar_bounded(board.clone(), HINT_MAX_DEPTH)
+        .into_moves()?
+        .first()
+        .copied()
+}
+
+/// Like [`hint`] but bounded by `max_depth`, and fall back to the move minimizing
+/// the heuristic when the bound is hit
+pub fn hint_with_depth(board: &Board, max_depth: usize) -> Option<Move> {
+    match idastar_bounded(board.clone(), max_depth) {
+        SearchOutcome::Solved(moves) => moves.first().copied(),
+        SearchOutcome::LimitReached => ordered_moves(&mut board.clone(), &ManhattanHeuristic)
+            .first()
+            .copied(),
+        _ => None,
+    }
+}
+
+/// Default node cap of [`estimate_difficulty`]
+pub const DIFFICULTY_MAX_NODES: u64 = 1_000_000;
+
+/// Length of the optimal solution found by [`estimate_difficulty`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolutionLength {
+    Exact(usize),
+    /// The node cap is hit, every solution has at least this many moves
+    AtLeast(usize),
+    NoSolution,
+}
+
+impl std::fmt::Display for SolutionLength {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolutionLength::Exact(moves) => write!(f, "{} moves", moves),
+            SolutionLength::AtLeast(moves) => write!(f, "at least {} moves", moves),
+            SolutionLength::NoSolution => write!(f, "no solution"),
+        }
+    }
+}
+
+/// How hard a board is for humans, see [`estimate_difficulty`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DifficultyReport {
+    pub length: SolutionLength,
+    pub nodes_expanded: u64,
+    /// Number of possible moves from the initial board
+    pub branching_factor: usize,
+    /// Solution length, plus log2 of the branching factor and log10 of expanded nodes,
+    /// so that boards of the same length are harder with more choices along the way.
+    /// Infinite if there is no solution
+    pub score: f64,
+}
+
+impl DifficultyReport {
+    /// Bucket the score, `thresholds` are the lowest scores of medium and hard
+    pub fn label(&self, thresholds: [f64; 2]) -> &'static str {
+        match self.length {
+            SolutionLength::NoSolution => "unsolvable",
+            _ if self.score < thresholds[0] => "easy",
+            _ if self.score < thresholds[1] => "medium",
+            _ => "hard",
+        }
+    }
+}
+
+/// Estimate the difficulty with IDA*, see [`estimate_difficulty_with_cap`]
+pub fn estimate_difficulty(board: &Board) -> DifficultyReport {
+    estimate_difficulty_with_cap(board, DIFFICULTY_MAX_NODES)
+}
+
+/// Estimate the difficulty by solving the board with IDA*, which gives up after
+/// expanding about `max_nodes` nodes. In that case the f-limit of the unfinished
+/// iteration is a lower bound of the solution length
+pub fn estimate_difficulty_with_cap(board: &Board, max_nodes: u64) -> DifficultyReport {
+    let branching_factor = board.branching_factor();
+    if board.quick_solvability() == Solvability::Unsolvable {
+        return DifficultyReport {
+            length: SolutionLength::NoSolution,
+            nodes_expanded: 0,
+            branching_factor,
+            score: f64::INFINITY,
+        };
+    }
+    let cancel = Arc::new(AtomicBool::new(false));
+    // Progress events count nodes of the current iteration only
+    let (mut limit, mut previous_nodes, mut iteration_nodes) = (0, 0, 0);
+    let mut on_progress = |event: ProgressEvent| {
+        if event.limit != limit {
+            previous_nodes += iteration_nodes;
+            limit = event.limit;
+        }
+        iteration_nodes = event.nodes_expanded;
+        if previous_nodes + iteration_nodes >= max_nodes {
+            cancel.store(true, atomic::Ordering::Relaxed);
+        }
+    };
+    let (outcome, stats) = idastar_search(
+        board.clone(),
+        &ManhattanHeuristic,
+        &SearchOptions::default(),
+        &SearchConstraints::default(),
+        cancel.clone(),
+        &mut on_progress,
+        None,
+    );
+    let length = match outcome {
+        SearchOutcome::Solved(moves) => SolutionLength::Exact(moves.len()),
+        SearchOutcome::Cancelled | SearchOutcome::LimitReached => {
+            SolutionLength::AtLeast(limit.max(0) as usize)
+        }
+        SearchOutcome::NoSolution | SearchOutcome::Unsolvable => SolutionLength::NoSolution,
+    };
+    let score = match length {
+        SolutionLength::Exact(moves) | SolutionLength::AtLeast(moves) => {
+            moves as f64
+                + (branching_factor.max(1) as f64).log2()
+                + ((stats.nodes_expanded + 1) as f64).log10()
+        }
+        SolutionLength::NoSolution => f64::INFINITY,
     };
+    DifficultyReport {
+        length,
+        nodes_expanded: stats.nodes_expanded,
+        branching_factor,
+        score,
+    }
+}
 
-    Ok((id, dir))
+/// IDA* which splits the root moves across `threads` workers.
+/// Workers share the same f-limit in each iteration, and the remaining workers are
+/// cancelled once any of them finds a solution.
+pub fn idastar_parallel(board: Board, threads: usize) -> Option<Vec<Move>> {
+    if board.is_goal() {
+        return Some(vec![]);
+    }
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .expect("Failed to build thread pool");
+    let root_moves = board.possible_moves();
+    let mut f_limit = board.heuristic();
+
+    pool.install(|| loop {
+        debug!("f_limit: {}", f_limit);
+        let found = AtomicBool::new(false);
+        let results = root_moves
+            .par_iter()
+            .map(|&(id, dir)| {
+                let mut board = board.clone();
+                let options = SearchOptions::default();
+                let mut on_progress = |_| {};
+                let mut search =
+                    IdaStarSearch::new(&ManhattanHeuristic, &options, &found, &mut on_progress);
+                search.visited.insert(board.zobrist_hash());
+                if let Err(e) = board.move_block(id, dir) {
+                    trace!("{} {:?}", e, (id, dir));
+                    return Err(i32::MAX);
+                }
+                let f_value = 1 + board.heuristic();
+                if f_value > f_limit {
+                    return Err(f_value);
+                }
+                match search.run(&mut board, 1, f_limit) {
+                    Ok(mut moves) => {
+                        found.store(true, atomic::Ordering::Relaxed);
+                        moves.push((id, dir));
+                        moves.reverse();
+                        Ok(moves)
+                    }
+                    Err(next_f_limit) => Err(next_f_limit),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut new_limit = i32::MAX;
+        let mut best: Option<Vec<Move>> = None;
+        for result in results {
+            match result {
+                Ok(moves) => {
+                    if best.as_ref().is_none_or(|best| moves.len() < best.len()) {
+                        best = Some(moves);
+                    }
+                }
+                Err(limit) => new_limit = std::cmp::min(new_limit, limit),
+            }
+        }
+        if best.is_some() {
+            return best;
+        }
+        if new_limit == i32::MAX {
+            return None;
+        }
+        f_limit = new_limit;
+    })
+}
+
+/// Node in the open set of best-first searches, ordered by its priority
+struct Node {
+    priority: f32,
+    g_value: i32,
+    board: Board,
+}
+
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Node {}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Node {
+    /// `BinaryHeap` is a max-heap, so the node with lowest priority should be the greatest.
+    /// Ties are broken by preferring deeper nodes.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .priority
+            .total_cmp(&self.priority)
+            .then_with(|| self.g_value.cmp(&other.g_value))
+    }
+}
+
+/// A*
+pub fn astar(board: Board) -> Option<Vec<Move>> {
+    weighted_astar(board, 1.0)
+}
+
+/// Weighted A*, which scores nodes by `g + weight * h`.
+/// The solution is optimal if `weight` is 1.0, larger weights trade optimality for speed.
+pub fn weighted_astar(board: Board, weight: f32) -> Option<Vec<Move>> {
+    _weighted_astar(board, weight, &SearchOptions::default()).0
+}
+
+/// A* with extra search options, also return statistics of the search
+pub fn astar_with_stats(board: Board, options: &SearchOptions) -> (Option<Vec<Move>>, SearchStats) {
+    _weighted_astar(board, 1.0, options)
+}
+
+/// Weighted A* with extra search options, also return statistics of the search
+fn _weighted_astar(
+    board: Board,
+    weight: f32,
+    options: &SearchOptions,
+) -> (Option<Vec<Move>>, SearchStats) {
+    let start = Instant::now();
+    let mirror_relabeling = if options.prune_mirrors {
+        board.mirror_relabeling()
+    } else {
+        None
+    };
+    let mut stats = SearchStats::default();
+    let mut open = BinaryHeap::new();
+    let mut closed = HashSet::new();
+    let mut g_values = HashMap::new();
+    let mut parents = HashMap::new();
+
+    g_values.insert(board.state().pack(), 0);
+    open.push(Node {
+        priority: weight * board.heuristic() as f32,
+        g_value: 0,
+        board,
+    });
+
+    while let Some(Node {
+        g_value, mut board, ..
+    }) = open.pop()
+    {
+        if board.is_goal() {
+            stats.duration = start.elapsed();
+            return (Some(reconstruct_moves(board, &parents)), stats);
+        }
+        // A state might be pushed multiple times, only expand the first (best) one
+        if !closed.insert(board.state().pack()) {
+            continue;
+        }
+        // The mirror image is as far from the goal, so it does not need to be expanded
+        if let Some(relabeling) = &mirror_relabeling {
+            closed.insert(
+                board
+                    .state()
+                    .mirrored(board.size())
+                    .relabeled(relabeling)
+                    .pack(),
+            );
+        }
+        stats.nodes_expanded += 1;
+
+        for (id, dir) in board.possible_moves() {
+            if let Err(e) = board.move_block(id, dir) {
+                trace!("{} {:?}", e, (id, dir));
+                continue;
+            }
+            let next_g_value = g_value + 1;
+            let state = board.state().pack();
+            let is_better = !closed.contains(&state)
+                && g_values
+                    .get(&state)
+                    .is_none_or(|old_g_value| next_g_value < *old_g_value);
+            if is_better {
+                g_values.insert(state.clone(), next_g_value);
+                parents.insert(state, (id, dir));
+                open.push(Node {
+                    priority: next_g_value as f32 + weight * board.heuristic() as f32,
+                    g_value: next_g_value,
+                    board: board.clone(),
+                });
+            }
+            assert!(board.move_block(id, dir.inverse()).is_ok());
+        }
+    }
+
+    stats.duration = start.elapsed();
+    (None, stats)
+}
+
+/// Cost of moving a block by one cell
+#[derive(Debug, Default, Clone, Copy)]
+pub enum CostModel {
+    /// Every move costs 1
+    #[default]
+    Uniform,
+    /// A move costs the number of cells of the block, e.g. 4 for a 2x2 block
+    CellCount,
+    /// A move costs what the function returns, which should only depend on the block's shape
+    Custom(fn(&Block) -> u32),
+}
+
+impl CostModel {
+    pub fn cost(&self, block: &Block) -> u32 {
+        match self {
+            CostModel::Uniform => 1,
+            CostModel::CellCount => (block.size().x * block.size().y) as u32,
+            CostModel::Custom(cost) => cost(block),
+        }
+    }
+
+    /// Cost of moving the block with given id on the board
+    pub fn move_cost(&self, board: &Board, id: BlockId) -> u32 {
+        board
+            .state()
+            .blocks()
+            .iter()
+            .find(|block| block.id() == id)
+            .map_or(0, |block| self.cost(block))
+    }
+
+    /// Total cost of applying `moves` to the board in order
+    pub fn total_cost(&self, board: &Board, moves: &[Move]) -> Result<u32, String> {
+        let mut board = board.clone();
+        let mut total = 0;
+        for &(id, dir) in moves {
+            total += self.move_cost(&board, id);
+            board.move_block(id, dir)?;
+        }
+        Ok(total)
+    }
+
+    /// Cost of the cheapest block, every move costs at least this much
+    fn min_cost(&self, board: &Board) -> u32 {
+        board
+            .state()
+            .blocks()
+            .iter()
+            .map(|block| self.cost(block))
+            .min()
+            .unwrap_or(0)
+    }
+}
+
+/// A* which minimizes the total cost of moves instead of their count, also return the cost.
+/// The Manhattan distance is scaled by the cheapest block's cost to stay admissible
+pub fn astar_cost(board: Board, model: CostModel) -> Option<(Vec<Move>, u32)> {
+    let unit_cost = model.min_cost(&board) as f32;
+    let mut open = BinaryHeap::new();
+    let mut closed = HashSet::new();
+    let mut g_values = HashMap::new();
+    let mut parents = HashMap::new();
+
+    g_values.insert(board.state().pack(), 0);
+    open.push(Node {
+        priority: unit_cost * board.heuristic() as f32,
+        g_value: 0,
+        board,
+    });
+
+    while let Some(Node {
+        g_value, mut board, ..
+    }) = open.pop()
+    {
+        if board.is_goal() {
+            return Some((reconstruct_moves(board, &parents), g_value as u32));
+        }
+        if !closed.insert(board.state().pack()) {
+            continue;
+        }
+
+        for (id, dir) in board.possible_moves() {
+            let cost = model.move_cost(&board, id) as i32;
+            if let Err(e) = board.move_block(id, dir) {
+                trace!("{} {:?}", e, (id, dir));
+                continue;
+            }
+            let next_g_value = g_value + cost;
+            let state = board.state().pack();
+            let is_better = !closed.contains(&state)
+                && g_values
+                    .get(&state)
+                    .is_none_or(|old_g_value| next_g_value < *old_g_value);
+            if is_better {
+                g_values.insert(state.clone(), next_g_value);
+                parents.insert(state, (id, dir));
+                open.push(Node {
+                    priority: next_g_value as f32 + unit_cost * board.heuristic() as f32,
+                    g_value: next_g_value,
+                    board: board.clone(),
+                });
+            }
+            assert!(board.move_block(id, dir.inverse()).is_ok());
+        }
+    }
+
+    None
+}
+
+/// Greedy best-first search, the solution is not guaranteed to be optimal
+pub fn greedy(board: Board) -> Option<Vec<Move>> {
+    let mut open = BinaryHeap::new();
+    let mut visited = HashSet::new();
+    let mut parents = HashMap::new();
+
+    visited.insert(board.state().pack());
+    open.push(Node {
+        priority: board.heuristic() as f32,
+        g_value: 0,
+        board,
+    });
+
+    while let Some(Node {
+        g_value, mut board, ..
+    }) = open.pop()
+    {
+        if board.is_goal() {
+            return Some(reconstruct_moves(board, &parents));
+        }
+
+        for (id, dir) in board.possible_moves() {
+            if let Err(e) = board.move_block(id, dir) {
+                trace!("{} {:?}", e, (id, dir));
+                continue;
+            }
+            let state = board.state().pack();
+            if visited.insert(state.clone()) {
+                parents.insert(state, (id, dir));
+                open.push(Node {
+                    priority: board.heuristic() as f32,
+                    g_value: g_value + 1,
+                    board: board.clone(),
+                });
+            }
+            assert!(board.move_block(id, dir.inverse()).is_ok());
+        }
+    }
+
+    None
+}
+
+/// Depth-first branch and bound, which finds an optimal solution by DFS,
+/// pruning branches where `g + h` cannot beat the best solution found so far.
+///
+/// `initial_bound` is an upper bound of the optimal solution length,
+/// it is seeded by a greedy search if `None`.
+pub fn dfbnb(board: Board, initial_bound: Option<usize>) -> Option<Vec<Move>> {
+    if board.is_goal() {
+        return Some(vec![]);
+    }
+    let mut best = None;
+    // Only solutions shorter than the bound are searched
+    let mut bound = match initial_bound {
+        Some(bound) => bound + 1,
+        None => {
+            let moves = greedy(board.clone())?;
+            let bound = moves.len();
+            best = Some(moves);
+            bound
+        }
+    };
+
+    let mut board = board;
+    let mut visited = HashSet::from([board.zobrist_hash()]);
+    // Moves to try of each node on current path
+    let mut stack = vec![(board.possible_moves(), 0)];
+    let mut path: Vec<Move> = vec![];
+
+    while let Some((moves, next)) = stack.last_mut() {
+        let (id, dir) = match moves.get(*next) {
+            Some(&m) => m,
+            None => {
+                stack.pop();
+                if let Some((id, dir)) = path.pop() {
+                    visited.remove(&board.zobrist_hash());
+                    assert!(board.move_block(id, dir.inverse()).is_ok());
+                }
+                continue;
+            }
+        };
+        *next += 1;
+        if let Err(e) = board.move_block(id, dir) {
+            trace!("{} {:?}", e, (id, dir));
+            continue;
+        }
+        let f_value = path.len() + 1 + board.heuristic() as usize;
+        if f_value >= bound || !visited.insert(board.zobrist_hash()) {
+            assert!(board.move_block(id, dir.inverse()).is_ok());
+            continue;
+        }
+        path.push((id, dir));
+        if board.is_goal() {
+            debug!("found solution with {} moves", path.len());
+            bound = path.len();
+            best = Some(path.clone());
+            path.pop();
+            visited.remove(&board.zobrist_hash());
+            assert!(board.move_block(id, dir.inverse()).is_ok());
+            continue;
+        }
+        stack.push((board.possible_moves(), 0));
+    }
+
+    best
+}
+
+/// Enumerate distinct optimal solutions, at most `cap` of them.
+///
+/// The optimal length is determined by [`idastar`] first, then every move sequence
+/// of that length reaching the goal is collected, including those which only differ
+/// in the order of independent moves.
+pub fn all_optimal(board: Board, cap: usize) -> Vec<Vec<Move>> {
+    let length = match idastar(board.clone()) {
+        Some(moves) => moves.len(),
+        None => return vec![],
+    };
+    let mut solutions = vec![];
+    if cap == 0 {
+        return solutions;
+    }
+    if length == 0 {
+        solutions.push(vec![]);
+        return solutions;
+    }
+
+    for_each_optimal(board, length, |path, solved| {
+        if solved {
+            solutions.push(path.to_vec());
+            if solutions.len() >= cap {
+                return Visit::Stop;
+            }
+        }
+        Visit::Continue
+    });
+
+    solutions
+}
+
+/// Like [`idastar`], but among solutions of the optimal length, return one moving
+/// the fewest distinct blocks
+pub fn optimal_min_blocks(board: Board) -> Option<Vec<Move>> {
+    let length = idastar(board.clone())?.len();
+    if length == 0 {
+        return Some(vec![]);
+    }
+
+    let mut best: Option<(usize, Vec<Move>)> = None;
+    for_each_optimal(board, length, |path, solved| {
+        let count = path.iter().map(|&(id, _)| id).collect::<HashSet<_>>().len();
+        if matches!(&best, Some((best_count, _)) if count >= *best_count) {
+            return Visit::Prune;
+        }
+        if solved {
+            best = Some((count, path.to_vec()));
+        }
+        Visit::Continue
+    });
+
+    best.map(|(_, moves)| moves)
+}
+
+/// What [`for_each_optimal`] should do after visiting a path
+enum Visit {
+    Continue,
+    /// Skip paths extending this one
+    Prune,
+    Stop,
+}
+
+/// DFS over paths which may still reach the goal in exactly `length` moves, in sorted
+/// move order. `visit` is called with every such path and whether it reaches the goal
+fn for_each_optimal<F>(board: Board, length: usize, mut visit: F)
+where
+    F: FnMut(&[Move], bool) -> Visit,
+{
+    let mut board = board;
+    let mut visited = HashSet::from([board.zobrist_hash()]);
+    // Moves to try of each node on current path, sorted to make the result deterministic
+    let mut possible_moves = board.possible_moves();
+    possible_moves.sort();
+    let mut stack = vec![(possible_moves, 0)];
+    let mut path: Vec<Move> = vec![];
+
+    while let Some((moves, next)) = stack.last_mut() {
+        let (id, dir) = match moves.get(*next) {
+            Some(&m) => m,
+            None => {
+                stack.pop();
+                if let Some((id, dir)) = path.pop() {
+                    visited.remove(&board.zobrist_hash());
+                    assert!(board.move_block(id, dir.inverse()).is_ok());
+                }
+                continue;
+            }
+        };
+        *next += 1;
+        if let Err(e) = board.move_block(id, dir) {
+            trace!("{} {:?}", e, (id, dir));
+            continue;
+        }
+        let f_value = path.len() + 1 + board.heuristic() as usize;
+        if f_value > length || !visited.insert(board.zobrist_hash()) {
+            assert!(board.move_block(id, dir.inverse()).is_ok());
+            continue;
+        }
+        path.push((id, dir));
+        let solved = path.len() == length && board.is_goal();
+        if path.len() < length || solved {
+            match visit(&path, solved) {
+                Visit::Continue if !solved => {
+                    let mut possible_moves = board.possible_moves();
+                    possible_moves.sort();
+                    stack.push((possible_moves, 0));
+                    continue;
+                }
+                Visit::Stop => break,
+                _ => {}
+            }
+        }
+        path.pop();
+        visited.remove(&board.zobrist_hash());
+        assert!(board.move_block(id, dir.inverse()).is_ok());
+    }
+}
+
+/// Beam search, which keeps only the `width` best boards by heuristic at each depth.
+/// It is incomplete, so it might return `None` even for solvable boards
+pub fn beam(board: Board, width: usize, max_depth: usize) -> Option<Vec<Move>> {
+    let mut visited = HashSet::new();
+    let mut parents = HashMap::new();
+    let mut level = vec![board];
+
+    visited.insert(level[0].state().pack());
+
+    for depth in 0..=max_depth {
+        debug!("depth: {}, beam size: {}", depth, level.len());
+        if let Some(board) = level.iter().find(|board| board.is_goal()) {
+            return Some(reconstruct_moves(board.clone(), &parents));
+        }
+        if depth == max_depth {
+            break;
+        }
+
+        let mut children = vec![];
+        for mut board in level {
+            for (id, dir) in board.possible_moves() {
+                if let Err(e) = board.move_block(id, dir) {
+                    trace!("{} {:?}", e, (id, dir));
+                    continue;
+                }
+                let state = board.state().pack();
+                if visited.insert(state.clone()) {
+                    parents.insert(state, (id, dir));
+                    children.push((board.heuristic(), board.clone()));
+                }
+                assert!(board.move_block(id, dir.inverse()).is_ok());
+            }
+        }
+        if children.is_empty() {
+            break;
+        }
+        children.sort_by_key(|(h, _)| *h);
+        level = children
+            .into_iter()
+            .take(width)
+            .map(|(_, board)| board)
+            .collect();
+    }
+
+    None
+}
+
+/// BFS
+pub fn bfs(board: Board) -> Option<Vec<Move>> {
+    bfs_search(board, Default::default(), &mut |_| {})
+        .0
+        .into_moves()
+}
+
+/// BFS which stops once `cancel` is set and calls `on_progress` periodically,
+/// the limit of progress events is the depth being expanded.
+/// Also return statistics of the search
+pub fn bfs_search(
+    board: Board,
+    cancel: Arc<AtomicBool>,
+    on_progress: &mut dyn FnMut(ProgressEvent),
+) -> (SearchOutcome, SearchStats) {
+    let start = Instant::now();
+    let mut stats = SearchStats::default();
+    let mut progress = Progress::new(on_progress);
+    let mut frontier = VecDeque::new();
+    let mut visited = HashSet::new();
+    let mut parents = HashMap::new();
+
+    visited.insert(board.state().pack());
+    frontier.push_back((board, 0));
+
+    let outcome = loop {
+        let (mut board, depth) = match frontier.pop_front() {
+            Some(node) => node,
+            None => break SearchOutcome::NoSolution,
+        };
+        if board.is_goal() {
+            break SearchOutcome::Solved(reconstruct_moves(board, &parents));
+        }
+        if stats.nodes_expanded.is_multiple_of(CHECK_INTERVAL)
+            && cancel.load(atomic::Ordering::Relaxed)
+        {
+            break SearchOutcome::Cancelled;
+        }
+        if depth > stats.max_depth {
+            progress.report(stats.nodes_expanded);
+            progress.begin_iteration(depth as i32, stats.nodes_expanded);
+            stats.max_depth = depth;
+        }
+        stats.nodes_expanded += 1;
+        progress.tick(stats.nodes_expanded);
+
+        for (id, dir) in board.possible_moves() {
+            if let Err(e) = board.move_block(id, dir) {
+                trace!("{} {:?}", e, (id, dir));
+                continue;
+            }
+            let state = board.state().pack();
+            if visited.insert(state.clone()) {
+                parents.insert(state, (id, dir));
+                frontier.push_back((board.clone(), depth + 1));
+            }
+            assert!(board.move_block(id, dir.inverse()).is_ok());
+        }
+    };
+    progress.report(stats.nodes_expanded);
+    stats.duration = start.elapsed();
+
+    (outcome, stats)
+}
+
+/// Boards with at most this many cells have at most 9! states whatever the blocks are
+const AUTO_TINY_CELLS: usize = 9;
+/// Boards with at most this many cells and blocks have small state spaces
+const AUTO_SMALL_CELLS: usize = 16;
+const AUTO_SMALL_BLOCKS: usize = 6;
+/// Boards with more cells are large
+const AUTO_LARGE_CELLS: usize = 30;
+/// Boards with fewer than one hole per this many cells are heavily blocked
+const AUTO_CELLS_PER_HOLE: usize = 5;
+
+/// Algorithm picked by [`auto`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoBackend {
+    /// BFS, for small state spaces
+    Bfs,
+    /// IDA* with manhattan distance
+    IdaStar,
+    /// IDA* with linear conflicts and move ordering, for large or heavily blocked boards
+    IdaStarLinearConflict,
+}
+
+impl AutoBackend {
+    /// Pick the backend by board characteristics
+    pub fn select(board: &Board) -> Self {
+        let size = board.size();
+        let cells = size.x as usize * size.y as usize;
+        let blocks = board.state().blocks().len();
+        if cells <= AUTO_TINY_CELLS || (cells <= AUTO_SMALL_CELLS && blocks <= AUTO_SMALL_BLOCKS) {
+            AutoBackend::Bfs
+        } else if cells > AUTO_LARGE_CELLS || board.hole_count() * AUTO_CELLS_PER_HOLE < cells {
+            AutoBackend::IdaStarLinearConflict
+        } else {
+            AutoBackend::IdaStar
+        }
+    }
+
+    /// Heuristic and options of IDA* backends, `None` for BFS
+    pub fn idastar_config(self) -> Option<(Box<dyn Heuristic>, SearchOptions)> {
+        match self {
+            AutoBackend::Bfs => None,
+            AutoBackend::IdaStar => Some((Box::new(ManhattanHeuristic), SearchOptions::default())),
+            AutoBackend::IdaStarLinearConflict => Some((
+                Box::new(LinearConflictHeuristic),
+                SearchOptions {
+                    order_children: true,
+                    ..Default::default()
+                },
+            )),
+        }
+    }
+}
+
+/// Solve with the algorithm picked by [`AutoBackend::select`]
+pub fn auto(board: Board) -> Option<Vec<Move>> {
+    auto_search(board, Default::default(), &mut |_| {})
+        .0
+        .into_moves()
+}
+
+/// Solve with the algorithm picked by [`AutoBackend::select`], which stops once `cancel`
+/// is set and calls `on_progress` periodically. IDA* never proves a board unsolvable,
+/// so boards failing cheap solvability checks have no solution without searching.
+/// Also return statistics of the search
+pub fn auto_search(
+    board: Board,
+    cancel: Arc<AtomicBool>,
+    on_progress: &mut dyn FnMut(ProgressEvent),
+) -> (SearchOutcome, SearchStats) {
+    if board.quick_solvability() == Solvability::Unsolvable
+        || board.parity_solvability() == Solvability::Unsolvable
+    {
+        return (SearchOutcome::NoSolution, SearchStats::default());
+    }
+    match AutoBackend::select(&board).idastar_config() {
+        Some((heuristic, options)) => idastar_search(
+            board,
+            &*heuristic,
+            &options,
+            &SearchConstraints::default(),
+            cancel,
+            on_progress,
+            None,
+        ),
+        None => bfs_search(board, cancel, on_progress),
+    }
+}
+
+/// Walk back from `board` through the moves that led to each state,
+/// return the moves from the initial state to `board`'s state
+fn reconstruct_moves(mut board: Board, parents: &HashMap<PackedState, Move>) -> Vec<Move> {
+    let mut moves = vec![];
+    while let Some(&(id, dir)) = parents.get(&board.state().pack()) {
+        moves.push((id, dir));
+        assert!(board.move_block(id, dir.inverse()).is_ok());
+    }
+    moves.reverse();
+    moves
+}
+
+/// What a command entered in manual mode did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManualStep {
+    /// The move is applied and recorded
+    Moved(Move),
+    /// The command is a move, but it is illegal on the board
+    Rejected(String),
+    /// The command cannot be parsed as a move
+    Invalid(String),
+}
+
+/// Apply a single command of manual mode to `board`, only legal moves are recorded in `moves`
+pub fn manual_step(board: &mut Board, moves: &mut Vec<Move>, command: &str) -> ManualStep {
+    let (id, dir) = match parse_move(command.trim()) {
+        Ok(m) => m,
+        Err(e) => return ManualStep::Invalid(e),
+    };
+    match board.move_block(id, dir) {
+        Ok(()) => {
+            moves.push((id, dir));
+            ManualStep::Moved((id, dir))
+        }
+        Err(e) => ManualStep::Rejected(e.to_string()),
+    }
+}
+
+pub fn manual(mut board: Board) -> Option<Vec<Move>> {
+    use std::io::{self, IsTerminal};
+
+    let input = io::stdin();
+    let mut buffer = String::new();
+    let mut moves = vec![];
+    let style = RenderStyle::for_terminal(io::stderr().is_terminal());
+
+    eprint!("{}", board.render(style));
+    loop {
+        eprintln!("Enter a move: ");
+        eprint!("Possible values are:");
+        for m in board.possible_moves_iter() {
+            eprint!(" {:?}", m);
+        }
+        eprintln!();
+        buffer.clear();
+        let bytes = input.read_line(&mut buffer).expect("Read move fail");
+        if bytes == 0 {
+            break;
+        }
+        match manual_step(&mut board, &mut moves, &buffer) {
+            ManualStep::Moved(_) => {}
+            ManualStep::Rejected(e) => {
+                eprintln!("{}", e);
+                continue;
+            }
+            ManualStep::Invalid(e) => {
+                eprintln!("Invalid command: {}", e);
+                continue;
+            }
+        }
+        eprint!("{}", board.render(style));
+        if board.is_goal() {
+            eprintln!("Reach goal in {} moves", board.move_count());
+            break;
+        }
+    }
+
+    Some(moves)
+}
+
+/// Parse a move like `3U`, which is block id followed by one of U, D, L and R
+pub fn parse_move(cmd: &str) -> Result<Move, String> {
+    let dir = cmd.chars().last().ok_or("Empty command")?;
+    let dir = match dir {
+        'U' => Dir::Up,
+        'D' => Dir::Down,
+        'L' => Dir::Left,
+        'R' => Dir::Right,
+        _ => return Err(format!("Invalid direction: {}", dir)),
+    };
+
+    let id = {
+        let mut chars = cmd.chars();
+        chars.next_back();
+        chars
+            .as_str()
+            .parse::<BlockId>()
+            .map_err(|e| format!("Invalid id: {}", e))?
+    };
+
+    Ok((id, dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heuristic::{MatchedHeuristic, ZeroHeuristic};
+    use rand::{rngs::StdRng, SeedableRng};
+    use sliding_puzzle_core::{Matrix2D, ShuffleOptions, Vec2};
+
+    /// Seeded random 4x4 boards, so that tests always search the same ones
+    fn generated_boards(count: usize) -> Vec<Board> {
+        let mut rng = StdRng::seed_from_u64(7);
+        (0..count)
+            .map(|_| {
+                Board::generate_with_rng(Vec2::new(4, 4), 10, 24, &mut rng)
+                    .expect("Invalid generate parameters")
+            })
+            .collect()
+    }
+
+    fn boards() -> Result<Vec<Board>, String> {
+        [
+            "3 3\n\
+            1 1 2\n\
+            0 3 0\n\
+            0 4 4\n\
+            ",
+            "5 4\n\
+            1 2 2 3\n\
+            1 2 2 3\n\
+            4 5 5 6\n\
+            4 7 8 6\n\
+            9 0 10 0\n\
+            ",
+            "5 4\n\
+            1 2 2 3\n\
+            1 2 2 3\n\
+            4 0 5 5\n\
+            4 0 7 6\n\
+            9 10 8 6\n\
+            ",
+        ]
+        .iter()
+        .map(|input| Ok(input.parse::<Board>()?))
+        .collect()
+    }
+
+    /// Swapping two tiles of a 2x2 puzzle makes it unsolvable
+    fn unsolvable_board() -> Result<Board, String> {
+        "2 2\n\
+        2 1\n\
+        3 0\n\
+        "
+        .parse::<Board>()
+        .map_err(String::from)
+    }
+
+    /// Apply moves to the board and check whether it reaches the goal
+    fn reach_goal(mut board: Board, moves: &[Move]) -> Result<bool, String> {
+        for (id, dir) in moves {
+            board.move_block(*id, *dir)?;
+        }
+        Ok(board.is_goal())
+    }
+
+    #[test]
+    fn test_idastar_parallel_is_optimal() -> Result<(), String> {
+        for board in boards()? {
+            let expected = idastar(board.clone()).map(|moves| moves.len());
+            for threads in [1, 4] {
+                let moves = idastar_parallel(board.clone(), threads);
+                assert_eq!(moves.as_ref().map(|moves| moves.len()), expected);
+                if let Some(moves) = moves {
+                    assert!(reach_goal(board.clone(), &moves)?);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_iddfs_parallel_is_optimal() -> Result<(), String> {
+        let mut boards = boards()?;
+        // Solutions shorter than the prefixes
+        for board in boards.clone() {
+            let moves = bfs(board.clone()).ok_or("No solution found")?;
+            for skip in moves.len().saturating_sub(2)..moves.len() {
+                let mut board = board.clone();
+                for &(id, dir) in &moves[..skip] {
+                    board.move_block(id, dir)?;
+                }
+                boards.push(board);
+            }
+        }
+        boards.extend(generated_boards(16));
+        boards.push(unsolvable_board()?);
+
+        for board in boards {
+            let expected = bfs(board.clone()).map(|moves| moves.len());
+            for threads in [1, 4] {
+                let moves = iddfs_parallel(board.clone(), threads);
+                assert_eq!(moves.as_ref().map(|moves| moves.len()), expected);
+                if let Some(moves) = moves {
+                    assert!(reach_goal(board.clone(), &moves)?);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_shuffle_options() {
+        let average_length = |options: ShuffleOptions| {
+            let mut rng = StdRng::seed_from_u64(7);
+            let mut total = 0;
+            for _ in 0..100 {
+                let board = Board::generate_with_shapes_and_rng(
+                    Vec2::new(4, 4),
+                    10,
+                    8,
+                    &Board::DEFAULT_BLOCK_SIZES,
+                    options,
+                    &mut rng,
+                )
+                .expect("Invalid generate parameters");
+                if options.forbid_goal {
+                    assert!(!board.is_goal());
+                }
+                total += idastar(board).expect("Generated boards are solvable").len();
+            }
+            total as f64 / 100.0
+        };
+        let off = ShuffleOptions {
+            forbid_backtrack: false,
+            forbid_goal: false,
+        };
+
+        assert!(average_length(ShuffleOptions::default()) > average_length(off));
+    }
+
+    #[test]
+    fn test_idastar_is_optimal_on_generated_boards() {
+        for board in generated_boards(32) {
+            let expected = bfs(board.clone()).map(|moves| moves.len());
+
+            assert_eq!(idastar(board.clone()).map(|moves| moves.len()), expected);
+            assert_eq!(
+                idastar_parallel(board, 2).map(|moves| moves.len()),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_idastar_expands_nodes_at_the_limit() -> Result<(), String> {
+        // Every move decreases the heuristic, so all nodes on the solution have
+        // f-value equal to the initial f-limit
+        let board = "2 3\n\
+        1 0 0\n\
+        2 0 3\n\
+        "
+        .parse::<Board>()?;
+        let (moves, stats) =
+            idastar_with_stats(board.clone(), &ManhattanHeuristic, &Default::default());
+        let moves = moves.ok_or("No solution found")?;
+        assert_eq!(moves.len() as i32, board.heuristic());
+        assert_eq!(stats.iterations, 1);
+
+        // Blocks 1 and 2 have to pass each other, the heuristic keeps its parity after
+        // every move, so the next contour is 2 moves deeper
+        let board = "2 3\n\
+        2 1 0\n\
+        0 0 0\n\
+        "
+        .parse::<Board>()?;
+        let (moves, stats) =
+            idastar_with_stats(board.clone(), &ManhattanHeuristic, &Default::default());
+        let moves = moves.ok_or("No solution found")?;
+        assert_eq!(moves.len() as i32, board.heuristic() + 2);
+        assert!(reach_goal(board, &moves)?);
+        assert_eq!(stats.iterations, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_linear_conflict_is_admissible() -> Result<(), String> {
+        let mut boards = boards()?;
+        boards.extend(generated_boards(16));
+        for mut board in boards {
+            let moves = match bfs(board.clone()) {
+                Some(moves) => moves,
+                None => continue,
+            };
+            // Every state on an optimal path is exactly `remain` moves away from the goal
+            for (i, (id, dir)) in moves.iter().enumerate() {
+                let remain = (moves.len() - i) as i32;
+                assert!(board.heuristic_linear_conflict() <= remain);
+                board.move_block(*id, *dir)?;
+            }
+            assert_eq!(board.heuristic_linear_conflict(), 0);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_idastar_with_linear_conflict_is_optimal() -> Result<(), String> {
+        for board in boards()? {
+            let manhattan = idastar_with(board.clone(), &ManhattanHeuristic);
+            let linear_conflict = idastar_with(board.clone(), &LinearConflictHeuristic);
+            let expected = bfs(board).map(|moves| moves.len());
+            assert_eq!(manhattan.map(|moves| moves.len()), expected);
+            assert_eq!(linear_conflict.map(|moves| moves.len()), expected);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_idastar_with_matched_heuristic_is_optimal() -> Result<(), String> {
+        for board in boards()? {
+            let moves = idastar_with(board.clone(), &MatchedHeuristic);
+            let expected = bfs(board.clone()).map(|moves| moves.len());
+            assert_eq!(moves.as_ref().map(|moves| moves.len()), expected);
+            if let Some(moves) = moves {
+                assert!(reach_goal(board, &moves)?);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_idastar_with_ordered_children_is_optimal() -> Result<(), String> {
+        let options = SearchOptions {
+            order_children: true,
+            ..Default::default()
+        };
+        for board in boards()? {
+            let expected = idastar(board.clone()).map(|moves| moves.len());
+            let moves = idastar_with_options(board.clone(), &ManhattanHeuristic, &options);
+            assert_eq!(moves.as_ref().map(|moves| moves.len()), expected);
+            if let Some(moves) = moves {
+                assert!(reach_goal(board, &moves)?);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_searches_with_pruning_are_optimal() -> Result<(), String> {
+        let options = SearchOptions {
+            prune_dead_ends: true,
+            ..Default::default()
+        };
+        for board in boards()? {
+            let (expected, stats) =
+                idastar_with_stats(board.clone(), &ManhattanHeuristic, &Default::default());
+            let expected = expected.ok_or("No solution found")?;
+            let (moves, pruned_stats) =
+                idastar_with_stats(board.clone(), &ManhattanHeuristic, &options);
+            let moves = moves.ok_or("No solution found")?;
+            assert_eq!(moves.len(), expected.len());
+            // Reachable boards of a solvable one are never dead ends, so no node is skipped
+            assert_eq!(pruned_stats.nodes_expanded, stats.nodes_expanded);
+            let (iddfs_moves, pruned_stats) = iddfs_with_options_and_stats(board.clone(), &options);
+            assert_eq!(iddfs_moves.map(|moves| moves.len()), Some(expected.len()));
+            assert_eq!(
+                pruned_stats.nodes_expanded,
+                iddfs_with_stats(board.clone()).1.nodes_expanded
+            );
+
+            // No state on the solution path should be pruned
+            let mut board = board;
+            assert!(!prune::is_dead_end(&board));
+            for (id, dir) in moves {
+                board.move_block(id, dir)?;
+                assert!(!prune::is_dead_end(&board));
+            }
+            assert!(board.is_goal());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_searches_prune_dead_end() -> Result<(), String> {
+        // With a single hole, block 2 is jammed against the left edge away from its goal,
+        // while the other blocks can still wander around
+        let board = "2 4\n\
+        2 1 1 3\n\
+        2 4 0 5\n\
+        "
+        .parse::<Board>()?;
+        assert_ne!(board.quick_solvability(), Solvability::Unsolvable);
+        let options = SearchOptions {
+            prune_dead_ends: true,
+            ..Default::default()
+        };
+
+        let (moves, stats) = idastar_with_stats(board.clone(), &ManhattanHeuristic, &options);
+        assert_eq!(moves, None);
+        assert_eq!(stats.nodes_expanded, 0);
+        let (moves, stats) =
+            idastar_with_stats(board.clone(), &ManhattanHeuristic, &Default::default());
+        assert_eq!(moves, None);
+        assert!(stats.nodes_expanded > 0);
+
+        let (moves, stats) = iddfs_with_options_and_stats(board.clone(), &options);
+        assert_eq!(moves, None);
+        assert_eq!(stats.nodes_expanded, 0);
+        let (moves, stats) = iddfs_with_stats(board);
+        assert_eq!(moves, None);
+        assert!(stats.nodes_expanded > 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_idastar_merging_equivalent_blocks() -> Result<(), String> {
+        let options = SearchOptions {
+            merge_equivalent_blocks: true,
+            ..Default::default()
+        };
+        for board in boards()? {
+            let exact = idastar(board.clone()).ok_or("No solution found")?;
+            let moves = idastar_with_options(board.clone(), &ManhattanHeuristic, &options)
+                .ok_or("No solution found")?;
+
+            // Moves are legal on the original board, and reach the exact goal optimally
+            assert!(reach_goal(board.clone(), &moves)?);
+            assert_eq!(moves.len(), exact.len());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_idastar_merging_swapped_blocks() -> Result<(), String> {
+        // Only two 1x1 blocks are swapped, which the merged search takes as solved,
+        // so the search falls back to telling the blocks apart
+        let board = "3 3\n\
+        2 1 3\n\
+        4 4 0\n\
+        5 5 0\n\
+        "
+        .parse::<Board>()?;
+        let options = SearchOptions {
+            merge_equivalent_blocks: true,
+            ..Default::default()
+        };
+        let exact = idastar(board.clone()).ok_or("No solution found")?;
+        let moves = idastar_with_options(board.clone(), &ManhattanHeuristic, &options)
+            .ok_or("No solution found")?;
+
+        assert!(!moves.is_empty());
+        assert!(reach_goal(board, &moves)?);
+        assert_eq!(moves.len(), exact.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ordered_moves() -> Result<(), String> {
+        let mut board = "5 4\n\
+        1 2 2 3\n\
+        1 2 2 3\n\
+        4 5 5 6\n\
+        4 7 8 6\n\
+        9 0 10 0\n\
+        "
+        .parse::<Board>()?;
+        let original = board.clone();
+        let moves = ordered_moves(&mut board, &ManhattanHeuristic);
+
+        assert_eq!(board, original);
+        assert_eq!(moves.first(), Some(&(10, Dir::Left)));
+        let mut expected = board.possible_moves();
+        expected.sort();
+        let mut sorted = moves.clone();
+        sorted.sort();
+        assert_eq!(sorted, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_idastar_with_zero_heuristic_is_optimal() -> Result<(), String> {
+        for board in boards()? {
+            let expected = iddfs(board.clone()).map(|moves| moves.len());
+            let moves = idastar_with(board, &ZeroHeuristic);
+            assert_eq!(moves.map(|moves| moves.len()), expected);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_deep_search_does_not_overflow() -> Result<(), String> {
+        // The only block has to go through every cell in the row, which is deep enough to
+        // overflow the tiny stack if each node consumes a stack frame
+        let width = 127;
+        let mut row = vec!["0"; width];
+        row[width - 1] = "1";
+        let board = format!("1 {}\n{}\n", width, row.join(" ")).parse::<Board>()?;
+        let handle = std::thread::Builder::new()
+            .stack_size(32 * 1024)
+            .spawn(move || {
+                let mut board = board;
+                let depth =
+                    DfsSearch::new(SearchLimits::default(), Default::default(), &mut |_| {})
+                        .run(&mut board.clone(), i32::MAX)
+                        .map(|moves| moves.len());
+                let options = SearchOptions::default();
+                let cancelled = AtomicBool::new(false);
+                let f_limit =
+                    IdaStarSearch::new(&ManhattanHeuristic, &options, &cancelled, &mut |_| {})
+                        .run(&mut board, 0, i32::MAX)
+                        .map(|moves| moves.len());
+                (depth, f_limit)
+            })
+            .map_err(|e| e.to_string())?;
+        let (depth, f_limit) = handle.join().map_err(|_| "Search panicked")?;
+
+        assert_eq!(depth, Ok(width - 1));
+        assert_eq!(f_limit, Ok(width - 1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_large_sparse_board() -> Result<(), String> {
+        let size = 60;
+        let mut grid = Matrix2D::fill(Vec2::new(size, size), 0);
+        grid.try_fill(Vec2::new(size - 2, size - 2), Vec2::new(2, 2), 1)?;
+        let board = Board::try_from(grid)?;
+        assert_eq!(board.heuristic(), 2 * (size as i32 - 2));
+
+        let moves = idastar(board.clone()).ok_or("No solution found")?;
+        assert_eq!(moves.len(), board.heuristic() as usize);
+        assert!(reach_goal(board, &moves)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_iddfs_stats() -> Result<(), String> {
+        let board = "5 4\n\
+        1 2 2 3\n\
+        1 2 2 3\n\
+        4 0 5 5\n\
+        4 0 7 6\n\
+        9 10 8 6\n\
+        "
+        .parse::<Board>()?;
+        let (moves, stats) = iddfs_with_stats(board);
+        let moves = moves.ok_or("No solution found")?;
+
+        assert_eq!(stats.iterations as usize, moves.len());
+        assert_eq!(stats.max_depth as usize, moves.len());
+        assert!(stats.nodes_expanded >= moves.len() as u64);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_iddfs_limited() -> Result<(), String> {
+        let board = boards()?.remove(2);
+        let expected = iddfs(board.clone()).ok_or("No solution found")?;
+        let moves = iddfs_limited(board.clone(), SearchLimits::default())
+            .into_moves()
+            .ok_or("No solution found")?;
+
+        assert_eq!(moves.len(), expected.len());
+        assert!(reach_goal(board, &moves)?);
+        assert_eq!(
+            iddfs_limited(unsolvable_board()?, SearchLimits::default()),
+            SearchOutcome::NoSolution
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_iddfs_max_nodes() -> Result<(), String> {
+        let board = boards()?.remove(2);
+        let limits = SearchLimits {
+            max_nodes: Some(3),
+            ..Default::default()
+        };
+        let (outcome, stats) = iddfs_limited_with_stats(board, limits);
+
+        assert_eq!(outcome, SearchOutcome::LimitReached);
+        assert_eq!(stats.nodes_expanded, 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_iddfs_max_duration() -> Result<(), String> {
+        // Far from the goal, so that IDDFS cannot finish in time
+        let board = "5 5\n\
+        2 4 4 7 7\n\
+        6 5 3 0 0\n\
+        9 9 3 12 10\n\
+        0 8 8 12 10\n\
+        0 8 8 1 11\n\
+        "
+        .parse::<Board>()?;
+        let limits = SearchLimits {
+            max_duration: Some(Duration::from_millis(50)),
+            ..Default::default()
+        };
+        let start = Instant::now();
+
+        assert_eq!(iddfs_limited(board, limits), SearchOutcome::LimitReached);
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        Ok(())
+    }
+
+    /// A board which takes far longer than the tests are willing to wait
+    fn huge_board() -> Result<Board, String> {
+        "6 6\n\
+        1 2 3 0 4 5\n\
+        6 7 7 8 9 10\n\
+        11 12 13 14 0 15\n\
+        16 17 18 19 20 21\n\
+        22 0 23 24 25 26\n\
+        27 28 29 30 31 0\n\
+        "
+        .parse::<Board>()
+        .map_err(String::from)
+    }
+
+    fn assert_cancelled(search: impl FnOnce(Board, Arc<AtomicBool>) -> SearchOutcome) {
+        let board = huge_board().expect("Invalid board");
+        let cancel = Arc::new(AtomicBool::new(false));
+        let canceller = {
+            let cancel = cancel.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(50));
+                cancel.store(true, atomic::Ordering::Relaxed);
+            })
+        };
+        let start = Instant::now();
+
+        assert_eq!(search(board, cancel), SearchOutcome::Cancelled);
+        assert!(start.elapsed() < Duration::from_secs(5));
+        canceller.join().expect("Canceller panicked");
+    }
+
+    #[test]
+    fn test_iddfs_cancellable() {
+        assert_cancelled(iddfs_cancellable);
+    }
+
+    #[test]
+    fn test_idastar_cancellable() {
+        assert_cancelled(idastar_cancellable);
+    }
+
+    #[test]
+    fn test_cancellable_not_cancelled() -> Result<(), String> {
+        let board = boards()?.remove(2);
+
+        for outcome in [
+            iddfs_cancellable(board.clone(), Default::default()),
+            idastar_cancellable(board.clone(), Default::default()),
+        ] {
+            let moves = outcome.into_moves().ok_or("No solution found")?;
+            assert!(reach_goal(board.clone(), &moves)?);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_iddfs_with_progress() -> Result<(), String> {
+        let board = boards()?.remove(2);
+        let (_, stats) = iddfs_with_stats(board.clone());
+        let mut events = vec![];
+        let moves = iddfs_with_progress(board.clone(), |event| events.push(event))
+            .ok_or("No solution found")?;
+
+        assert!(reach_goal(board, &moves)?);
+        // One report at the end of each iteration
+        assert_eq!(events.len() as u32, stats.iterations);
+        assert_eq!(
+            events.iter().map(|e| e.limit).collect::<Vec<_>>(),
+            (1..=stats.iterations as i32).collect::<Vec<_>>()
+        );
+        assert!(events.windows(2).all(|w| w[0].elapsed <= w[1].elapsed));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_idastar_with_progress() -> Result<(), String> {
+        let board = boards()?.remove(2);
+        let mut events = vec![];
+        let moves = idastar_with_progress(board.clone(), |event| events.push(event))
+            .ok_or("No solution found")?;
+
+        assert!(reach_goal(board.clone(), &moves)?);
+        assert_eq!(events.first().map(|e| e.limit), Some(board.heuristic()));
+        assert!(events.windows(2).all(|w| w[0].limit < w[1].limit));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_progress_is_reported_within_iteration() {
+        let mut events = vec![];
+        let mut on_progress = |event| events.push(event);
+        let mut progress = Progress::new(&mut on_progress);
+        progress.begin_iteration(3, 10);
+        for nodes_expanded in 11..=10 + 2 * PROGRESS_INTERVAL {
+            progress.tick(nodes_expanded);
+        }
+
+        assert_eq!(
+            events.iter().map(|e| e.nodes_expanded).collect::<Vec<_>>(),
+            vec![PROGRESS_INTERVAL - 10, 2 * PROGRESS_INTERVAL - 10]
+        );
+        assert!(events.iter().all(|e| e.limit == 3));
+    }
+
+    #[test]
+    fn test_idastar_stats() -> Result<(), String> {
+        for board in boards()? {
+            let (moves, stats) =
+                idastar_with_stats(board, &ManhattanHeuristic, &SearchOptions::default());
+            let moves = moves.ok_or("No solution found")?;
+
+            assert!(stats.iterations >= 1);
+            assert!(stats.max_depth as usize >= moves.len());
+            assert!(stats.nodes_expanded >= moves.len() as u64);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_astar_reach_goal() -> Result<(), String> {
+        for board in boards()? {
+            let moves = astar(board.clone()).ok_or("No solution found")?;
+            assert!(reach_goal(board, &moves)?);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_astar_is_optimal() -> Result<(), String> {
+        for board in boards()? {
+            let expected = iddfs(board.clone()).map(|moves| moves.len());
+            assert_eq!(astar(board.clone()).map(|moves| moves.len()), expected);
+            assert_eq!(idastar(board).map(|moves| moves.len()), expected);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_retarget_changes_solution() -> Result<(), String> {
+        let mut board = "2 3\n\
+        1 0 0\n\
+        2 0 3\n\
+        "
+        .parse::<Board>()?;
+        let goals = [
+            "2 3\n1 0 0\n2 0 3\n\n0 0 1\n2 0 3\n",
+            "2 3\n1 0 0\n2 0 3\n\n1 2 0\n0 0 3\n",
+        ]
+        .iter()
+        .map(|input| Ok(input.parse::<Board>()?.goal().clone()))
+        .collect::<Result<Vec<_>, String>>()?;
+
+        let mut solutions = vec![];
+        for goal in goals {
+            board.retarget(goal)?;
+            let moves = idastar(board.clone()).ok_or("No solution found")?;
+            assert_eq!(moves.len(), board.heuristic() as usize);
+            assert!(reach_goal(board.clone(), &moves)?);
+            solutions.push(moves);
+        }
+        assert_ne!(solutions[0], solutions[1]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_transformed_solution() -> Result<(), String> {
+        for board in boards()? {
+            let moves = idastar(board.clone()).ok_or("No solution found")?;
+            let map = |dir_map: fn(Dir) -> Dir| {
+                moves
+                    .iter()
+                    .map(|&(id, dir)| (id, dir_map(dir)))
+                    .collect::<Vec<_>>()
+            };
+            assert!(reach_goal(board.rotated90(), &map(Dir::rotate90))?);
+            assert!(reach_goal(board.mirrored_h(), &map(Dir::mirror_h))?);
+            assert!(reach_goal(board.mirrored_v(), &map(Dir::mirror_v))?);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cropped_solution() -> Result<(), String> {
+        for board in boards()? {
+            let padded = board.embed(board.size() + Vec2::new(2, 3), Vec2::new(1, 2))?;
+            let cropped = padded.crop_to_content();
+            assert_eq!(cropped, board);
+            let moves = idastar(cropped).ok_or("No solution found")?;
+            assert!(reach_goal(padded, &moves)?);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bfs_reach_goal() -> Result<(), String> {
+        for board in boards()? {
+            let moves = bfs(board.clone()).ok_or("No solution found")?;
+            assert!(reach_goal(board, &moves)?);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bfs_is_optimal() -> Result<(), String> {
+        for board in boards()? {
+            let expected = iddfs(board.clone()).map(|moves| moves.len());
+            assert_eq!(bfs(board).map(|moves| moves.len()), expected);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_depth_limited() -> Result<(), String> {
+        let board = "2 3\n\
+        1 0 0\n\
+        2 0 3\n\
+        "
+        .parse::<Board>()?;
+
+        let moves = match depth_limited(&board, 3) {
+            DepthLimitedResult::Found(moves) => moves,
+            result => return Err(format!("Unexpected result: {:?}", result)),
+        };
+        assert_eq!(moves.len(), 3);
+        assert_eq!(depth_limited(&board, 2), DepthLimitedResult::Cutoff);
+        assert_eq!(depth_limited(&board, 0), DepthLimitedResult::Cutoff);
+
+        let mut solved = board;
+        for (id, dir) in moves {
+            solved.move_block(id, dir)?;
+        }
+        assert_eq!(depth_limited(&solved, 0), DepthLimitedResult::Found(vec![]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_depth_limited_exhausted() -> Result<(), String> {
+        // Blocks can never pass each other, and each of them can only move one step
+        let board = "1 3\n\
+        2 0 1\n\
+        "
+        .parse::<Board>()?;
+        assert_eq!(depth_limited(&board, 2), DepthLimitedResult::Cutoff);
+        assert_eq!(depth_limited(&board, 3), DepthLimitedResult::Exhausted);
+        assert_eq!(depth_limited(&board, 8), DepthLimitedResult::Exhausted);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_iddfs_is_optimal() -> Result<(), String> {
+        let mut boards = boards()?;
+        boards.extend(generated_boards(16));
+        for board in boards {
+            let expected = bfs(board.clone()).map(|moves| moves.len());
+            let moves = iddfs(board.clone());
+            assert_eq!(moves.as_ref().map(|moves| moves.len()), expected);
+            if let Some(moves) = &moves {
+                assert!(reach_goal(board.clone(), moves)?);
+            }
+            if let Some(optimal) = idastar(board.clone()) {
+                assert_eq!(moves.map(|moves| moves.len()), Some(optimal.len()));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_iddfs_does_not_reexpand_fruitless_states() -> Result<(), String> {
+        let board = boards()?.remove(2);
+        let mut on_progress = |_| {};
+        let mut search = DfsSearch::new(
+            SearchLimits::default(),
+            Default::default(),
+            &mut on_progress,
+        );
+        for limit in 1..4 {
+            assert!(search.run(&mut board.clone(), limit).is_err());
+        }
+        let expanded = search.stats.nodes_expanded;
+        // Nothing new to expand when searching again with the same limit
+        assert!(search.run(&mut board.clone(), 3).is_err());
+        assert_eq!(search.stats.nodes_expanded, expanded);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_iddfs_no_solution() -> Result<(), String> {
+        // Block 2 leaves only one free cell in each of its rows,
+        // so the horizontal block 3 can never get below it
+        let board = "3 3\n\
+        3 3 0\n\
+        1 2 2\n\
+        0 2 2\n\
+        "
+        .parse::<Board>()?;
+        let start = Instant::now();
+
+        assert_eq!(iddfs(board), None);
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfbnb_is_optimal() -> Result<(), String> {
+        for board in boards()? {
+            let expected = idastar(board.clone()).ok_or("No solution found")?;
+            let optimal = bfs(board.clone()).ok_or("No solution found")?;
+            let moves = dfbnb(board.clone(), None).ok_or("No solution found")?;
+
+            assert_eq!(moves.len(), expected.len());
+            assert_eq!(moves.len(), optimal.len());
+            assert!(reach_goal(board, &moves)?);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfbnb_with_initial_bound() -> Result<(), String> {
+        let board = boards()?.remove(2);
+        let optimal = bfs(board.clone()).ok_or("No solution found")?.len();
+
+        let moves = dfbnb(board.clone(), Some(optimal)).ok_or("No solution found")?;
+        assert_eq!(moves.len(), optimal);
+        assert!(reach_goal(board.clone(), &moves)?);
+        // No solution within a bound below the optimal length
+        assert_eq!(dfbnb(board, Some(optimal - 1)), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dfbnb_no_solution() -> Result<(), String> {
+        let board = unsolvable_board()?;
+        assert_eq!(dfbnb(board, None), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_all_optimal() -> Result<(), String> {
+        for board in boards()? {
+            let optimal = bfs(board.clone()).ok_or("No solution found")?;
+            let solutions = all_optimal(board.clone(), 1000);
+
+            assert!(!solutions.is_empty());
+            for moves in &solutions {
+                assert_eq!(moves.len(), optimal.len());
+                assert!(reach_goal(board.clone(), moves)?);
+            }
+            let distinct = solutions.iter().collect::<HashSet<_>>();
+            assert_eq!(distinct.len(), solutions.len());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_all_optimal_includes_permutations() -> Result<(), String> {
+        // Block 3 can move up either before or after block 2 moves right
+        let board = "2 3\n\
+        1 0 0\n\
+        2 0 3\n\
+        "
+        .parse::<Board>()?;
+        let solutions = all_optimal(board.clone(), 100);
+
+        assert_eq!(solutions.len(), 3);
+        assert_eq!(all_optimal(board, 2).len(), 2);
+        assert!(all_optimal(unsolvable_board()?, 100).is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_optimal_min_blocks() -> Result<(), String> {
+        // Block 1 either goes around block 2 alone, or block 2 steps aside and back,
+        // both take 4 moves
+        let board = "2 3\n\
+        0 2 1\n\
+        0 0 0\n\
+        "
+        .parse::<Board>()?;
+        let block_count = |moves: &[Move]| {
+            moves
+                .iter()
+                .map(|&(id, _)| id)
+                .collect::<HashSet<_>>()
+                .len()
+        };
+        let counts = all_optimal(board.clone(), 100)
+            .iter()
+            .map(|moves| block_count(moves))
+            .collect::<HashSet<_>>();
+        assert_eq!(counts, HashSet::from([1, 2]));
+
+        let moves = optimal_min_blocks(board.clone()).ok_or("No solution found")?;
+        assert_eq!(moves.len(), 4);
+        assert_eq!(block_count(&moves), 1);
+        assert!(reach_goal(board, &moves)?);
+
+        for board in boards()? {
+            let optimal = bfs(board.clone()).ok_or("No solution found")?;
+            let moves = optimal_min_blocks(board.clone()).ok_or("No solution found")?;
+            assert_eq!(moves.len(), optimal.len());
+            assert!(reach_goal(board, &moves)?);
+        }
+        assert_eq!(optimal_min_blocks(unsolvable_board()?), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_astar_cost() -> Result<(), String> {
+        // Block 2 can step down and back to let block 1 pass in 5 moves, but the
+        // cheapest way is block 1 going around it alone in 7 moves
+        let board = "3 4\n\
+        0 2 2 1\n\
+        0 2 2 0\n\
+        0 0 0 0\n\
+        "
+        .parse::<Board>()?;
+        let shortest = astar(board.clone()).ok_or("No solution found")?;
+        let (moves, cost) =
+            astar_cost(board.clone(), CostModel::CellCount).ok_or("No solution found")?;
+
+        assert_eq!(shortest.len(), 5);
+        assert_eq!(CostModel::CellCount.total_cost(&board, &shortest)?, 11);
+        assert_eq!(moves.len(), 7);
+        assert_eq!(cost, 7);
+        assert_eq!(CostModel::CellCount.total_cost(&board, &moves)?, cost);
+        assert!(reach_goal(board.clone(), &moves)?);
+
+        let (moves, cost) =
+            astar_cost(board.clone(), CostModel::Uniform).ok_or("No solution found")?;
+        assert_eq!((moves.len(), cost), (5, 5));
+        // Moving block 2 is free, so it does not matter how often it moves
+        let (_, cost) = astar_cost(
+            board,
+            CostModel::Custom(|block| (block.size().x == 1) as u32),
+        )
+        .ok_or("No solution found")?;
+        assert_eq!(cost, 3);
+        assert_eq!(astar_cost(unsolvable_board()?, CostModel::CellCount), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_non_contiguous_ids() -> Result<(), String> {
+        let board = "2 3\n9 0 2\n5 5 0\n".parse::<Board>()?;
+        let optimal = idastar(board.clone()).ok_or("Board should be solvable")?;
+        for moves in [
+            iddfs(board.clone()),
+            astar(board.clone()),
+            bfs(board.clone()),
+            greedy(board.clone()),
+        ] {
+            let moves = moves.ok_or("Board should be solvable")?;
+            // Moves refer to the ids of the input
+            assert!(moves.iter().all(|(id, _)| [2, 5, 9].contains(id)));
+            let report = board.verify_solution(&moves).map_err(|e| e.to_string())?;
+            assert!(report.reached_goal);
+            assert!(moves.len() >= optimal.len());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solution_of_normalized_board() -> Result<(), String> {
+        let board = "3 3\n\
+        0 3 3\n\
+        2 1 0\n\
+        4 0 0\n\
+        "
+        .parse::<Board>()?;
+        let (normalized, mapping) = board.normalize_ids();
+        let moves = idastar(normalized).ok_or("Normalized board should be solvable")?;
+        let inverse = mapping
+            .iter()
+            .map(|(&old, &new)| (new, old))
+            .collect::<HashMap<_, _>>();
+        let original_moves = moves
+            .iter()
+            .map(|&(id, dir)| (inverse[&id], dir))
+            .collect::<Vec<_>>();
+        let report = board
+            .verify_solution(&original_moves)
+            .map_err(|e| e.to_string())?;
+        assert!(report.reached_goal);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_estimate_difficulty() -> Result<(), String> {
+        let thresholds = [5.0, 12.0];
+        let solved = "2 2\n1 0\n0 0\n".parse::<Board>()?;
+        let report = estimate_difficulty(&solved);
+        assert_eq!(report.length, SolutionLength::Exact(0));
+        assert_eq!(report.label(thresholds), "easy");
+
+        // One of the hardest 8-puzzles, which needs 31 moves
+        let shuffled = "3 3\n8 6 7\n2 5 4\n3 0 1\n".parse::<Board>()?;
+        let report = estimate_difficulty(&shuffled);
+        assert_eq!(report.length, SolutionLength::Exact(31));
+        assert_eq!(report.label(thresholds), "hard");
+        let report = estimate_difficulty_with_cap(&shuffled, 1_000);
+        assert!(
+            matches!(report.length, SolutionLength::AtLeast(moves) if (21..31).contains(&moves)),
+            "{:?}",
+            report
+        );
+        assert!(report.length.to_string().starts_with("at least "));
+        assert_eq!(report.label(thresholds), "hard");
+
+        // Blocks can never pass each other in a single row
+        let unsolvable = "1 3\n2 1 0\n".parse::<Board>()?;
+        let report = estimate_difficulty(&unsolvable);
+        assert_eq!(report.length, SolutionLength::NoSolution);
+        assert_eq!(report.label(thresholds), "unsolvable");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_manual_step() -> Result<(), String> {
+        let mut board = "2 3\n0 0 0\n0 2 1\n".parse::<Board>()?;
+        let mut moves = vec![];
+        assert_eq!(
+            manual_step(&mut board, &mut moves, "2U\n"),
+            ManualStep::Moved((2, Dir::Up))
+        );
+        // Illegal moves and unknown blocks are reported but not recorded
+        assert!(matches!(
+            manual_step(&mut board, &mut moves, "2U"),
+            ManualStep::Rejected(_)
+        ));
+        assert!(matches!(
+            manual_step(&mut board, &mut moves, "9L"),
+            ManualStep::Rejected(_)
+        ));
+        assert!(matches!(
+            manual_step(&mut board, &mut moves, "2X"),
+            ManualStep::Invalid(_)
+        ));
+        assert_eq!(
+            manual_step(&mut board, &mut moves, "1L"),
+            ManualStep::Moved((1, Dir::Left))
+        );
+        assert_eq!(moves, [(2, Dir::Up), (1, Dir::Left)]);
+        assert_eq!(board.move_count(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_move() {
+        assert_eq!(parse_move("3U"), Ok((3, Dir::Up)));
+        assert_eq!(parse_move("200L"), Ok((200, Dir::Left)));
+        assert!(parse_move("200X").is_err());
+        assert!(parse_move("U").is_err());
+    }
+
+    #[test]
+    fn test_route_around_wall() -> Result<(), String> {
+        // Block 1 has to go through the second row
+        let board = "2 3\n\
+        0 -1 1\n\
+        0 0 0\n\
+        "
+        .parse::<Board>()?;
+        let moves = bfs(board.clone()).ok_or("No solution found")?;
+
+        assert_eq!(moves.len(), 4);
+        assert!(reach_goal(board.clone(), &moves)?);
+        assert_eq!(idastar(board.clone()).map(|moves| moves.len()), Some(4));
+        assert_eq!(astar(board).map(|moves| moves.len()), Some(4));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_slide_semantics() -> Result<(), String> {
+        // Block 1 slides along the second row in one move
+        let board = "2 3\n\
+        0 -1 1\n\
+        0 0 0\n\
+        "
+        .parse::<Board>()?;
+        let slide = SearchConstraints {
+            semantics: MoveSemantics::SlideToStop,
+            ..Default::default()
+        };
+        let single = SearchConstraints::default();
+
+        assert_eq!(
+            iddfs_with_constraints(board.clone(), &single).map(|moves| moves.len()),
+            Some(4)
+        );
+        assert_eq!(
+            idastar_with_constraints(board.clone(), &single).map(|moves| moves.len()),
+            Some(4)
+        );
+        for moves in [
+            iddfs_with_constraints(board.clone(), &slide),
+            idastar_with_constraints(board.clone(), &slide),
+        ] {
+            let moves = moves.ok_or("No solution found")?;
+            assert_eq!(moves.len(), 3);
+            let mut board = board.clone();
+            for &(id, dir) in &moves {
+                board.slide_block(id, dir)?;
+            }
+            assert!(board.is_goal());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_slide_longer_than_255_cells() -> Result<(), String> {
+        // One slide crosses 299 cells, which has to be undone step by step
+        let mut text = "1 300\n".to_string();
+        text.push_str(&"0 ".repeat(299));
+        text.push_str("1\n");
+        let board = text.parse::<Board>()?;
+        let slide = SearchConstraints {
+            semantics: MoveSemantics::SlideToStop,
+            ..Default::default()
+        };
+
+        for moves in [
+            iddfs_with_constraints(board.clone(), &slide),
+            idastar_with_constraints(board.clone(), &slide),
+        ] {
+            let moves = moves.ok_or("No solution found")?;
+            assert_eq!(moves, [(1, Dir::Left)]);
+            let mut board = board.clone();
+            assert_eq!(board.slide_block(1, Dir::Left)?, 299);
+            assert!(board.is_goal());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_wall_makes_board_unsolvable() -> Result<(), String> {
+        let open = "1 3\n0 0 1\n".parse::<Board>()?;
+        let walled = "1 3\n0 -1 1\n".parse::<Board>()?;
+
+        assert_eq!(bfs(open).map(|moves| moves.len()), Some(2));
+        assert_eq!(bfs(walled.clone()), None);
+        assert_eq!(idastar(walled), None);
+
+        Ok(())
+    }
+
+    /// Apply hints until the goal is reached, return the number of hints applied
+    fn follow_hints(
+        mut board: Board,
+        hint: impl Fn(&Board) -> Option<Move>,
+        max_hints: usize,
+    ) -> Result<usize, String> {
+        let mut count = 0;
+        while !board.is_goal() {
+            if count == max_hints {
+                return Err("Too many hints".to_string());
+            }
+            let (id, dir) = hint(&board).ok_or("No hint found")?;
+            board.move_block(id, dir)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    #[test]
+    fn test_hint_reach_goal() -> Result<(), String> {
+        for board in boards()? {
+            let optimal = bfs(board.clone()).ok_or("No solution found")?;
+            let count = follow_hints(board.clone(), hint, optimal.len())?;
+            assert_eq!(count, optimal.len());
+            assert_eq!(hint(&board).is_some(), !optimal.is_empty());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hint_with_depth() -> Result<(), String> {
+        let board = boards()?.remove(2);
+        let optimal = bfs(board.clone()).ok_or("No solution found")?;
+
+        // Bound is hit, so only a greedy move is given
+        assert_eq!(
+            idastar_bounded(board.clone(), 1),
+            SearchOutcome::LimitReached
+        );
+        assert!(hint_with_depth(&board, 1).is_some());
+        assert_eq!(hint_with_depth(&unsolvable_board()?, HINT_MAX_DEPTH), None);
+        // Following hints still reaches the goal
+        follow_hints(
+            board,
+            |board| hint_with_depth(board, 1),
+            100 * optimal.len(),
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_with_frozen_block() -> Result<(), String> {
+        // Block 3 has to move up to reach the goal
+        let board = "2 3\n\
+        1 0 0\n\
+        2 0 3\n\
+        "
+        .parse::<Board>()?;
+        let optimal = bfs(board.clone()).ok_or("No solution found")?;
+        for search in [iddfs_with_constraints, idastar_with_constraints] {
+            let unconstrained = SearchConstraints::default();
+            let moves = search(board.clone(), &unconstrained).ok_or("No solution found")?;
+            assert_eq!(moves.len(), optimal.len());
+
+            // Freezing an irrelevant block changes nothing
+            let constraints = SearchConstraints {
+                frozen: HashSet::from([1]),
+                ..Default::default()
+            };
+            let moves = search(board.clone(), &constraints).ok_or("No solution found")?;
+            assert_eq!(moves.len(), optimal.len());
+            assert!(moves.iter().all(|&m| constraints.allows(m)));
+
+            let constraints = SearchConstraints {
+                frozen: HashSet::from([3]),
+                ..Default::default()
+            };
+            assert_eq!(search(board.clone(), &constraints), None);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_with_frozen_ring() -> Result<(), String> {
+        // Blocks 6 and 7 turn the rest of the board into a ring,
+        // around which blocks 1 and 2 cannot pass each other
+        let board = "3 4\n\
+        2 1 3 4\n\
+        5 6 7 8\n\
+        9 0 0 0\n\
+        "
+        .parse::<Board>()?;
+        assert!(idastar(board.clone()).is_some());
+        let constraints = SearchConstraints {
+            frozen: HashSet::from([6, 7]),
+            ..Default::default()
+        };
+        assert_eq!(iddfs_with_constraints(board.clone(), &constraints), None);
+        assert_eq!(idastar_with_constraints(board, &constraints), None);
+
+        // States are not entered again under constraints, which keeps solutions optimal
+        let constraints = SearchConstraints {
+            frozen: HashSet::from([BlockId::MAX]),
+            ..Default::default()
+        };
+        for board in boards()? {
+            let expected = idastar(board.clone()).ok_or("No solution found")?;
+            let moves =
+                idastar_with_constraints(board.clone(), &constraints).ok_or("No solution found")?;
+            assert_eq!(moves.len(), expected.len());
+            assert!(reach_goal(board, &moves)?);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_idastar_traced() -> Result<(), String> {
+        // The heuristic underestimates, so the first iteration always prunes some nodes
+        let board = "2 3\n\
+        0 -1 1\n\
+        0 0 0\n\
+        "
+        .parse::<Board>()?;
+        let expected = idastar(board.clone()).ok_or("No solution found")?;
+        let mut collector = TraceCollector::default();
+        let moves = idastar_traced(board.clone(), &mut collector).ok_or("No solution found")?;
+
+        assert_eq!(moves.len(), expected.len());
+        let entries = collector.entries();
+        assert_eq!(entries[0].depth, 0);
+        assert_eq!(entries[0].applied, None);
+        assert!(entries.iter().any(|e| e.event == TraceEvent::Pruned));
+        // The last entry is the goal reached by the solution
+        let last = entries.last().ok_or("Empty trace")?;
+        assert_eq!(last.depth as usize, moves.len());
+        assert_eq!(last.applied, moves.last().copied());
+        assert_eq!(last.heuristic, 0);
+
+        let mut collector = TraceCollector::new(3);
+        idastar_traced(board, &mut collector).ok_or("No solution found")?;
+        assert_eq!(collector.entries().len(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_auto_backend() -> Result<(), String> {
+        let [small, _, blocked] = <[Board; 3]>::try_from(boards()?)
+            .map_err(|_| "Unexpected number of boards".to_string())?;
+        assert_eq!(AutoBackend::select(&small), AutoBackend::Bfs);
+        assert_eq!(
+            AutoBackend::select(&blocked),
+            AutoBackend::IdaStarLinearConflict
+        );
+
+        // Plenty of holes in a medium grid
+        let medium = "4 5\n\
+        1 1 2 0 0\n\
+        3 0 4 0 5\n\
+        0 0 6 6 0\n\
+        0 7 0 0 0\n\
+        "
+        .parse::<Board>()?;
+        assert_eq!(AutoBackend::select(&medium), AutoBackend::IdaStar);
+
+        let large = Board::generate(Vec2::new(8, 8), 24, 0);
+        assert_eq!(
+            AutoBackend::select(&large),
+            AutoBackend::IdaStarLinearConflict
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_auto_is_optimal() -> Result<(), String> {
+        for board in boards()? {
+            let expected = bfs(board.clone()).ok_or("No solution found")?;
+            let moves = auto(board.clone()).ok_or("No solution found")?;
+            assert_eq!(moves.len(), expected.len());
+            assert!(reach_goal(board, &moves)?);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_auto_unsolvable() -> Result<(), String> {
+        let tiny = "3 3\n\
+        2 1 3\n\
+        4 5 6\n\
+        7 8 0\n\
+        "
+        .parse::<Board>()?;
+        assert_eq!(AutoBackend::select(&tiny), AutoBackend::Bfs);
+        assert_eq!(auto(tiny), None);
+
+        // IDA* would search forever without the parity check
+        let large = "4 4\n\
+        2 1 3 4\n\
+        5 6 7 8\n\
+        9 10 11 12\n\
+        13 14 15 0\n\
+        "
+        .parse::<Board>()?;
+        assert_ne!(AutoBackend::select(&large), AutoBackend::Bfs);
+        let (outcome, stats) = auto_search(large, Default::default(), &mut |_| {});
+        assert_eq!(outcome, SearchOutcome::NoSolution);
+        assert_eq!(stats.nodes_expanded, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_beam_reach_goal() -> Result<(), String> {
+        for board in boards()? {
+            let moves = beam(board.clone(), 512, 100).ok_or("No solution found")?;
+            assert!(reach_goal(board, &moves)?);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_beam_is_incomplete() -> Result<(), String> {
+        let board = boards()?.remove(2);
+        let optimal = bfs(board.clone()).ok_or("No solution found")?;
+
+        // A solvable board, but the solution is deeper than the beam can go
+        assert_eq!(beam(board, 512, optimal.len() - 1), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_beam_no_solution() -> Result<(), String> {
+        let board = unsolvable_board()?;
+        assert_eq!(beam(board, 512, 100), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bfs_no_solution() -> Result<(), String> {
+        let board = unsolvable_board()?;
+        assert_eq!(bfs(board), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_weighted_astar_reach_goal() -> Result<(), String> {
+        for board in boards()? {
+            let moves = weighted_astar(board.clone(), 2.5).ok_or("No solution found")?;
+            assert!(reach_goal(board, &moves)?);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_weighted_astar_unit_weight_is_optimal() -> Result<(), String> {
+        for board in boards()? {
+            let expected = astar(board.clone()).map(|moves| moves.len());
+            assert_eq!(
+                weighted_astar(board, 1.0).map(|moves| moves.len()),
+                expected
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_weighted_astar_expands_fewer_nodes() -> Result<(), String> {
+        let board = "5 5\n\
+        2 4 4 7 7\n\
+        6 5 3 0 0\n\
+        9 9 3 12 10\n\
+        0 8 8 12 10\n\
+        0 8 8 1 11\n\
+        "
+        .parse::<Board>()?;
+        let options = SearchOptions::default();
+        let (moves, stats) = _weighted_astar(board.clone(), 1.0, &options);
+        let (weighted_moves, weighted_stats) = _weighted_astar(board, 3.0, &options);
+        assert!(moves.is_some());
+        assert!(weighted_moves.is_some());
+        assert!(weighted_stats.nodes_expanded < stats.nodes_expanded);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_astar_with_mirror_pruning() -> Result<(), String> {
+        // Both the goal and the initial state are mirror symmetric
+        let board = "4 4\n\
+        3 3 4 4\n\
+        1 1 2 2\n\
+        5 5 6 6\n\
+        0 0 0 0\n\
+        "
+        .parse::<Board>()?;
+        let options = SearchOptions {
+            prune_mirrors: true,
+            ..Default::default()
+        };
+        let (moves, stats) = astar_with_stats(board.clone(), &SearchOptions::default());
+        let (pruned_moves, pruned_stats) = astar_with_stats(board.clone(), &options);
+        let moves = moves.ok_or("No solution found")?;
+        let pruned_moves = pruned_moves.ok_or("No solution found")?;
+
+        assert_eq!(pruned_moves.len(), moves.len());
+        assert!(reach_goal(board, &pruned_moves)?);
+        assert!(pruned_stats.nodes_expanded < stats.nodes_expanded);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_greedy_reach_goal() -> Result<(), String> {
+        for board in boards()? {
+            let moves = greedy(board.clone()).ok_or("No solution found")?;
+            let optimal = bfs(board.clone()).ok_or("No solution found")?;
+            assert!(moves.len() >= optimal.len());
+            assert!(reach_goal(board, &moves)?);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_greedy_no_solution() -> Result<(), String> {
+        let board = unsolvable_board()?;
+        assert_eq!(greedy(board), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_astar_no_solution() -> Result<(), String> {
+        let board = unsolvable_board()?;
+        assert_eq!(astar(board), None);
+
+        Ok(())
+    }
 }
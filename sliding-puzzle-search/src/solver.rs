@@ -0,0 +1,414 @@
+use crate::{
+    heuristic::Heuristic,
+    search::{
+        self, CostModel, ProgressEvent, SearchConstraints, SearchLimits, SearchOptions,
+        SearchOutcome, SearchStats,
+    },
+    trace::TraceCollector,
+};
+use sliding_puzzle_core::{Board, Move};
+use std::sync::{atomic::AtomicBool, Arc};
+
+/// Optional features of [`Solver::solve_with`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    /// Report search statistics
+    Stats,
+    /// Stop once [`SolveContext::limits`] are reached
+    Limits,
+    /// Stop once [`SolveContext::cancel`] is set
+    Cancel,
+    /// Call [`SolveContext::on_progress`] periodically
+    Progress,
+    /// Respect [`SolveContext::constraints`]
+    Constraints,
+    /// Record visited nodes to [`SolveContext::trace`]
+    Trace,
+}
+
+/// Extra knobs of a solve, solvers ignore features they do not support
+pub struct SolveContext<'a> {
+    pub limits: SearchLimits,
+    pub constraints: SearchConstraints,
+    pub cancel: Arc<AtomicBool>,
+    pub on_progress: &'a mut dyn FnMut(ProgressEvent),
+    pub trace: Option<&'a mut TraceCollector>,
+}
+
+/// A search algorithm which can be registered to the CLI
+pub trait Solver {
+    /// Name used to select this solver, e.g. `--algorithm <name>`
+    fn name(&self) -> &str;
+
+    fn solve(&self, board: Board) -> Option<Vec<Move>>;
+
+    /// Whether the solution is guaranteed to be optimal
+    fn is_optimal(&self) -> bool {
+        true
+    }
+
+    /// Whether [`Solver::solve_with`] supports given feature
+    fn supports(&self, _feature: Feature) -> bool {
+        false
+    }
+
+    /// Solve with extra knobs, also return statistics if supported
+    fn solve_with(
+        &self,
+        board: Board,
+        _context: &mut SolveContext,
+    ) -> (SearchOutcome, Option<SearchStats>) {
+        (self.solve(board).into(), None)
+    }
+}
+
+/// See [`search::iddfs`]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Iddfs;
+
+impl Solver for Iddfs {
+    fn name(&self) -> &str {
+        "iddfs"
+    }
+
+    fn solve(&self, board: Board) -> Option<Vec<Move>> {
+        search::iddfs(board)
+    }
+
+    fn supports(&self, feature: Feature) -> bool {
+        feature != Feature::Trace
+    }
+
+    fn solve_with(
+        &self,
+        board: Board,
+        context: &mut SolveContext,
+    ) -> (SearchOutcome, Option<SearchStats>) {
+        let (outcome, stats) = search::iddfs_search(
+            board,
+            context.limits,
+            &SearchOptions::default(),
+            &context.constraints,
+            context.cancel.clone(),
+            context.on_progress,
+        );
+        (outcome, Some(stats))
+    }
+}
+
+/// See [`search::iddfs_parallel`]
+#[derive(Debug, Clone, Copy)]
+pub struct IddfsParallel {
+    pub threads: usize,
+}
+
+impl Solver for IddfsParallel {
+    fn name(&self) -> &str {
+        "iddfs-parallel"
+    }
+
+    fn solve(&self, board: Board) -> Option<Vec<Move>> {
+        search::iddfs_parallel(board, self.threads)
+    }
+}
+
+/// See [`search::idastar_with`]
+pub struct IdaStar {
+    pub heuristic: Box<dyn Heuristic>,
+}
+
+impl Solver for IdaStar {
+    fn name(&self) -> &str {
+        "ida-star"
+    }
+
+    fn solve(&self, board: Board) -> Option<Vec<Move>> {
+        search::idastar_with(board, &*self.heuristic)
+    }
+
+    fn supports(&self, feature: Feature) -> bool {
+        feature != Feature::Limits
+    }
+
+    fn solve_with(
+        &self,
+        board: Board,
+        context: &mut SolveContext,
+    ) -> (SearchOutcome, Option<SearchStats>) {
+        let (outcome, stats) = search::idastar_search(
+            board,
+            &*self.heuristic,
+            &SearchOptions::default(),
+            &context.constraints,
+            context.cancel.clone(),
+            context.on_progress,
+            context.trace.as_deref_mut(),
+        );
+        (outcome, Some(stats))
+    }
+}
+
+/// See [`search::auto_search`]. Features are supported by both BFS and IDA* backends
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Auto;
+
+impl Solver for Auto {
+    fn name(&self) -> &str {
+        "auto"
+    }
+
+    fn solve(&self, board: Board) -> Option<Vec<Move>> {
+        search::auto(board)
+    }
+
+    fn supports(&self, feature: Feature) -> bool {
+        matches!(
+            feature,
+            Feature::Stats | Feature::Cancel | Feature::Progress
+        )
+    }
+
+    fn solve_with(
+        &self,
+        board: Board,
+        context: &mut SolveContext,
+    ) -> (SearchOutcome, Option<SearchStats>) {
+        let (outcome, stats) =
+            search::auto_search(board, context.cancel.clone(), context.on_progress);
+        (outcome, Some(stats))
+    }
+}
+
+/// See [`search::dfbnb`]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Dfbnb;
+
+impl Solver for Dfbnb {
+    fn name(&self) -> &str {
+        "dfbnb"
+    }
+
+    fn solve(&self, board: Board) -> Option<Vec<Move>> {
+        search::dfbnb(board, None)
+    }
+}
+
+/// See [`search::idastar_parallel`]
+#[derive(Debug, Clone, Copy)]
+pub struct IdaStarParallel {
+    pub threads: usize,
+}
+
+impl Solver for IdaStarParallel {
+    fn name(&self) -> &str {
+        "ida-star-parallel"
+    }
+
+    fn solve(&self, board: Board) -> Option<Vec<Move>> {
+        search::idastar_parallel(board, self.threads)
+    }
+}
+
+/// See [`search::astar`], or [`search::astar_cost`] if moves are not uniformly priced
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AStar {
+    pub cost: CostModel,
+}
+
+impl Solver for AStar {
+    fn name(&self) -> &str {
+        "astar"
+    }
+
+    fn solve(&self, board: Board) -> Option<Vec<Move>> {
+        match self.cost {
+            CostModel::Uniform => search::astar(board),
+            cost => search::astar_cost(board, cost).map(|(moves, _)| moves),
+        }
+    }
+}
+
+/// See [`search::weighted_astar`]
+#[derive(Debug, Clone, Copy)]
+pub struct WeightedAStar {
+    pub weight: f32,
+}
+
+impl Solver for WeightedAStar {
+    fn name(&self) -> &str {
+        "weighted-astar"
+    }
+
+    fn solve(&self, board: Board) -> Option<Vec<Move>> {
+        search::weighted_astar(board, self.weight)
+    }
+
+    fn is_optimal(&self) -> bool {
+        false
+    }
+}
+
+/// See [`search::bfs`]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Bfs;
+
+impl Solver for Bfs {
+    fn name(&self) -> &str {
+        "bfs"
+    }
+
+    fn solve(&self, board: Board) -> Option<Vec<Move>> {
+        search::bfs(board)
+    }
+}
+
+/// See [`search::greedy`]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Greedy;
+
+impl Solver for Greedy {
+    fn name(&self) -> &str {
+        "greedy"
+    }
+
+    fn solve(&self, board: Board) -> Option<Vec<Move>> {
+        search::greedy(board)
+    }
+
+    fn is_optimal(&self) -> bool {
+        false
+    }
+}
+
+/// See [`search::beam`]
+#[derive(Debug, Clone, Copy)]
+pub struct Beam {
+    pub width: usize,
+    pub max_depth: usize,
+}
+
+impl Solver for Beam {
+    fn name(&self) -> &str {
+        "beam"
+    }
+
+    fn solve(&self, board: Board) -> Option<Vec<Move>> {
+        search::beam(board, self.width, self.max_depth)
+    }
+
+    fn is_optimal(&self) -> bool {
+        false
+    }
+}
+
+/// See [`search::manual`]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Manual;
+
+impl Solver for Manual {
+    fn name(&self) -> &str {
+        "manual"
+    }
+
+    fn solve(&self, board: Board) -> Option<Vec<Move>> {
+        search::manual(board)
+    }
+
+    fn is_optimal(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{heuristic::ManhattanHeuristic, search::AutoBackend};
+
+    #[test]
+    fn test_solve_with_falls_back_to_solve() -> Result<(), String> {
+        let board = "3 3\n\
+        1 1 2\n\
+        0 3 0\n\
+        0 4 4\n\
+        "
+        .parse::<Board>()?;
+        let solvers: Vec<Box<dyn Solver>> = vec![
+            Box::new(Iddfs),
+            Box::new(IdaStar {
+                heuristic: Box::new(ManhattanHeuristic),
+            }),
+            Box::new(Bfs),
+            Box::new(Auto),
+        ];
+        assert_eq!(AutoBackend::select(&board), AutoBackend::Bfs);
+        let expected = search::bfs(board.clone()).ok_or("No solution found")?;
+
+        for solver in solvers {
+            let mut context = SolveContext {
+                limits: SearchLimits::default(),
+                constraints: SearchConstraints::default(),
+                cancel: Arc::default(),
+                on_progress: &mut |_| {},
+                trace: None,
+            };
+            let (outcome, stats) = solver.solve_with(board.clone(), &mut context);
+            let moves = outcome.into_moves().ok_or("No solution found")?;
+
+            assert_eq!(moves.len(), expected.len());
+            assert_eq!(stats.is_some(), solver.supports(Feature::Stats));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_auto_with_bfs_backend_is_cancellable() -> Result<(), String> {
+        let board = "3 3\n\
+        1 1 2\n\
+        0 3 0\n\
+        0 4 4\n\
+        "
+        .parse::<Board>()?;
+        let mut events = 0;
+        let mut on_progress = |_| events += 1;
+        let mut context = SolveContext {
+            limits: SearchLimits::default(),
+            constraints: SearchConstraints::default(),
+            cancel: Arc::new(AtomicBool::new(true)),
+            on_progress: &mut on_progress,
+            trace: None,
+        };
+
+        assert!(Auto.supports(Feature::Cancel));
+        let (outcome, stats) = Auto.solve_with(board, &mut context);
+        assert_eq!(outcome, SearchOutcome::Cancelled);
+        assert_eq!(stats.map(|stats| stats.nodes_expanded), Some(0));
+        assert!(events > 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_auto_unsolvable_3x3() -> Result<(), String> {
+        // Tiles 1 and 2 swapped, more blocks than BFS is picked for by their count
+        let board = "3 3\n\
+        2 1 3\n\
+        4 5 6\n\
+        7 8 0\n\
+        "
+        .parse::<Board>()?;
+        let mut context = SolveContext {
+            limits: SearchLimits::default(),
+            constraints: SearchConstraints::default(),
+            cancel: Arc::default(),
+            on_progress: &mut |_| {},
+            trace: None,
+        };
+
+        let (outcome, _) = Auto.solve_with(board.clone(), &mut context);
+        assert_eq!(outcome, SearchOutcome::NoSolution);
+        assert_eq!(Auto.solve(board), None);
+
+        Ok(())
+    }
+}
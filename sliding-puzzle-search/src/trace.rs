@@ -0,0 +1,143 @@
+use sliding_puzzle_core::{format_move, Move};
+use std::io::Write;
+
+/// Default number of entries kept by [`TraceCollector`]
+pub const DEFAULT_TRACE_CAPACITY: usize = 10_000;
+
+/// What the search did with a visited node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceEvent {
+    /// Its children were searched, or it is the goal
+    Expanded,
+    /// Cut off by the f-limit, the visited set or dead-end pruning
+    Pruned,
+}
+
+/// A node visited by a traced search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceEntry {
+    /// Number of moves from the root
+    pub depth: u32,
+    /// Move applied to reach this node, `None` for the root
+    pub applied: Option<Move>,
+    pub heuristic: i32,
+    pub event: TraceEvent,
+}
+
+/// Records nodes visited by a search in DFS order, at most `capacity` of them
+#[derive(Debug, Clone)]
+pub struct TraceCollector {
+    entries: Vec<TraceEntry>,
+    capacity: usize,
+}
+
+impl Default for TraceCollector {
+    fn default() -> Self {
+        Self::new(DEFAULT_TRACE_CAPACITY)
+    }
+}
+
+impl TraceCollector {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: vec![],
+            capacity,
+        }
+    }
+
+    pub fn entries(&self) -> &[TraceEntry] {
+        &self.entries
+    }
+
+    /// Whether later entries are dropped
+    pub fn is_full(&self) -> bool {
+        self.entries.len() >= self.capacity
+    }
+
+    pub fn record(&mut self, entry: TraceEntry) {
+        if !self.is_full() {
+            self.entries.push(entry);
+        }
+    }
+
+    /// Write explored nodes as a Graphviz DOT tree, each iteration starts a new root.
+    /// Pruned nodes are dashed
+    pub fn write_dot(&self, w: &mut dyn Write) -> std::io::Result<()> {
+        writeln!(w, "digraph trace {{")?;
+        writeln!(w, "    node [shape=box];")?;
+        // Index of the latest node at each depth on current path
+        let mut path: Vec<usize> = vec![];
+        for (i, entry) in self.entries.iter().enumerate() {
+            let label = match entry.applied {
+                Some(applied) => format_move(applied),
+                None => "root".to_string(),
+            };
+            let style = match entry.event {
+                TraceEvent::Expanded => "",
+                TraceEvent::Pruned => ", style=dashed",
+            };
+            writeln!(
+                w,
+                "    n{} [label=\"{}\\nh={}\"{}];",
+                i, label, entry.heuristic, style
+            )?;
+            path.truncate(entry.depth as usize);
+            if let Some(parent) = path.last() {
+                writeln!(w, "    n{} -> n{};", parent, i)?;
+            }
+            path.push(i);
+        }
+        writeln!(w, "}}")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sliding_puzzle_core::Dir;
+
+    fn entry(depth: u32, applied: Option<Move>, event: TraceEvent) -> TraceEntry {
+        TraceEntry {
+            depth,
+            applied,
+            heuristic: 0,
+            event,
+        }
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut collector = TraceCollector::new(2);
+        for _ in 0..3 {
+            collector.record(entry(0, None, TraceEvent::Expanded));
+        }
+
+        assert!(collector.is_full());
+        assert_eq!(collector.entries().len(), 2);
+    }
+
+    #[test]
+    fn test_write_dot() -> Result<(), String> {
+        let mut collector = TraceCollector::default();
+        collector.record(entry(0, None, TraceEvent::Expanded));
+        collector.record(entry(1, Some((7, Dir::Left)), TraceEvent::Expanded));
+        collector.record(entry(2, Some((3, Dir::Up)), TraceEvent::Pruned));
+        collector.record(entry(1, Some((2, Dir::Down)), TraceEvent::Pruned));
+        let mut output = vec![];
+        collector
+            .write_dot(&mut output)
+            .map_err(|e| e.to_string())?;
+        let output = String::from_utf8(output).map_err(|e| e.to_string())?;
+
+        assert!(output.starts_with("digraph trace {"));
+        assert!(output.contains("n1 [label=\"7L\\nh=0\"];"));
+        assert!(output.contains("n2 [label=\"3U\\nh=0\", style=dashed];"));
+        assert!(output.contains("n0 -> n1;"));
+        assert!(output.contains("n1 -> n2;"));
+        assert!(output.contains("n0 -> n3;"));
+        assert!(output.trim_end().ends_with('}'));
+
+        Ok(())
+    }
+}
@@ -1,16 +1,61 @@
-use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
-use sliding_puzzle_core::{Board, Move, Vec2};
-use sliding_puzzle_search::search;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use sliding_puzzle_core::{BlockId, Board, BoardState, Dir, HoleSet, Move, Vec2};
+use sliding_puzzle_search::{
+    heuristic::ManhattanHeuristic,
+    search::{self, DepthLimitedResult, SearchOptions, SearchStats},
+};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    collections::{HashSet, VecDeque},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Seed of generated boards, so that every run benchmarks the same boards
+const SEED: u64 = 0x5eed;
+
+/// System allocator which counts allocations and live heap bytes,
+/// so that benches can report memory use
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Heap bytes which are still allocated for the value built by `build`
+fn retained_bytes<T>(build: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let value = build();
+    let after = ALLOCATED.load(Ordering::Relaxed);
+    drop(value);
+    after - before
+}
 
 fn generate_board_with_exact_step(
     size: Vec2,
-    block_count: i8,
+    block_count: BlockId,
     shuffle_round: usize,
     step: usize,
 ) -> Result<Board, String> {
+    let mut rng = StdRng::seed_from_u64(SEED);
     let mut remain_try = 128;
     let board = loop {
-        let mut board = Board::generate(size, block_count, shuffle_round);
+        let mut board = Board::generate_with_rng(size, block_count, shuffle_round, &mut rng)?;
         let moves = search::idastar(board.clone()).unwrap_or_default();
 
         if moves.len() < step {
@@ -22,11 +67,7 @@ fn generate_board_with_exact_step(
         }
 
         let diff = moves.len() - step;
-        if diff > 0 {
-            for (id, dir) in moves.into_iter().take(diff) {
-                board.move_block(id, dir)?;
-            }
-        }
+        board.apply_moves(&moves[..diff])?;
 
         break board;
     };
@@ -37,7 +78,7 @@ fn generate_board_with_exact_step(
 fn my_search_bench<SF>(
     group_name: String,
     function_name: String,
-    board_params: Vec<(Vec2, i8)>,
+    board_params: Vec<(Vec2, BlockId)>,
     shuffles: Vec<usize>,
     search_fn: SF,
 ) -> impl FnOnce(&mut Criterion)
@@ -81,6 +122,45 @@ fn bnech_iddfs(c: &mut Criterion) {
     )(c);
 }
 
+/// IDDFS which starts every iteration with an empty transposition table
+fn iddfs_fresh_table(board: Board) -> Option<Vec<Move>> {
+    for limit in 1.. {
+        match search::depth_limited(&board, limit) {
+            DepthLimitedResult::Found(moves) => return Some(moves),
+            DepthLimitedResult::Exhausted => return None,
+            DepthLimitedResult::Cutoff => {}
+        }
+    }
+    None
+}
+
+fn bench_iddfs_transposition(c: &mut Criterion) {
+    let board_params = vec![(Vec2::new(5, 5), 8), (Vec2::new(8, 8), 24)];
+    let shuffles = vec![4, 6];
+    for keep_table in [false, true] {
+        let group_name = "iddfs-transposition".to_string();
+        let function_name = if keep_table {
+            "IDS-kept-table"
+        } else {
+            "IDS-fresh-table"
+        }
+        .to_string();
+        my_search_bench(
+            group_name,
+            function_name,
+            board_params.clone(),
+            shuffles.clone(),
+            move |board| {
+                if keep_table {
+                    search::iddfs(board)
+                } else {
+                    iddfs_fresh_table(board)
+                }
+            },
+        )(c);
+    }
+}
+
 fn bench_idastar(c: &mut Criterion) {
     let group_name = "sliding-puzzle".to_string();
     let function_name = "IDA*".to_string();
@@ -99,5 +179,420 @@ fn bench_idastar(c: &mut Criterion) {
     )(c);
 }
 
-criterion_group!(benches, bench_idastar, bnech_iddfs);
+fn bench_idastar_ordering(c: &mut Criterion) {
+    let board_params = vec![(Vec2::new(5, 5), 8), (Vec2::new(8, 8), 24)];
+    let shuffles = vec![4, 8, 12];
+    for order_children in [false, true] {
+        let group_name = "move-ordering".to_string();
+        let function_name = if order_children {
+            "IDA*-ordered"
+        } else {
+            "IDA*-unordered"
+        }
+        .to_string();
+        let options = SearchOptions {
+            order_children,
+            ..Default::default()
+        };
+        my_search_bench(
+            group_name,
+            function_name,
+            board_params.clone(),
+            shuffles.clone(),
+            move |board| search::idastar_with_options(board, &ManhattanHeuristic, &options),
+        )(c);
+    }
+}
+
+fn bench_idastar_pruning(c: &mut Criterion) {
+    let board_params = vec![(Vec2::new(5, 5), 8), (Vec2::new(8, 8), 24)];
+    let shuffles = vec![4, 8, 12];
+    for prune_dead_ends in [false, true] {
+        let group_name = "dead-end-pruning".to_string();
+        let function_name = if prune_dead_ends {
+            "IDA*-pruned"
+        } else {
+            "IDA*-unpruned"
+        }
+        .to_string();
+        let options = SearchOptions {
+            prune_dead_ends,
+            ..Default::default()
+        };
+        my_search_bench(
+            group_name,
+            function_name,
+            board_params.clone(),
+            shuffles.clone(),
+            move |board| search::idastar_with_options(board, &ManhattanHeuristic, &options),
+        )(c);
+    }
+    // Both modes search the same boards, as each node pays for the check.
+    // Block 2 of the last board is jammed away from its goal, so every node is a dead end
+    let mut boards = vec![];
+    for shuffle in [4, 6] {
+        let label = format!("08x08@{:02}", shuffle);
+        match generate_board_with_exact_step(Vec2::new(8, 8), 24, shuffle * 3, shuffle) {
+            Ok(board) => boards.push((label, board)),
+            Err(e) => panic!("label {}: {}", label, e),
+        }
+    }
+    let jammed = "2 4\n2 1 1 3\n2 4 0 5\n"
+        .parse::<Board>()
+        .expect("Invalid board");
+    boards.push(("jammed".to_string(), jammed));
+    let mut group = c.benchmark_group("dead-end-pruning");
+    for (label, board) in &boards {
+        for prune_dead_ends in [false, true] {
+            let function_name = if prune_dead_ends {
+                "IDS-pruned"
+            } else {
+                "IDS-unpruned"
+            };
+            let options = SearchOptions {
+                prune_dead_ends,
+                ..Default::default()
+            };
+            group.bench_with_input(
+                BenchmarkId::new(function_name, label),
+                board,
+                |bencher, board| {
+                    bencher.iter(|| search::iddfs_with_options(board.clone(), &options))
+                },
+            );
+        }
+    }
+}
+
+fn bench_iddfs_parallel(c: &mut Criterion) {
+    let board_params = vec![(Vec2::new(8, 8), 24)];
+    let shuffles = vec![4, 6];
+    for threads in [1, 2, 8] {
+        let group_name = "iddfs-parallel".to_string();
+        let function_name = format!("IDS-{}-threads", threads);
+        my_search_bench(
+            group_name,
+            function_name,
+            board_params.clone(),
+            shuffles.clone(),
+            move |board| search::iddfs_parallel(board, threads),
+        )(c);
+    }
+}
+
+fn bench_neighbors(c: &mut Criterion) {
+    let mut group = c.benchmark_group("neighbors");
+    for (size, block_count) in [(Vec2::new(5, 5), 8), (Vec2::new(8, 8), 24)] {
+        let label = format!("{:02}x{:02}", size.x, size.y);
+        let board =
+            Board::generate_with_rng(size, block_count, 64, &mut StdRng::seed_from_u64(SEED))
+                .expect("Invalid generate parameters");
+        group.bench_with_input(
+            BenchmarkId::new("clone+move", &label),
+            &board,
+            |bencher, board| {
+                bencher.iter(|| {
+                    let mut count = 0;
+                    for (id, dir) in board.possible_moves() {
+                        let mut next = board.clone();
+                        if next.move_block(id, dir).is_ok() {
+                            count += 1;
+                        }
+                    }
+                    count
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("neighbors", &label),
+            &board,
+            |bencher, board| bencher.iter(|| board.neighbors().count()),
+        );
+    }
+}
+
+fn bench_move_block(c: &mut Criterion) {
+    let mut group = c.benchmark_group("move-block");
+    let size = Vec2::new(16, 16);
+    let mut board = Board::generate_with_rng(size, 96, 256, &mut StdRng::seed_from_u64(SEED))
+        .expect("Invalid generate parameters");
+    let mut moves = board.neighbors().map(|(m, _)| m).collect::<Vec<_>>();
+    moves.sort_unstable();
+    let (id, dir) = moves[0];
+    group.bench_function(BenchmarkId::new("move-and-back", "16x16"), |bencher| {
+        bencher.iter(|| {
+            board.move_block(id, dir).expect("Move should be valid");
+            board
+                .move_block(id, dir.inverse())
+                .expect("Inverse move should be valid");
+        })
+    });
+}
+
+fn bench_random_walk(c: &mut Criterion) {
+    let mut group = c.benchmark_group("move-block");
+    let size = Vec2::new(16, 16);
+    let board = Board::generate_with_rng(size, 96, 256, &mut StdRng::seed_from_u64(SEED))
+        .expect("Invalid generate parameters");
+    group.bench_with_input(
+        BenchmarkId::new("random-walk", "16x16"),
+        &board,
+        |bencher, board| {
+            bencher.iter(|| {
+                let mut board = board.clone();
+                let mut rng = StdRng::seed_from_u64(SEED);
+                for _ in 0..256 {
+                    let mut moves = board.possible_moves();
+                    moves.sort_unstable();
+                    if let Some(&(id, dir)) = moves.choose(&mut rng) {
+                        let _ = board.move_block(id, dir);
+                    }
+                }
+                board
+            })
+        },
+    );
+}
+
+/// Cells turned into holes and cells filled by each move of a random walk from the board
+fn hole_changes(board: &Board, steps: usize) -> Vec<(Vec<Vec2>, Vec<Vec2>)> {
+    let mut board = board.clone();
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let mut changes = vec![];
+    for _ in 0..steps {
+        let mut moves = board.possible_moves();
+        moves.sort_unstable();
+        let &(id, dir) = moves
+            .choose(&mut rng)
+            .expect("Board should have a possible move");
+        let before = board.holes().collect::<HashSet<_>>();
+        if board.move_block(id, dir).is_err() {
+            continue;
+        }
+        let after = board.holes().collect::<HashSet<_>>();
+        changes.push((
+            after.difference(&before).copied().collect(),
+            before.difference(&after).copied().collect(),
+        ));
+    }
+    changes
+}
+
+/// The hole updates of `move_block` alone, on the bitset and on the hash set it replaced
+fn bench_hole_set(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hole-set");
+    let size = Vec2::new(16, 16);
+    let board = Board::generate_with_rng(size, 96, 256, &mut StdRng::seed_from_u64(SEED))
+        .expect("Invalid generate parameters");
+    let changes = hole_changes(&board, 256);
+    let neighbours = |pos: Vec2| {
+        Dir::ALL
+            .into_iter()
+            .map(move |dir| pos + dir.to_vec2())
+            .filter(move |next| (0..size.x).contains(&next.x) && (0..size.y).contains(&next.y))
+    };
+
+    let mut hash_set = HashSet::new();
+    let mut hole_set = HoleSet::new(size);
+    for hole in board.holes() {
+        hash_set.insert(hole);
+        hole_set.insert(hole);
+    }
+    group.bench_function(BenchmarkId::new("HashSet", "16x16"), |bencher| {
+        bencher.iter_batched(
+            || hash_set.clone(),
+            |mut holes| {
+                let mut found = 0;
+                for (freed, filled) in &changes {
+                    for &pos in freed {
+                        holes.insert(pos);
+                    }
+                    for pos in filled {
+                        holes.remove(pos);
+                    }
+                    for pos in freed.iter().chain(filled).flat_map(|&pos| neighbours(pos)) {
+                        found += holes.contains(&pos) as usize;
+                    }
+                }
+                found
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function(BenchmarkId::new("HoleSet", "16x16"), |bencher| {
+        bencher.iter_batched(
+            || hole_set.clone(),
+            |mut holes| {
+                let mut found = 0;
+                for (freed, filled) in &changes {
+                    for &pos in freed {
+                        holes.insert(pos);
+                    }
+                    for &pos in filled {
+                        holes.remove(pos);
+                    }
+                    for pos in freed.iter().chain(filled).flat_map(|&pos| neighbours(pos)) {
+                        found += holes.contains(pos) as usize;
+                    }
+                }
+                found
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn bench_heuristic(c: &mut Criterion) {
+    let mut group = c.benchmark_group("heuristic");
+    let size = Vec2::new(16, 16);
+    let board = Board::generate_with_rng(size, 96, 256, &mut StdRng::seed_from_u64(SEED))
+        .expect("Invalid generate parameters");
+    group.bench_function(BenchmarkId::new("cached", "16x16"), |bencher| {
+        bencher.iter(|| board.heuristic())
+    });
+    group.bench_function(BenchmarkId::new("recompute", "16x16"), |bencher| {
+        bencher.iter(|| board.recompute_heuristic())
+    });
+}
+
+fn bench_clone(c: &mut Criterion) {
+    let mut group = c.benchmark_group("clone");
+    for (size, block_count) in [(Vec2::new(8, 8), 24), (Vec2::new(16, 16), 96)] {
+        let label = format!("{:02}x{:02}", size.x, size.y);
+        let board =
+            Board::generate_with_rng(size, block_count, 64, &mut StdRng::seed_from_u64(SEED))
+                .expect("Invalid generate parameters");
+        group.bench_with_input(
+            BenchmarkId::new("board", &label),
+            &board,
+            |bencher, board| bencher.iter(|| board.clone()),
+        );
+    }
+}
+
+/// Up to `count` states reachable from `board`, in BFS order
+fn reachable_states(board: &Board, count: usize) -> Vec<BoardState> {
+    let mut visited = HashSet::from([board.state().clone()]);
+    let mut states = vec![board.state().clone()];
+    let mut frontier = VecDeque::from([board.clone()]);
+    while let Some(board) = frontier.pop_front() {
+        for (_, next) in board.neighbors() {
+            if states.len() == count {
+                return states;
+            }
+            if visited.insert(next.state().clone()) {
+                states.push(next.state().clone());
+                frontier.push_back(next);
+            }
+        }
+    }
+    states
+}
+
+/// Visited sets keyed by full states and by packed states, the memory per state is printed
+fn bench_visited_set(c: &mut Criterion) {
+    let mut group = c.benchmark_group("visited-set");
+    for (size, block_count) in [(Vec2::new(5, 5), 8), (Vec2::new(8, 8), 24)] {
+        let label = format!("{:02}x{:02}", size.x, size.y);
+        let board =
+            Board::generate_with_rng(size, block_count, 64, &mut StdRng::seed_from_u64(SEED))
+                .expect("Invalid generate parameters");
+        let states = reachable_states(&board, 10_000);
+        let full = retained_bytes(|| states.iter().cloned().collect::<HashSet<_>>());
+        let packed = retained_bytes(|| states.iter().map(BoardState::pack).collect::<HashSet<_>>());
+        println!(
+            "visited-set/{}: BoardState {} bytes/state, PackedState {} bytes/state",
+            label,
+            full / states.len(),
+            packed / states.len()
+        );
+        group.throughput(Throughput::Elements(states.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::new("BoardState", &label),
+            &states,
+            |bencher, states| bencher.iter(|| states.iter().cloned().collect::<HashSet<_>>()),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("PackedState", &label),
+            &states,
+            |bencher, states| {
+                bencher.iter(|| states.iter().map(BoardState::pack).collect::<HashSet<_>>())
+            },
+        );
+    }
+}
+
+/// Search which returns its statistics
+type StatsSearch = Box<dyn Fn(Board) -> SearchStats>;
+
+/// Searches measured in expanded nodes per second, the allocations per node are printed
+fn bench_search_allocations(c: &mut Criterion) {
+    let mut group = c.benchmark_group("search-allocations");
+    let ordered = SearchOptions {
+        order_children: true,
+        ..Default::default()
+    };
+    // IDS only gets through shallow boards in time, and the heuristic is weakest with one hole
+    let searches: [(&str, (Vec2, BlockId), usize, StatsSearch); 3] = [
+        (
+            "IDS",
+            (Vec2::new(8, 8), 24),
+            6,
+            Box::new(|board| search::iddfs_with_stats(board).1),
+        ),
+        (
+            "IDA*",
+            (Vec2::new(4, 4), 15),
+            20,
+            Box::new(|board| {
+                search::idastar_with_stats(board, &ManhattanHeuristic, &Default::default()).1
+            }),
+        ),
+        (
+            "IDA*-ordered",
+            (Vec2::new(4, 4), 15),
+            20,
+            Box::new(move |board| {
+                search::idastar_with_stats(board, &ManhattanHeuristic, &ordered).1
+            }),
+        ),
+    ];
+    for (name, (size, block_count), step, search_fn) in &searches {
+        let label = format!("{:02}x{:02}@{:02}", size.x, size.y, step);
+        let board = generate_board_with_exact_step(*size, *block_count, step * 3, *step)
+            .unwrap_or_else(|e| panic!("label {}: {}", label, e));
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        let stats = search_fn(board.clone());
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+        println!(
+            "search-allocations/{}/{}: {} nodes, {:.2} allocations/node",
+            name,
+            label,
+            stats.nodes_expanded,
+            allocations as f64 / stats.nodes_expanded as f64
+        );
+        group.throughput(Throughput::Elements(stats.nodes_expanded));
+        group.bench_with_input(BenchmarkId::new(*name, &label), &board, |bencher, board| {
+            bencher.iter(|| search_fn(board.clone()))
+        });
+    }
+}
+
+criterion_group!(
+    benches,
+    bench_idastar,
+    bnech_iddfs,
+    bench_iddfs_transposition,
+    bench_idastar_ordering,
+    bench_idastar_pruning,
+    bench_iddfs_parallel,
+    bench_neighbors,
+    bench_move_block,
+    bench_random_walk,
+    bench_hole_set,
+    bench_heuristic,
+    bench_clone,
+    bench_visited_set,
+    bench_search_allocations
+);
 criterion_main!(benches);
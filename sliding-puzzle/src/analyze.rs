@@ -0,0 +1,165 @@
+use serde_json::{json, Value};
+use sliding_puzzle_core::{Board, Solvability, Vec2};
+use sliding_puzzle_search::{
+    heuristic::ManhattanHeuristic,
+    search::{idastar_with_stats, SearchOptions},
+};
+use std::io::Write;
+
+/// Result of solving the board with IDA*
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolveReport {
+    /// Optimal solution length, `None` if there is no solution
+    pub moves: Option<usize>,
+    pub nodes_expanded: u64,
+}
+
+/// Metrics of a board which help to pick an algorithm
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Analysis {
+    pub size: Vec2,
+    /// Block count by block size
+    pub shapes: Vec<(Vec2, usize)>,
+    pub hole_count: usize,
+    pub heuristic: i32,
+    pub branching_factor: usize,
+    pub holes_connected: bool,
+    pub solvability: Solvability,
+    /// Only filled if solving is requested
+    pub solve: Option<SolveReport>,
+}
+
+/// Format a size as rows x columns, e.g. 2x1 is a vertical block
+fn format_size(size: Vec2) -> String {
+    format!("{}x{}", size.y, size.x)
+}
+
+fn format_solvability(solvability: Solvability) -> &'static str {
+    match solvability {
+        Solvability::Solved => "solved",
+        Solvability::Unsolvable => "unsolvable",
+        Solvability::Unknown => "unknown",
+    }
+}
+
+/// Collect metrics of `board`, also solve it with IDA* if `solve`
+pub fn analyze(board: &Board, solve: bool) -> Analysis {
+    let solvability = board.quick_solvability();
+    let solve = solve.then(|| {
+        if solvability == Solvability::Unsolvable {
+            return SolveReport {
+                moves: None,
+                nodes_expanded: 0,
+            };
+        }
+        let (moves, stats) = idastar_with_stats(
+            board.clone(),
+            &ManhattanHeuristic,
+            &SearchOptions::default(),
+        );
+        SolveReport {
+            moves: moves.map(|moves| moves.len()),
+            nodes_expanded: stats.nodes_expanded,
+        }
+    });
+    Analysis {
+        size: board.size(),
+        shapes: board.shape_histogram().into_iter().collect(),
+        hole_count: board.hole_count(),
+        heuristic: board.heuristic(),
+        branching_factor: board.branching_factor(),
+        holes_connected: board.holes_connected(),
+        solvability,
+        solve,
+    }
+}
+
+impl Analysis {
+    pub fn write(&self, output: &mut dyn Write) -> std::io::Result<()> {
+        writeln!(output, "Size = {}", format_size(self.size))?;
+        let shapes = self
+            .shapes
+            .iter()
+            .map(|&(size, count)| format!("{} {}", count, format_size(size)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(output, "Blocks = {}", shapes)?;
+        writeln!(output, "Holes = {}", self.hole_count)?;
+        writeln!(output, "Heuristic = {}", self.heuristic)?;
+        writeln!(output, "Branching factor = {}", self.branching_factor)?;
+        writeln!(output, "Holes connected = {}", self.holes_connected)?;
+        writeln!(
+            output,
+            "Solvability = {}",
+            format_solvability(self.solvability)
+        )?;
+        if let Some(solve) = &self.solve {
+            match solve.moves {
+                Some(moves) => writeln!(output, "Optimal length = {}", moves)?,
+                None => writeln!(output, "Optimal length = no solution")?,
+            }
+            writeln!(output, "Nodes expanded = {}", solve.nodes_expanded)?;
+        }
+        Ok(())
+    }
+
+    pub fn to_json(&self) -> Value {
+        let shapes = self
+            .shapes
+            .iter()
+            .map(|&(size, count)| (format_size(size), json!(count)))
+            .collect::<serde_json::Map<_, _>>();
+        let mut result = json!({
+            "rows": self.size.y,
+            "cols": self.size.x,
+            "blocks": shapes,
+            "holes": self.hole_count,
+            "heuristic": self.heuristic,
+            "branching_factor": self.branching_factor,
+            "holes_connected": self.holes_connected,
+            "solvability": format_solvability(self.solvability),
+        });
+        if let Some(solve) = &self.solve {
+            result["optimal_length"] = json!(solve.moves);
+            result["nodes_expanded"] = json!(solve.nodes_expanded);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze() -> Result<(), String> {
+        let board = "2 3\n0 0 0\n0 2 1\n".parse::<Board>()?;
+        let analysis = analyze(&board, true);
+        let mut output = Vec::<u8>::new();
+        analysis.write(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        // Expanded nodes depend on the order moves are tried
+        assert!(
+            output.starts_with(
+                "Size = 2x3\n\
+                Blocks = 2 1x1\n\
+                Holes = 4\n\
+                Heuristic = 4\n\
+                Branching factor = 3\n\
+                Holes connected = true\n\
+                Solvability = unknown\n\
+                Optimal length = 4\n\
+                Nodes expanded = "
+            ),
+            "{}",
+            output
+        );
+
+        let json = analyze(&board, false).to_json();
+        assert_eq!(json["blocks"], json!({ "1x1": 2 }));
+        assert_eq!(json["solvability"], "unknown");
+        assert!(json.get("optimal_length").is_none());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+use crate::{
+    error::CliError,
+    output::{HumanWriter, ResultWriter, Solution},
+    search,
+};
+use rayon::prelude::*;
+use sliding_puzzle_core::{Board, Move};
+use sliding_puzzle_search::{
+    search::{SearchConstraints, SearchLimits, SearchOutcome},
+    solver::SolveContext,
+};
+use std::{
+    fs,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Result of a single board of the batch
+enum BoardResult {
+    Solved {
+        moves: Vec<Move>,
+        duration: Duration,
+    },
+    Failed {
+        duration: Duration,
+    },
+    /// The file cannot be read or parsed, the message tells why
+    Skipped(String),
+}
+
+/// Aggregated results of a batch
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Summary {
+    pub solved: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub total_duration: Duration,
+    /// Sum of solution lengths of solved boards
+    pub total_moves: usize,
+}
+
+impl Summary {
+    fn add(&mut self, result: &BoardResult) {
+        match result {
+            BoardResult::Solved { moves, duration } => {
+                self.solved += 1;
+                self.total_moves += moves.len();
+                self.total_duration += *duration;
+            }
+            BoardResult::Failed { duration } => {
+                self.failed += 1;
+                self.total_duration += *duration;
+            }
+            BoardResult::Skipped(_) => self.skipped += 1,
+        }
+    }
+
+    /// Mean run time of boards which were searched
+    pub fn mean_duration(&self) -> Duration {
+        match self.solved + self.failed {
+            0 => Duration::ZERO,
+            count => self.total_duration / count as u32,
+        }
+    }
+
+    /// Mean solution length of solved boards
+    pub fn mean_moves(&self) -> f64 {
+        match self.solved {
+            0 => 0.0,
+            count => self.total_moves as f64 / count as f64,
+        }
+    }
+
+    pub fn write(&self, output: &mut dyn Write) -> std::io::Result<()> {
+        writeln!(output, "Solved            = {}", self.solved)?;
+        writeln!(output, "Failed            = {}", self.failed)?;
+        writeln!(output, "Skipped           = {}", self.skipped)?;
+        writeln!(
+            output,
+            "Total run time    = {:.4} seconds",
+            self.total_duration.as_secs_f32()
+        )?;
+        writeln!(
+            output,
+            "Mean run time     = {:.4} seconds",
+            self.mean_duration().as_secs_f32()
+        )?;
+        writeln!(output, "Mean moves        = {:.2}", self.mean_moves())?;
+        Ok(())
+    }
+}
+
+/// `*.txt` files directly under `dir`, sorted by name
+fn board_files(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "txt") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Solve a single board file and write the result into `output_dir` with the same file name
+fn solve_file(
+    path: &Path,
+    output_dir: &Path,
+    algorithm: &str,
+    params: &search::Params,
+    quiet: bool,
+) -> Result<BoardResult, CliError> {
+    let board = match fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|input| input.parse::<Board>().map_err(|e| e.to_string()))
+    {
+        Ok(board) => board,
+        Err(e) => return Ok(BoardResult::Skipped(e)),
+    };
+    // Solvers are not shared between threads, each board builds its own
+    let solvers = search::registry(params);
+    let solver = solvers
+        .iter()
+        .find(|solver| solver.name() == algorithm)
+        .expect("Algorithm should be validated before the batch starts");
+    let mut context = SolveContext {
+        limits: SearchLimits::default(),
+        constraints: SearchConstraints::default(),
+        cancel: Arc::default(),
+        on_progress: &mut |_| {},
+        trace: None,
+    };
+
+    let start = Instant::now();
+    let (outcome, _) = search::execute(&**solver, board, &mut context);
+    let duration = start.elapsed();
+
+    let file_name = path.file_name().expect("Board files should have a name");
+    let output_path = output_dir.join(file_name);
+    let output_file = fs::File::create(&output_path)
+        .map_err(CliError::create(output_path.display().to_string()))?;
+    let mut output = BufWriter::new(output_file);
+    let writer = HumanWriter { quiet };
+    match outcome {
+        SearchOutcome::Solved(moves) => {
+            let solution = Solution {
+                duration,
+                solutions: std::slice::from_ref(&moves),
+                slide_from: None,
+                cost: None,
+                optimal: solver.is_optimal(),
+                prefix_len: 0,
+                stats: None,
+            };
+            writer.write_solution(&solution, &mut output)?;
+            Ok(BoardResult::Solved { moves, duration })
+        }
+        outcome => {
+            writer.write_failure(&outcome, None, &mut output)?;
+            Ok(BoardResult::Failed { duration })
+        }
+    }
+}
+
+/// Solve every `*.txt` board in `input_dir` with `jobs` threads, or as many as CPUs if `None`.
+/// Files which cannot be parsed are reported to stderr and skipped.
+/// `quiet` suppresses the run time in result files
+pub fn run(
+    input_dir: &Path,
+    output_dir: &Path,
+    algorithm: &str,
+    params: &search::Params,
+    jobs: Option<usize>,
+    quiet: bool,
+) -> Result<Summary, CliError> {
+    let files = board_files(input_dir).map_err(CliError::open(input_dir.display().to_string()))?;
+    fs::create_dir_all(output_dir).map_err(CliError::create(output_dir.display().to_string()))?;
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs.unwrap_or(0))
+        .build()
+        .map_err(std::io::Error::other)?;
+    let results = pool.install(|| {
+        files
+            .par_iter()
+            .map(|path| solve_file(path, output_dir, algorithm, params, quiet))
+            .collect::<Vec<_>>()
+    });
+
+    let mut summary = Summary::default();
+    for (path, result) in files.iter().zip(results) {
+        let result = result?;
+        if let BoardResult::Skipped(reason) = &result {
+            eprintln!("Skip {}: {}", path.display(), reason);
+        }
+        summary.add(&result);
+    }
+    Ok(summary)
+}
@@ -0,0 +1,216 @@
+use crate::search;
+use clap::ArgEnum;
+use sliding_puzzle_core::Board;
+use sliding_puzzle_search::{
+    search::{SearchConstraints, SearchLimits, SearchOutcome},
+    solver::SolveContext,
+};
+use std::{
+    io::Write,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Format of the comparison
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ArgEnum)]
+pub enum CompareFormat {
+    /// Aligned columns
+    Table,
+    Csv,
+}
+
+/// Result of a single algorithm
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    pub algorithm: String,
+    pub optimal: bool,
+    /// Solution length, `None` if no solution is found
+    pub moves: Option<usize>,
+    /// Why no solution is found, or "solved"
+    pub outcome: &'static str,
+    pub duration: Duration,
+    /// `None` if the solver does not report statistics
+    pub nodes_expanded: Option<u64>,
+}
+
+fn describe(outcome: &SearchOutcome) -> &'static str {
+    match outcome {
+        SearchOutcome::Solved(_) => "solved",
+        SearchOutcome::Unsolvable | SearchOutcome::NoSolution => "no solution",
+        SearchOutcome::LimitReached => "limit reached",
+        SearchOutcome::Cancelled => "cancelled",
+    }
+}
+
+/// Solve a clone of `board` with a single algorithm, which must be registered
+fn run_one(board: &Board, algorithm: &str, limits: SearchLimits) -> Row {
+    let solvers = search::registry(&search::Params::default());
+    let solver = solvers
+        .iter()
+        .find(|solver| solver.name() == algorithm)
+        .expect("Algorithms should be validated before comparing");
+    let mut context = SolveContext {
+        limits,
+        constraints: SearchConstraints::default(),
+        cancel: Arc::default(),
+        on_progress: &mut |_| {},
+        trace: None,
+    };
+    let start = Instant::now();
+    let (outcome, stats) = search::execute(&**solver, board.clone(), &mut context);
+    let duration = start.elapsed();
+    Row {
+        algorithm: algorithm.to_string(),
+        optimal: solver.is_optimal(),
+        outcome: describe(&outcome),
+        moves: outcome.into_moves().map(|moves| moves.len()),
+        duration,
+        nodes_expanded: stats.map(|stats| stats.nodes_expanded),
+    }
+}
+
+/// Run each algorithm on `board`, one thread per algorithm if `parallel`.
+/// Rows are in the same order as `algorithms`
+pub fn run(board: &Board, algorithms: &[String], limits: SearchLimits, parallel: bool) -> Vec<Row> {
+    if !parallel {
+        return algorithms
+            .iter()
+            .map(|algorithm| run_one(board, algorithm, limits))
+            .collect();
+    }
+    thread::scope(|scope| {
+        let handles = algorithms
+            .iter()
+            .map(|algorithm| scope.spawn(move || run_one(board, algorithm, limits)))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("Search thread panicked"))
+            .collect()
+    })
+}
+
+/// Describe the disagreement if optimal algorithms find solutions of different lengths
+pub fn check_optimal_lengths(rows: &[Row]) -> Option<String> {
+    let optimal = rows
+        .iter()
+        .filter_map(|row| Some((row, row.moves.filter(|_| row.optimal)?)))
+        .collect::<Vec<_>>();
+    let (_, first) = optimal.first()?;
+    if optimal.iter().all(|(_, moves)| moves == first) {
+        return None;
+    }
+    let lengths = optimal
+        .iter()
+        .map(|(row, moves)| format!("{} {}", row.algorithm, moves))
+        .collect::<Vec<_>>()
+        .join(", ");
+    Some(format!(
+        "optimal algorithms disagree on solution length: {}",
+        lengths
+    ))
+}
+
+const HEADER: [&str; 5] = ["algorithm", "outcome", "moves", "runtime_secs", "nodes"];
+
+fn cells(row: &Row) -> [String; 5] {
+    let optional = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+    [
+        row.algorithm.clone(),
+        row.outcome.to_string(),
+        optional(row.moves.map(|moves| moves.to_string())),
+        format!("{:.4}", row.duration.as_secs_f64()),
+        optional(row.nodes_expanded.map(|nodes| nodes.to_string())),
+    ]
+}
+
+/// Write rows in given format
+pub fn write(rows: &[Row], format: CompareFormat, output: &mut dyn Write) -> std::io::Result<()> {
+    let lines = std::iter::once(HEADER.map(str::to_string))
+        .chain(rows.iter().map(cells))
+        .collect::<Vec<_>>();
+    match format {
+        CompareFormat::Csv => {
+            for line in lines {
+                writeln!(output, "{}", line.join(","))?;
+            }
+        }
+        CompareFormat::Table => {
+            let widths = (0..HEADER.len())
+                .map(|column| lines.iter().map(|line| line[column].len()).max())
+                .collect::<Option<Vec<_>>>()
+                .unwrap_or_default();
+            for line in lines {
+                // The name is left-aligned, numbers are right-aligned
+                let mut text = format!("{:<width$}", line[0], width = widths[0]);
+                for (cell, width) in line.iter().zip(&widths).skip(1) {
+                    text.push_str(&format!("  {:>width$}", cell, width = width));
+                }
+                writeln!(output, "{}", text)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(algorithm: &str, optimal: bool, moves: Option<usize>) -> Row {
+        Row {
+            algorithm: algorithm.to_string(),
+            optimal,
+            moves,
+            outcome: if moves.is_some() {
+                "solved"
+            } else {
+                "limit reached"
+            },
+            duration: Duration::from_millis(250),
+            nodes_expanded: moves.map(|moves| moves as u64 * 10),
+        }
+    }
+
+    #[test]
+    fn test_check_optimal_lengths() {
+        let agreed = [
+            row("iddfs", true, Some(4)),
+            row("greedy", false, Some(6)),
+            row("astar", true, None),
+            row("ida-star", true, Some(4)),
+        ];
+        assert_eq!(check_optimal_lengths(&agreed), None);
+        assert_eq!(check_optimal_lengths(&[]), None);
+
+        let disagreed = [row("iddfs", true, Some(4)), row("ida-star", true, Some(5))];
+        assert_eq!(
+            check_optimal_lengths(&disagreed),
+            Some("optimal algorithms disagree on solution length: iddfs 4, ida-star 5".into())
+        );
+    }
+
+    #[test]
+    fn test_write() {
+        let rows = [row("ida-star", true, Some(12)), row("bfs", true, None)];
+        let written = |format| {
+            let mut output = Vec::<u8>::new();
+            write(&rows, format, &mut output).unwrap();
+            String::from_utf8(output).unwrap()
+        };
+
+        assert_eq!(
+            written(CompareFormat::Csv),
+            "algorithm,outcome,moves,runtime_secs,nodes\n\
+            ida-star,solved,12,0.2500,120\n\
+            bfs,limit reached,-,0.2500,-\n"
+        );
+        assert_eq!(
+            written(CompareFormat::Table),
+            "algorithm        outcome  moves  runtime_secs  nodes\n\
+             ida-star          solved     12        0.2500    120\n\
+             bfs        limit reached      -        0.2500      -\n"
+        );
+    }
+}
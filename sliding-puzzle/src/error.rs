@@ -0,0 +1,86 @@
+use std::fmt::Display;
+
+/// Exit status of a successful run
+pub const EXIT_SUCCESS: i32 = 0;
+/// Exit status when the board has no solution, or given moves do not solve it
+pub const EXIT_NO_SOLUTION: i32 = 1;
+/// Exit status when an input cannot be read or parsed
+pub const EXIT_INPUT_ERROR: i32 = 2;
+/// Exit status of any other failure, e.g. writing the output
+pub const EXIT_INTERNAL_ERROR: i32 = 3;
+/// Exit status when a search is stopped by Ctrl-C or a limit
+pub const EXIT_INTERRUPTED: i32 = 4;
+
+/// Why a command fails
+#[derive(Debug)]
+pub enum CliError {
+    /// An input file or directory cannot be read
+    Open {
+        path: String,
+        source: std::io::Error,
+    },
+    /// An output file or directory cannot be created
+    Create {
+        path: String,
+        source: std::io::Error,
+    },
+    /// The board cannot be parsed, `source` is the quoted path, stdin or inline board
+    InvalidBoard { source: String, reason: String },
+    /// The moves file cannot be parsed
+    InvalidMoves { path: String, reason: String },
+    /// Some boards of a batch cannot be parsed, they are reported separately
+    SkippedBoards(usize),
+    /// Any other IO error
+    Io(std::io::Error),
+}
+
+impl CliError {
+    pub fn open(path: impl Into<String>) -> impl FnOnce(std::io::Error) -> Self {
+        let path = path.into();
+        move |source| CliError::Open { path, source }
+    }
+
+    pub fn create(path: impl Into<String>) -> impl FnOnce(std::io::Error) -> Self {
+        let path = path.into();
+        move |source| CliError::Create { path, source }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Open { .. }
+            | CliError::InvalidBoard { .. }
+            | CliError::InvalidMoves { .. }
+            | CliError::SkippedBoards(_) => EXIT_INPUT_ERROR,
+            CliError::Create { .. } | CliError::Io(_) => EXIT_INTERNAL_ERROR,
+        }
+    }
+}
+
+impl Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::Open { path, source } => write!(f, "cannot open '{}': {}", path, source),
+            CliError::Create { path, source } => {
+                write!(f, "cannot create '{}': {}", path, source)
+            }
+            CliError::InvalidBoard { source, reason } => {
+                write!(f, "invalid board in {}: {}", source, reason)
+            }
+            CliError::InvalidMoves { path, reason } => {
+                write!(f, "invalid moves in '{}': {}", path, reason)
+            }
+            CliError::SkippedBoards(count) => {
+                write!(f, "{} boards cannot be parsed and are skipped", count)
+            }
+            CliError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+impl From<std::io::Error> for CliError {
+    fn from(e: std::io::Error) -> Self {
+        CliError::Io(e)
+    }
+}
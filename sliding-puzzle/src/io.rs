@@ -0,0 +1,174 @@
+use clap::ArgEnum;
+use serde_json::{json, Map, Value};
+use sliding_puzzle_core::{BlockId, Board, Matrix2D, Move, Vec2};
+use sliding_puzzle_search::search::SearchStats;
+use std::time::Duration;
+
+/// Format of boards read and written
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ArgEnum)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+impl Format {
+    /// Parse a board in this format
+    pub fn parse_board(self, input: &str) -> Result<Board, String> {
+        match self {
+            Format::Text => Ok(input.parse::<Board>()?),
+            Format::Json => board_from_json(input),
+        }
+    }
+}
+
+/// Get a field of the top-level JSON object
+fn field<'a>(object: &'a Map<String, Value>, name: &str) -> Result<&'a Value, String> {
+    object
+        .get(name)
+        .ok_or_else(|| format!("{}: missing field", name))
+}
+
+/// Convert a JSON value to an integer of type `T`, the error includes `path`
+fn integer<T: TryFrom<i64>>(value: &Value, path: &str) -> Result<T, String> {
+    value
+        .as_i64()
+        .and_then(|v| T::try_from(v).ok())
+        .ok_or_else(|| format!("{}: expected an integer in range, got {}", path, value))
+}
+
+fn array<'a>(value: &'a Value, path: &str) -> Result<&'a Vec<Value>, String> {
+    value
+        .as_array()
+        .ok_or_else(|| format!("{}: expected an array, got {}", path, value))
+}
+
+/// Parse a board like `{"rows":2,"cols":3,"grid":[[1,1,0],[2,3,0]]}`
+pub fn board_from_json(input: &str) -> Result<Board, String> {
+    let value = serde_json::from_str::<Value>(input).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let object = value
+        .as_object()
+        .ok_or_else(|| format!("expected an object, got {}", value))?;
+    let rows = integer::<i16>(field(object, "rows")?, "rows")?;
+    let cols = integer::<i16>(field(object, "cols")?, "cols")?;
+    let grid = array(field(object, "grid")?, "grid")?;
+    if grid.len() != rows.max(0) as usize {
+        return Err(format!("grid: expected {} rows, got {}", rows, grid.len()));
+    }
+
+    let mut cells = vec![];
+    for (y, row) in grid.iter().enumerate() {
+        let path = format!("grid[{}]", y);
+        let row = array(row, &path)?;
+        if row.len() != cols.max(0) as usize {
+            return Err(format!(
+                "{}: expected {} cells, got {}",
+                path,
+                cols,
+                row.len()
+            ));
+        }
+        for (x, cell) in row.iter().enumerate() {
+            cells.push(integer::<BlockId>(cell, &format!("{}[{}]", path, x))?);
+        }
+    }
+    let grid =
+        Matrix2D::from_vec(Vec2::new(cols, rows), cells).map_err(|e| format!("grid: {}", e))?;
+    Board::try_from(grid).map_err(|e| format!("grid: {}", e))
+}
+
+/// Serialize a board in the format [`board_from_json`] parses
+pub fn board_to_json(board: &Board) -> Value {
+    let size = board.size();
+    let grid = board
+        .grid()
+        .rows()
+        .map(|row| row.to_vec())
+        .collect::<Vec<_>>();
+    json!({
+        "rows": size.y,
+        "cols": size.x,
+        "grid": grid,
+    })
+}
+
+fn stats_to_json(result: &mut Map<String, Value>, stats: Option<&SearchStats>) {
+    if let Some(stats) = stats {
+        result.insert("nodes_expanded".to_string(), json!(stats.nodes_expanded));
+        result.insert("max_depth".to_string(), json!(stats.max_depth));
+        result.insert("iterations".to_string(), json!(stats.iterations));
+    }
+}
+
+/// Serialize a found solution
+pub fn solution_to_json(moves: &[Move], duration: Duration, stats: Option<&SearchStats>) -> Value {
+    let moves = moves
+        .iter()
+        .map(|&(id, dir)| json!({ "id": id, "dir": dir.to_string() }))
+        .collect::<Vec<_>>();
+    let mut result = Map::new();
+    result.insert("solved".to_string(), json!(true));
+    result.insert("move_count".to_string(), json!(moves.len()));
+    result.insert("moves".to_string(), Value::Array(moves));
+    result.insert("runtime_secs".to_string(), json!(duration.as_secs_f64()));
+    stats_to_json(&mut result, stats);
+    Value::Object(result)
+}
+
+/// Serialize a search without solution, `reason` tells why it stopped if it was aborted
+pub fn failure_to_json(reason: Option<&str>, stats: Option<&SearchStats>) -> Value {
+    let mut result = Map::new();
+    result.insert("solved".to_string(), json!(false));
+    if let Some(reason) = reason {
+        result.insert("reason".to_string(), json!(reason));
+    }
+    stats_to_json(&mut result, stats);
+    Value::Object(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sliding_puzzle_core::Dir;
+
+    #[test]
+    fn test_board_round_trip() -> Result<(), String> {
+        let board = "2 3\n1 1 0\n2 3 0\n".parse::<Board>()?;
+        let json = board_to_json(&board);
+        assert_eq!(
+            json,
+            json!({ "rows": 2, "cols": 3, "grid": [[1, 1, 0], [2, 3, 0]] })
+        );
+        assert_eq!(board_from_json(&json.to_string())?, board);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_malformed_board() {
+        let error = |input: &str| board_from_json(input).unwrap_err();
+
+        assert!(error("{").starts_with("Invalid JSON"));
+        assert_eq!(error(r#"{"rows":1,"grid":[[1]]}"#), "cols: missing field");
+        assert!(error(r#"{"rows":"1","cols":1,"grid":[[1]]}"#).starts_with("rows: "));
+        assert!(error(r#"{"rows":2,"cols":2,"grid":[[1,0],3]}"#).starts_with("grid[1]: "));
+        assert!(error(r#"{"rows":2,"cols":2,"grid":[[1,0],[0,"x"]]}"#).starts_with("grid[1][1]: "));
+        assert!(error(r#"{"rows":2,"cols":2,"grid":[[1,0],[0]]}"#).starts_with("grid[1]: "));
+        assert!(error(r#"{"rows":2,"cols":2,"grid":[[1,0],[0,1]]}"#).starts_with("grid: "));
+    }
+
+    #[test]
+    fn test_solution_to_json() {
+        let moves = [(5, Dir::Left), (2, Dir::Up)];
+        let json = solution_to_json(&moves, Duration::from_millis(420), None);
+        assert_eq!(
+            json,
+            json!({
+                "solved": true,
+                "moves": [{ "id": 5, "dir": "L" }, { "id": 2, "dir": "U" }],
+                "move_count": 2,
+                "runtime_secs": 0.42,
+            })
+        );
+        assert_eq!(failure_to_json(None, None), json!({ "solved": false }));
+    }
+}
@@ -1,11 +1,43 @@
+mod analyze;
+mod batch;
+mod compare;
+mod error;
+mod io;
+mod output;
+mod play;
+mod replay;
 mod search;
 mod util;
 
-use clap::{Parser, Subcommand};
-use sliding_puzzle_core::{Board, Dir, Move, Vec2};
+use clap::{CommandFactory, ErrorKind, Parser, Subcommand};
+use error::{CliError, EXIT_INTERRUPTED, EXIT_NO_SOLUTION, EXIT_SUCCESS};
+use rand::{rngs::StdRng, SeedableRng};
+use sliding_puzzle_core::{
+    format_move,
+    render::svg::{self, SvgOptions},
+    BlockId, Board, Move, RenderStyle, Vec2,
+};
+use sliding_puzzle_search::{
+    search::{
+        all_optimal, estimate_difficulty_with_cap, hint_with_depth, idastar_bounded,
+        optimal_min_blocks, MoveSemantics, ProgressEvent, SearchConstraints, SearchLimits,
+        SearchOutcome, DIFFICULTY_MAX_NODES, HINT_MAX_DEPTH,
+    },
+    solver::{Feature, SolveContext, Solver},
+    trace::TraceCollector,
+};
 use std::{
+    cell::Cell,
+    collections::HashSet,
     fs,
-    io::{BufWriter, Write},
+    io::{BufWriter, IsTerminal, Read, Write},
+    path::Path,
+    process,
+    sync::{
+        atomic::{self, AtomicBool},
+        mpsc, Arc,
+    },
+    thread,
     time::{Duration, Instant},
 };
 
@@ -16,12 +48,26 @@ use tikv_jemallocator::Jemalloc;
 #[global_allocator]
 static GLOBAL: Jemalloc = Jemalloc;
 
+/// Exit statuses listed in `--help`
+const EXIT_CODES_HELP: &str = "EXIT STATUS:
+    0    Success
+    1    No solution is found, or given moves are illegal
+    2    An input cannot be read or parsed, or verify does not reach the goal
+    3    Internal error, e.g. the output cannot be written
+    4    The search is interrupted by Ctrl-C or a limit";
+
+/// Exit status of verify when all moves are legal but the goal is not reached
+const EXIT_GOAL_NOT_REACHED: i32 = 2;
+
 /// Sliding puzzle CLI entry
 #[derive(Parser, Debug)]
-#[clap(author, version, about, long_about = None)]
+#[clap(author, version, about, long_about = None, after_help = EXIT_CODES_HELP)]
 struct Cli {
     #[clap(subcommand)]
     command: Command,
+    /// Do not print the run time
+    #[clap(short, long, global = true)]
+    quiet: bool,
     #[cfg(not(target_env = "msvc"))]
     /// Print malloc statistic after execution
     #[clap(long)]
@@ -32,30 +78,252 @@ struct Cli {
 enum Command {
     /// Search optimal solution of given board
     Search {
-        /// Path to the input file
+        /// Path to the input file, read stdin if it is `-` or omitted
         #[clap(short, long)]
-        input: String,
+        input: Option<String>,
+        /// The board itself instead of a file, lines are separated by `;`, e.g. "2 2;1 0;0 2"
+        #[clap(long, conflicts_with = "input")]
+        board: Option<String>,
         /// Path to the output file, default to stdout
         #[clap(short, long)]
         output: Option<String>,
-        /// Algorithm to use, default to IDDFS
-        #[clap(arg_enum, short, long, default_value_t = search::Algorithm::IDDFS)]
-        algorithm: search::Algorithm,
+        /// Algorithm to use, one of auto, iddfs, iddfs-parallel, ida-star, ida-star-parallel,
+        /// astar, dfbnb, weighted-astar, bfs, greedy, beam and manual. auto picks one by the board
+        #[clap(short, long, default_value = "auto")]
+        algorithm: String,
+        /// Heuristic weight, only valid for weighted A*. Default to 1.0
+        #[clap(long)]
+        weight: Option<f32>,
+        /// Number of worker threads, only valid for parallel algorithms. Default to 4
+        #[clap(long)]
+        threads: Option<usize>,
+        /// Number of boards kept at each depth, only valid for beam search. Default to 512
+        #[clap(long)]
+        beam_width: Option<usize>,
+        /// Heuristic to use, only valid for IDA*. Default to manhattan
+        #[clap(arg_enum, long)]
+        heuristic: Option<search::HeuristicKind>,
+        /// Print search statistics after the run time
+        #[clap(long)]
+        stats: bool,
+        /// Print progress to stderr, only valid for IDDFS, IDA* and auto
+        #[clap(short, long)]
+        verbose: bool,
+        /// Abort the search after given seconds, only valid for IDDFS
+        #[clap(long)]
+        timeout: Option<f64>,
+        /// Abort the search after expanding given number of nodes, only valid for IDDFS
+        #[clap(long)]
+        max_nodes: Option<u64>,
+        /// Print every optimal solution, one per line, only valid for optimal algorithms
+        #[clap(long)]
+        all_solutions: bool,
+        /// At most how many solutions to print with --all-solutions. Default to 100
+        #[clap(long, requires = "all-solutions")]
+        cap: Option<usize>,
+        /// Comma-delimited ids of blocks which must never move, e.g. 3,7.
+        /// Only valid for IDDFS and IDA*
+        #[clap(long, parse(try_from_str = util::ids_from_str), conflicts_with = "all-solutions")]
+        freeze: Option<HashSet<BlockId>>,
+        /// Cost of each move minimized by the search, only valid for A*. Default to uniform
+        #[clap(arg_enum, long, conflicts_with_all = &["all-solutions", "tie-break"])]
+        cost: Option<search::CostKind>,
+        /// How to pick among optimal solutions, only valid for optimal algorithms
+        #[clap(arg_enum, long, conflicts_with_all = &["all-solutions", "freeze"])]
+        tie_break: Option<search::TieBreak>,
+        /// Each move slides the block until it hits something, only valid for IDDFS and IDA*.
+        /// Moves are printed with the distance travelled, e.g. 5R3
+        #[clap(long, conflicts_with_all = &["all-solutions", "tie-break", "cost"])]
+        slide: bool,
+        /// Write explored nodes as a Graphviz DOT file to given path, only valid for IDA*
+        #[clap(long)]
+        trace: Option<String>,
+        /// Path to moves applied before the search, in any format verify accepts.
+        /// They are printed before the moves found by the search
+        #[clap(long, conflicts_with = "slide")]
+        prefix_moves: Option<String>,
+        /// Format of the result. json reads the input board as JSON as well. Default to human
+        #[clap(arg_enum, long)]
+        format: Option<output::OutputFormat>,
+    },
+    /// Print the best next move of given board
+    Hint {
+        /// Path to the input file, read stdin if it is `-` or omitted
+        #[clap(short, long)]
+        input: Option<String>,
+        /// Search at most this many moves ahead, fall back to the move minimizing
+        /// the heuristic if no solution is found. Default to 64
+        #[clap(long)]
+        max_depth: Option<usize>,
+    },
+    /// Check whether given moves solve the board. Exit with status 1 if a move is illegal,
+    /// or 2 if all moves are legal but the goal is not reached
+    Verify {
+        /// Path to the input file, read stdin if it is `-` or omitted
+        #[clap(short, long)]
+        input: Option<String>,
+        /// Path to the moves, either a list like "1U 2L" or the output of search,
+        /// in which case the first solution is checked
+        #[clap(short, long)]
+        moves: String,
+    },
+    /// Solve every *.txt board in a directory, write one result per board and print a summary.
+    /// Exit with non-zero status if any board cannot be parsed
+    Batch {
+        /// Directory of the input boards
+        #[clap(long)]
+        input_dir: String,
+        /// Directory of the results, each is named after its board. Created if missing
+        #[clap(long)]
+        output_dir: String,
+        /// Algorithm to use, see search. Default to auto
+        #[clap(short, long, default_value = "auto")]
+        algorithm: String,
+        /// Number of boards solved in parallel. Default to the number of CPUs
+        #[clap(short, long)]
+        jobs: Option<usize>,
+    },
+    /// Play given moves on the board, print the board after each move.
+    /// Blocks are colored if stdout is a terminal and NO_COLOR is not set
+    Replay {
+        /// Path to the input file, read stdin if it is `-` or omitted
+        #[clap(short, long)]
+        input: Option<String>,
+        /// Path to the moves, in any format verify accepts
+        #[clap(short, long)]
+        moves: String,
+        /// Pause between frames in milliseconds
+        #[clap(long, default_value_t = 300)]
+        delay_ms: u64,
+        /// Wait for Enter between frames instead of pausing
+        #[clap(long)]
+        step: bool,
+        /// Do not clear the screen between frames
+        #[clap(long)]
+        no_clear: bool,
+    },
+    /// Run several algorithms on the same board and compare their results.
+    /// Warn if optimal algorithms find solutions of different lengths
+    Compare {
+        /// Path to the input file, read stdin if it is `-` or omitted
+        #[clap(short, long)]
+        input: Option<String>,
+        /// Comma-delimited algorithms to compare, see search
+        #[clap(short, long, value_delimiter = ',', required = true)]
+        algorithms: Vec<String>,
+        /// Abort each search after given seconds, only valid for algorithms supporting limits
+        #[clap(long)]
+        timeout: Option<f64>,
+        /// Run all algorithms at the same time, each in its own thread
+        #[clap(long)]
+        parallel: bool,
+        /// Format of the comparison
+        #[clap(arg_enum, long, default_value = "table")]
+        format: compare::CompareFormat,
+    },
+    /// Print metrics of a board which help to pick an algorithm
+    Analyze {
+        /// Path to the input file, read stdin if it is `-` or omitted
+        #[clap(short, long)]
+        input: Option<String>,
+        /// The board itself instead of a file, see search
+        #[clap(long, conflicts_with = "input")]
+        board: Option<String>,
+        /// Also solve the board with IDA*, report the optimal length and expanded nodes
+        #[clap(long)]
+        solve: bool,
+        /// Format of the input board and the metrics. Default to text
+        #[clap(arg_enum, long)]
+        format: Option<io::Format>,
+    },
+    /// Relabel blocks in row-major order of their top-left cells. The goal is relabeled
+    /// the same way and written after the board
+    Canonicalize {
+        /// Path to the input file, read stdin if it is `-` or omitted
+        #[clap(short, long)]
+        input: Option<String>,
+        /// Path to the output file, default to stdout
+        #[clap(short, long)]
+        output: Option<String>,
+        /// Print the new id of each block to stderr, like "3 -> 1"
+        #[clap(long)]
+        print_mapping: bool,
+    },
+    /// Estimate how hard the board is by solving it with IDA*, and label it
+    /// easy, medium or hard
+    Difficulty {
+        /// Path to the input file, read stdin if it is `-` or omitted
+        #[clap(short, long)]
+        input: Option<String>,
+        /// Lowest scores of medium and hard boards
+        #[clap(long, value_delimiter = ',', default_value = "5,12")]
+        thresholds: Vec<f64>,
+        /// Give up after expanding about this many nodes, and report a lower bound of the length
+        #[clap(long, default_value_t = DIFFICULTY_MAX_NODES)]
+        max_nodes: u64,
+    },
+    /// Play the board interactively in the terminal, see the key list at the bottom of the screen.
+    /// The manual algorithm of search stays for piped moves
+    Play {
+        /// Path to the input file
+        #[clap(short, long)]
+        input: Option<String>,
+        /// The board itself instead of a file, see search
+        #[clap(long, conflicts_with = "input")]
+        board: Option<String>,
+    },
+    /// Draw the board as an SVG image, or every step of a solution if moves are given
+    Render {
+        /// Path to the input file, read stdin if it is `-` or omitted
+        #[clap(short, long)]
+        input: Option<String>,
+        /// Path to the SVG file, default to stdout
+        #[clap(short, long)]
+        output: Option<String>,
+        /// Path to the moves, in any format verify accepts. Each step becomes a frame
+        /// shown for --frame-secs
+        #[clap(short, long)]
+        moves: Option<String>,
+        /// Side length of a cell in pixels
+        #[clap(long, default_value_t = 40)]
+        cell_size: u32,
+        /// How long each frame is shown, in seconds
+        #[clap(long, default_value_t = 1.0)]
+        frame_secs: f64,
     },
     /// Generate a board
     Generate {
-        /// Path to the output file, default to stdout
+        /// Path to the output file, default to stdout. If it is a directory,
+        /// each board is written to its own file named by --output-pattern
         #[clap(short, long)]
         output: Option<String>,
+        /// Name of the file of each board, `{i}` is replaced by the index of the board.
+        /// Relative to --output if it is a directory
+        #[clap(long)]
+        output_pattern: Option<String>,
+        /// How many boards to generate, board i uses seed + i.
+        /// Boards written to the same file are separated by a blank line
+        #[clap(long, default_value_t = 1)]
+        count: u64,
         /// The output board size
-        #[clap(short, long, parse(try_from_str = util::vec2_from_str))]
+        #[clap(short, long)]
         size: Vec2,
-        /// At most how many blocks should be generated
+        /// How many blocks should be generated, fail if they cannot fit into the board
         #[clap(short = 'n', long)]
-        block_count: i8,
+        block_count: BlockId,
         /// At most how many round to shuffle the board
         #[clap(long, default_value_t = 8)]
         shuffle_round: usize,
+        /// Seed of the random number generator, the same seed always yields the same board.
+        /// A random one is printed to stderr if omitted, and the text output records it
+        #[clap(long)]
+        seed: Option<u64>,
+        /// Format of the output board. Default to text
+        #[clap(arg_enum, long)]
+        format: Option<io::Format>,
+        /// Note written as `#` lines before the board, only for the text format
+        #[clap(long)]
+        comment: Option<String>,
     },
 }
 
@@ -66,88 +334,690 @@ fn print_malloc_stats() {
     }
 }
 
-fn write_success_result(
-    duration: Duration,
-    moves: Vec<Move>,
-    output: &mut dyn Write,
-) -> std::io::Result<()> {
-    writeln!(
-        output,
-        "Total run time = {:.4} seconds.",
-        duration.as_secs_f32()
-    )?;
-    writeln!(output, "An optimal solution has {} moves:", moves.len())?;
-    let moves = moves
-        .into_iter()
-        .map(|(id, dir)| {
-            let dir = match dir {
-                Dir::Up => 'U',
-                Dir::Down => 'D',
-                Dir::Left => 'L',
-                Dir::Right => 'R',
-            };
-            format!("{}{} ", id, dir)
-        })
-        .collect::<String>();
-    writeln!(output, "{}", &moves)?;
+/// Read moves from the output of search, or a bare list of moves
+fn parse_moves(content: &str) -> Result<Vec<Move>, String> {
+    let mut lines = content.lines();
+    if lines.any(|line| line.contains("solution has")) {
+        util::moves_from_str(lines.next().unwrap_or_default())
+    } else {
+        util::moves_from_str(content)
+    }
+}
+
+/// Read moves from given path, see [`parse_moves`]
+fn read_moves(path: String) -> Result<Vec<Move>, CliError> {
+    let content = fs::read_to_string(&path).map_err(CliError::open(&path))?;
+    parse_moves(&content).map_err(|reason| CliError::InvalidMoves { path, reason })
+}
+
+/// Exit with an error for an option the algorithm does not accept
+fn unsupported(option: &str, algorithm: &str) -> ! {
+    Cli::command()
+        .error(
+            ErrorKind::ArgumentConflict,
+            format!("{} is not supported by {}", option, algorithm),
+        )
+        .exit()
+}
+
+/// Find the solver selected by `--algorithm`, exit with an error if there is none
+fn find_solver<'a>(solvers: &'a [Box<dyn Solver>], algorithm: &str) -> &'a dyn Solver {
+    match solvers.iter().find(|solver| solver.name() == algorithm) {
+        Some(solver) => &**solver,
+        None => {
+            let names = solvers
+                .iter()
+                .map(|solver| solver.name())
+                .collect::<Vec<_>>()
+                .join(", ");
+            Cli::command()
+                .error(
+                    ErrorKind::InvalidValue,
+                    format!(
+                        "invalid algorithm {}, possible values: {}",
+                        algorithm, names
+                    ),
+                )
+                .exit();
+        }
+    }
+}
 
-    Ok(())
+/// Read the board from given path, or from stdin if the path is `-` or omitted
+fn read_board(input: Option<String>, format: io::Format) -> Result<Board, CliError> {
+    let (source, content) = match input.as_deref() {
+        Some("-") | None => {
+            let stdin = std::io::stdin();
+            if input.is_none() && stdin.is_terminal() {
+                Cli::command()
+                    .error(
+                        ErrorKind::MissingRequiredArgument,
+                        "--input is required unless a board is piped to stdin",
+                    )
+                    .exit();
+            }
+            let mut content = String::new();
+            stdin.lock().read_to_string(&mut content)?;
+            ("stdin".to_string(), content)
+        }
+        Some(path) => (
+            format!("'{}'", path),
+            fs::read_to_string(path).map_err(CliError::open(path))?,
+        ),
+    };
+    let board = if content.trim().is_empty() {
+        Err("input is empty".to_string())
+    } else {
+        format.parse_board(&content)
+    };
+    board.map_err(|reason| CliError::InvalidBoard { source, reason })
 }
 
-fn write_fail_result(output: &mut dyn Write) -> std::io::Result<()> {
-    writeln!(output, "no solution")?;
-    Ok(())
+/// Read the board from `--board` if given, whose lines are separated by `;`, else from `input`
+fn read_board_or_inline(
+    input: Option<String>,
+    inline: Option<String>,
+    format: io::Format,
+) -> Result<Board, CliError> {
+    let inline = match inline {
+        Some(inline) => inline,
+        None => return read_board(input, format),
+    };
+    let board = if inline.trim().is_empty() {
+        Err("input is empty".to_string())
+    } else {
+        format.parse_board(&inline.replace(';', "\n"))
+    };
+    board.map_err(|reason| CliError::InvalidBoard {
+        source: "inline board".to_string(),
+        reason,
+    })
 }
 
 /// Get output from given path. If not, use stdout
-fn get_output(output: Option<String>) -> std::io::Result<BufWriter<Box<dyn Write>>> {
+fn get_output(output: Option<String>) -> Result<BufWriter<Box<dyn Write>>, CliError> {
     let output: Box<dyn Write> = match output {
-        Some(output) => Box::new(fs::File::create(output)?),
+        Some(output) => Box::new(fs::File::create(&output).map_err(CliError::create(output))?),
         None => Box::new(std::io::stdout()),
     };
     Ok(BufWriter::new(output))
 }
 
-fn main() -> std::io::Result<()> {
+fn main() {
     let start = Instant::now();
     let cli = Cli::parse();
     pretty_env_logger::init();
-    match cli.command {
+    let print_malloc_stats = cli.print_malloc_stats;
+    let code = run(cli.command, cli.quiet, start).unwrap_or_else(|e| {
+        eprintln!("error: {}", e);
+        e.exit_code()
+    });
+    if print_malloc_stats {
+        self::print_malloc_stats();
+    }
+    process::exit(code);
+}
+
+/// Run the command, return the exit status
+fn run(command: Command, quiet: bool, start: Instant) -> Result<i32, CliError> {
+    match command {
         Command::Search {
             input,
+            board,
             output,
             algorithm,
+            weight,
+            threads,
+            beam_width,
+            heuristic,
+            stats,
+            verbose,
+            timeout,
+            max_nodes,
+            all_solutions,
+            cap,
+            freeze,
+            tie_break,
+            cost,
+            slide,
+            trace,
+            prefix_moves,
+            format,
         } => {
-            let board = fs::read_to_string(input)?
-                .parse::<Board>()
-                .expect("Invalid input file");
+            let mut params = search::Params::default();
+            if let Some(weight) = weight {
+                params.weight = weight;
+            }
+            if let Some(threads) = threads {
+                params.threads = threads;
+            }
+            if let Some(beam_width) = beam_width {
+                params.beam_width = beam_width;
+            }
+            if let Some(heuristic) = heuristic {
+                params.heuristic = heuristic;
+            }
+            if let Some(cost) = cost {
+                params.cost = cost;
+            }
+            let solvers = search::registry(&params);
+            let solver = find_solver(&solvers, &algorithm);
+            let used_options = [
+                ("--weight", weight.is_some()),
+                ("--threads", threads.is_some()),
+                ("--heuristic", heuristic.is_some()),
+                ("--beam-width", beam_width.is_some()),
+                ("--cost", cost.is_some()),
+            ];
+            for (option, used) in used_options {
+                let accepted = search::SOLVER_OPTIONS
+                    .iter()
+                    .any(|&(o, name)| o == option && name == algorithm);
+                if used && !accepted {
+                    unsupported(option, &algorithm);
+                }
+            }
+            let used_features = [
+                ("--stats", stats, Feature::Stats),
+                ("--verbose", verbose, Feature::Progress),
+                ("--timeout", timeout.is_some(), Feature::Limits),
+                ("--max-nodes", max_nodes.is_some(), Feature::Limits),
+                ("--freeze", freeze.is_some(), Feature::Constraints),
+                ("--slide", slide, Feature::Constraints),
+                ("--trace", trace.is_some(), Feature::Trace),
+            ];
+            for (option, used, feature) in used_features {
+                if used && !solver.supports(feature) {
+                    unsupported(option, &algorithm);
+                }
+            }
+            if all_solutions && !solver.is_optimal() {
+                unsupported("--all-solutions", &algorithm);
+            }
+            if tie_break.is_some() && !solver.is_optimal() {
+                unsupported("--tie-break", &algorithm);
+            }
+            let limits = SearchLimits {
+                max_duration: timeout.map(Duration::from_secs_f64),
+                max_nodes,
+            };
+            let cancel = Arc::<AtomicBool>::default();
+            if solver.supports(Feature::Cancel) {
+                // Stop the search on Ctrl-C, so that partial stats are still written
+                let cancel = cancel.clone();
+                ctrlc::set_handler(move || cancel.store(true, atomic::Ordering::Relaxed))
+                    .expect("Failed to set Ctrl-C handler");
+            }
+            let label = if algorithm == "iddfs" {
+                "depth"
+            } else {
+                "f-limit"
+            };
+            // The limit of the last iteration is reported if the search is interrupted
+            let last_limit = Cell::new(None);
+            let mut on_progress = |event: ProgressEvent| {
+                last_limit.set(Some(event.limit));
+                if verbose {
+                    search::print_progress(label, event);
+                }
+            };
+            let constraints = SearchConstraints {
+                frozen: freeze.unwrap_or_default(),
+                semantics: if slide {
+                    MoveSemantics::SlideToStop
+                } else {
+                    MoveSemantics::SingleStep
+                },
+            };
+            let mut collector = TraceCollector::default();
+            let mut context = SolveContext {
+                limits,
+                constraints,
+                cancel,
+                on_progress: &mut on_progress,
+                trace: trace.as_ref().map(|_| &mut collector),
+            };
+            let format = format.unwrap_or(output::OutputFormat::Human);
+            if format == output::OutputFormat::Json {
+                for (option, used) in [("--all-solutions", all_solutions), ("--slide", slide)] {
+                    if used {
+                        unsupported(option, "--format json");
+                    }
+                }
+            }
+            let mut board = read_board_or_inline(input, board, format.input_format())?;
+            let start_board = board.clone();
+            let prefix = match prefix_moves {
+                Some(path) => {
+                    let moves = read_moves(path.clone())?;
+                    board
+                        .apply_moves(&moves)
+                        .map_err(|e| CliError::InvalidMoves {
+                            path,
+                            reason: e.to_string(),
+                        })?;
+                    moves
+                }
+                None => vec![],
+            };
             let mut output = get_output(output)?;
-            match search::execute(algorithm, board) {
+            let (outcome, search_stats) = search::execute(solver, board.clone(), &mut context);
+            let nodes_expanded = search_stats.as_ref().map(|stats| stats.nodes_expanded);
+            let search_stats = search_stats.filter(|_| stats);
+            if let Some(trace) = trace {
+                let trace_file = fs::File::create(&trace).map_err(CliError::create(trace))?;
+                let mut trace_output = BufWriter::new(trace_file);
+                collector.write_dot(&mut trace_output)?;
+            }
+            let code = exit_code(&outcome);
+            let writer = format.writer(quiet);
+            match outcome {
+                SearchOutcome::Solved(mut moves) => {
+                    if tie_break == Some(search::TieBreak::FewestBlocks) {
+                        moves = optimal_min_blocks(board.clone()).unwrap_or(moves);
+                    }
+                    let mut solutions = vec![];
+                    if all_solutions {
+                        solutions = all_optimal(board.clone(), cap.unwrap_or(100));
+                    }
+                    if solutions.is_empty() {
+                        solutions.push(moves);
+                    }
+                    for solution in &mut solutions {
+                        solution.splice(0..0, prefix.iter().copied());
+                    }
+                    let total_cost = match cost {
+                        Some(cost) => cost.build().total_cost(&start_board, &solutions[0]).ok(),
+                        None => None,
+                    };
+                    let solution = output::Solution {
+                        duration: start.elapsed(),
+                        solutions: &solutions,
+                        slide_from: slide.then_some(&board),
+                        cost: total_cost,
+                        optimal: solver.is_optimal(),
+                        prefix_len: prefix.len(),
+                        stats: search_stats.as_ref(),
+                    };
+                    writer.write_solution(&solution, &mut output)?;
+                }
+                SearchOutcome::Cancelled | SearchOutcome::LimitReached => {
+                    let interruption = output::Interruption {
+                        outcome: &outcome,
+                        duration: start.elapsed(),
+                        limit: last_limit.get(),
+                        nodes_expanded,
+                        stats: search_stats.as_ref(),
+                    };
+                    writer.write_interrupted(&interruption, &mut output)?;
+                }
+                outcome => writer.write_failure(&outcome, search_stats.as_ref(), &mut output)?,
+            }
+            output.flush()?;
+            Ok(code)
+        }
+        Command::Hint { input, max_depth } => {
+            let board = read_board(input, io::Format::Text)?;
+            let max_depth = max_depth.unwrap_or(HINT_MAX_DEPTH);
+            match idastar_bounded(board.clone(), max_depth) {
+                SearchOutcome::Solved(moves) => match moves.first() {
+                    Some(&m) => {
+                        println!("{}", format_move(m));
+                        println!("{} moves remaining", moves.len());
+                    }
+                    None => println!("already solved"),
+                },
+                SearchOutcome::LimitReached => match hint_with_depth(&board, max_depth) {
+                    Some(m) => {
+                        println!("{}", format_move(m));
+                        println!("more than {} moves remaining", max_depth);
+                    }
+                    None => {
+                        println!("no solution");
+                        return Ok(EXIT_NO_SOLUTION);
+                    }
+                },
+                _ => {
+                    println!("no solution");
+                    return Ok(EXIT_NO_SOLUTION);
+                }
+            }
+            Ok(EXIT_SUCCESS)
+        }
+        Command::Verify { input, moves } => {
+            let board = read_board(input, io::Format::Text)?;
+            let moves = read_moves(moves)?;
+            match board.verify_solution(&moves) {
+                Ok(report) if report.reached_goal => {
+                    println!("OK: goal reached in {} moves", report.move_count);
+                    Ok(EXIT_SUCCESS)
+                }
+                Ok(report) => {
+                    println!("goal not reached after {} moves", report.move_count);
+                    Ok(EXIT_GOAL_NOT_REACHED)
+                }
+                Err(e) => {
+                    println!("{} ({})", e, format_move(moves[e.index]));
+                    Ok(EXIT_NO_SOLUTION)
+                }
+            }
+        }
+        Command::Canonicalize {
+            input,
+            output,
+            print_mapping,
+        } => {
+            let board = read_board(input, io::Format::Text)?;
+            let (board, mapping) = board.normalize_ids();
+            let mut output = get_output(output)?;
+            write!(output, "{}", board.to_string_with_goal())?;
+            output.flush()?;
+            if print_mapping {
+                let mut mapping = mapping.into_iter().collect::<Vec<_>>();
+                mapping.sort_unstable();
+                for (old, new) in mapping {
+                    eprintln!("{} -> {}", old, new);
+                }
+            }
+            Ok(EXIT_SUCCESS)
+        }
+        Command::Difficulty {
+            input,
+            thresholds,
+            max_nodes,
+        } => {
+            let thresholds = match thresholds[..] {
+                [medium, hard] if medium <= hard => [medium, hard],
+                _ => Cli::command()
+                    .error(
+                        ErrorKind::InvalidValue,
+                        "--thresholds expects two ascending scores, e.g. 5,12",
+                    )
+                    .exit(),
+            };
+            let board = read_board(input, io::Format::Text)?;
+            let report = estimate_difficulty_with_cap(&board, max_nodes);
+            println!("Length = {}", report.length);
+            println!("Nodes expanded = {}", report.nodes_expanded);
+            println!("Branching factor = {}", report.branching_factor);
+            println!("Score = {:.2}", report.score);
+            println!("Difficulty = {}", report.label(thresholds));
+            Ok(EXIT_SUCCESS)
+        }
+        Command::Play { input, board } => {
+            if !std::io::stdin().is_terminal() || input.as_deref() == Some("-") {
+                Cli::command()
+                    .error(ErrorKind::InvalidValue, "play needs stdin to be a terminal")
+                    .exit();
+            }
+            let board = read_board_or_inline(input, board, io::Format::Text)?;
+            // Find the optimal length in the background, it is shown once the goal is reached
+            let (sender, receiver) = mpsc::channel();
+            let initial = board.clone();
+            thread::spawn(move || {
+                let moves = idastar_bounded(initial, HINT_MAX_DEPTH).into_moves();
+                sender.send(moves.map(|moves| moves.len())).ok();
+            });
+            let mut session = play::PlaySession::new(board);
+            play::run(
+                &mut session,
+                RenderStyle::for_terminal(std::io::stdout().is_terminal()),
+                &|| receiver.try_recv().ok().flatten(),
+                &mut std::io::stdin().lock(),
+                &mut std::io::stdout(),
+            )?;
+            Ok(EXIT_SUCCESS)
+        }
+        Command::Render {
+            input,
+            output,
+            moves,
+            cell_size,
+            frame_secs,
+        } => {
+            let board = read_board(input, io::Format::Text)?;
+            let options = SvgOptions {
+                cell_size,
+                frame_secs,
+                ..SvgOptions::default()
+            };
+            let svg = match moves {
                 Some(moves) => {
-                    let duration = start.elapsed();
-                    write_success_result(duration, moves, &mut output)?;
+                    let moves = read_moves(moves)?;
+                    match svg::solution_to_svg(&board, &moves, &options) {
+                        Ok(svg) => svg,
+                        Err(e) => {
+                            eprintln!("{} ({})", e, format_move(moves[e.index]));
+                            return Ok(EXIT_NO_SOLUTION);
+                        }
+                    }
                 }
-                None => {
-                    write_fail_result(&mut output)?;
+                None => svg::board_to_svg(&board, &options),
+            };
+            let mut output = get_output(output)?;
+            output.write_all(svg.as_bytes())?;
+            output.flush()?;
+            Ok(EXIT_SUCCESS)
+        }
+        Command::Replay {
+            input,
+            moves,
+            delay_ms,
+            step,
+            no_clear,
+        } => {
+            let mut board = read_board(input, io::Format::Text)?;
+            let moves = read_moves(moves)?;
+            let options = replay::Options {
+                delay: Duration::from_millis(delay_ms),
+                step,
+                clear: !no_clear,
+                style: RenderStyle::for_terminal(std::io::stdout().is_terminal()),
+            };
+            let result = replay::run(
+                &mut board,
+                &moves,
+                &options,
+                &mut std::io::stdin().lock(),
+                &mut std::io::stdout(),
+            )?;
+            if let Err(e) = result {
+                eprintln!("{} ({})", e, format_move(moves[e.index]));
+                return Ok(EXIT_NO_SOLUTION);
+            }
+            Ok(EXIT_SUCCESS)
+        }
+        Command::Compare {
+            input,
+            algorithms,
+            timeout,
+            parallel,
+            format,
+        } => {
+            let solvers = search::registry(&search::Params::default());
+            for algorithm in &algorithms {
+                find_solver(&solvers, algorithm);
+                if algorithm == "manual" {
+                    unsupported("compare", algorithm);
                 }
             }
+            let board = read_board(input, io::Format::Text)?;
+            let limits = SearchLimits {
+                max_duration: timeout.map(Duration::from_secs_f64),
+                max_nodes: None,
+            };
+            let rows = compare::run(&board, &algorithms, limits, parallel);
+            let mut output = std::io::stdout();
+            compare::write(&rows, format, &mut output)?;
+            if let Some(warning) = compare::check_optimal_lengths(&rows) {
+                eprintln!("warning: {}", warning);
+            }
+            Ok(EXIT_SUCCESS)
+        }
+        Command::Analyze {
+            input,
+            board,
+            solve,
+            format,
+        } => {
+            let format = format.unwrap_or(io::Format::Text);
+            let board = read_board_or_inline(input, board, format)?;
+            let analysis = analyze::analyze(&board, solve);
+            let mut output = std::io::stdout();
+            match format {
+                io::Format::Text => analysis.write(&mut output)?,
+                io::Format::Json => writeln!(output, "{}", analysis.to_json())?,
+            }
+            Ok(EXIT_SUCCESS)
+        }
+        Command::Batch {
+            input_dir,
+            output_dir,
+            algorithm,
+            jobs,
+        } => {
+            let params = search::Params::default();
+            // Only validate the algorithm here, each board builds its own solver
+            find_solver(&search::registry(&params), &algorithm);
+            if algorithm == "manual" {
+                unsupported("batch", &algorithm);
+            }
+            let summary = batch::run(
+                input_dir.as_ref(),
+                output_dir.as_ref(),
+                &algorithm,
+                &params,
+                jobs,
+                quiet,
+            )?;
+            summary.write(&mut std::io::stdout())?;
+            if summary.skipped > 0 {
+                return Err(CliError::SkippedBoards(summary.skipped));
+            }
+            Ok(EXIT_SUCCESS)
         }
         Command::Generate {
             output,
             size,
             block_count,
             shuffle_round,
+            output_pattern,
+            count,
+            seed,
+            format,
+            comment,
         } => {
-            let board = Board::generate(size, block_count, shuffle_round);
-            // Write to output file
-            let mut output = get_output(output)?;
-            writeln!(output, "{}", board)?;
+            // Always seed explicitly, so that any run can be reproduced later
+            let seed = seed.unwrap_or_else(|| {
+                let seed = rand::random();
+                eprintln!("seed: {}", seed);
+                seed
+            });
+            let format = format.unwrap_or(io::Format::Text);
+            let output_dir = output.as_ref().filter(|output| {
+                output.ends_with(std::path::MAIN_SEPARATOR) || Path::new(output).is_dir()
+            });
+            let pattern = match (output_dir, output_pattern) {
+                (Some(dir), pattern) => Some(
+                    Path::new(dir)
+                        .join(pattern.as_deref().unwrap_or("board_{i}.txt"))
+                        .display()
+                        .to_string(),
+                ),
+                (None, pattern) => pattern,
+            };
+            if let Some(dir) = output_dir {
+                fs::create_dir_all(dir).map_err(CliError::create(dir))?;
+            }
+            let mut output = match pattern {
+                Some(_) => None,
+                None => Some(get_output(output)?),
+            };
+            for i in 0..count {
+                let seed = seed.wrapping_add(i);
+                let mut rng = StdRng::seed_from_u64(seed);
+                let board =
+                    match Board::generate_with_rng(size, block_count, shuffle_round, &mut rng) {
+                        Ok(board) => board,
+                        Err(e) => Cli::command()
+                            .error(ErrorKind::InvalidValue, e.to_string())
+                            .exit(),
+                    };
+                match (&mut output, &pattern) {
+                    (Some(output), _) => {
+                        // A text board already ends with a blank line
+                        if i > 0 && format == io::Format::Json {
+                            writeln!(output)?;
+                        }
+                        write_board(&board, seed, format, comment.as_deref(), output)?;
+                    }
+                    (None, Some(pattern)) => {
+                        let path = pattern.replace("{i}", &i.to_string());
+                        let mut output = get_output(Some(path))?;
+                        write_board(&board, seed, format, comment.as_deref(), &mut output)?;
+                        output.flush()?;
+                    }
+                    (None, None) => unreachable!("Boards are written to a file or a pattern"),
+                }
+            }
+            if let Some(output) = &mut output {
+                output.flush()?;
+            }
+            if count > 1 {
+                eprintln!("Generated {} boards", count);
+            }
+            Ok(EXIT_SUCCESS)
         }
     }
+}
 
-    if cli.print_malloc_stats {
-        print_malloc_stats();
+/// Write a generated board, the text format records its seed and `comment` as `#` lines
+fn write_board(
+    board: &Board,
+    seed: u64,
+    format: io::Format,
+    comment: Option<&str>,
+    output: &mut dyn Write,
+) -> std::io::Result<()> {
+    match format {
+        io::Format::Text => {
+            let mut header = format!("seed: {}", seed);
+            if let Some(comment) = comment {
+                header.push('\n');
+                header.push_str(comment);
+            }
+            writeln!(output, "{}", board.to_string_with_comment(&header))
+        }
+        io::Format::Json => writeln!(output, "{}", io::board_to_json(board)),
     }
+}
+
+/// Exit status of a search, an interrupted search is told apart from one without solution
+fn exit_code(outcome: &SearchOutcome) -> i32 {
+    match outcome {
+        SearchOutcome::Solved(_) => EXIT_SUCCESS,
+        SearchOutcome::Cancelled | SearchOutcome::LimitReached => EXIT_INTERRUPTED,
+        SearchOutcome::Unsolvable | SearchOutcome::NoSolution => EXIT_NO_SOLUTION,
+    }
+}
 
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        let size = |arg: &str| {
+            let cli = Cli::try_parse_from(["sliding-puzzle", "generate", "-n", "3", "--size", arg])
+                .map_err(|e| e.to_string())?;
+            match cli.command {
+                Command::Generate { size, .. } => Ok(size),
+                _ => Err("Not a generate command".to_string()),
+            }
+        };
+
+        assert_eq!(size("100,100"), Ok(Vec2::new(100, 100)));
+        assert_eq!(size("4x2"), Ok(Vec2::new(4, 2)));
+        assert!(size("4,y").unwrap_err().contains("cannot parse y"));
+    }
 }
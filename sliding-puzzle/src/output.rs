@@ -0,0 +1,449 @@
+use crate::io;
+use clap::ArgEnum;
+use sliding_puzzle_core::{format_move, Board, Move};
+use sliding_puzzle_search::search::{SearchOutcome, SearchStats};
+use std::{fmt::Display, io::Write, time::Duration};
+
+/// Format of search results
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ArgEnum)]
+pub enum OutputFormat {
+    /// Run time, statistics and a summary before the moves
+    #[clap(alias = "text")]
+    Human,
+    /// Only the moves, one solution per line
+    Plain,
+    /// A JSON object, the input board is read as JSON as well
+    Json,
+}
+
+impl OutputFormat {
+    /// Build the writer of this format, `quiet` suppresses the run time
+    pub fn writer(self, quiet: bool) -> Box<dyn ResultWriter> {
+        match self {
+            OutputFormat::Human => Box::new(HumanWriter { quiet }),
+            OutputFormat::Plain => Box::new(PlainWriter),
+            OutputFormat::Json => Box::new(JsonWriter),
+        }
+    }
+
+    /// Format of the input board
+    pub fn input_format(self) -> io::Format {
+        match self {
+            OutputFormat::Json => io::Format::Json,
+            _ => io::Format::Text,
+        }
+    }
+}
+
+/// A successful search
+pub struct Solution<'a> {
+    pub duration: Duration,
+    /// Solutions of the same length, most formats only write the first one
+    pub solutions: &'a [Vec<Move>],
+    /// Write moves as slides from this board with the distance travelled, e.g. "5R3"
+    pub slide_from: Option<&'a Board>,
+    /// Total cost of the first solution
+    pub cost: Option<u32>,
+    /// Whether moves after the prefix are optimal
+    pub optimal: bool,
+    /// Number of leading moves applied before the search, see `--prefix-moves`
+    pub prefix_len: usize,
+    pub stats: Option<&'a SearchStats>,
+}
+
+/// A search stopped by Ctrl-C or a limit before finding a solution
+pub struct Interruption<'a> {
+    /// Either [`SearchOutcome::Cancelled`] or [`SearchOutcome::LimitReached`]
+    pub outcome: &'a SearchOutcome,
+    pub duration: Duration,
+    /// Depth limit of IDDFS or f-limit of IDA* when stopped, if the search reports progress
+    pub limit: Option<i32>,
+    /// Expanded nodes, if the search reports statistics
+    pub nodes_expanded: Option<u64>,
+    /// Statistics to write in full, only set if requested
+    pub stats: Option<&'a SearchStats>,
+}
+
+impl Display for Interruption<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "search interrupted after {:.2} seconds",
+            self.duration.as_secs_f32()
+        )?;
+        if let Some(limit) = self.limit {
+            write!(f, " at depth limit {}", limit)?;
+        }
+        if let Some(nodes_expanded) = self.nodes_expanded {
+            write!(f, ", {} nodes expanded", nodes_expanded)?;
+        }
+        Ok(())
+    }
+}
+
+/// Write the result of a search in some format
+pub trait ResultWriter {
+    fn write_solution(&self, solution: &Solution, output: &mut dyn Write) -> std::io::Result<()>;
+
+    /// Write a search which does not find a solution, `outcome` tells why
+    fn write_failure(
+        &self,
+        outcome: &SearchOutcome,
+        stats: Option<&SearchStats>,
+        output: &mut dyn Write,
+    ) -> std::io::Result<()>;
+
+    /// Write a search stopped by Ctrl-C or a limit
+    fn write_interrupted(
+        &self,
+        interruption: &Interruption,
+        output: &mut dyn Write,
+    ) -> std::io::Result<()>;
+}
+
+/// Format moves separated by spaces
+fn format_moves(solution: &Solution, moves: &[Move]) -> String {
+    match solution.slide_from {
+        Some(board) => format_slides(board.clone(), moves),
+        None => moves
+            .iter()
+            .map(|&m| format_move(m))
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+/// Format moves which slide blocks until they stop, with the distance travelled, e.g. "5R3"
+fn format_slides(mut board: Board, moves: &[Move]) -> String {
+    moves
+        .iter()
+        .map(|&(id, dir)| {
+            let distance = board
+                .slide_block(id, dir)
+                .expect("Moves found by search should be valid");
+            format!("{}{}", format_move((id, dir)), distance)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn write_stats(stats: &SearchStats, output: &mut dyn Write) -> std::io::Result<()> {
+    writeln!(output, "Nodes expanded = {}.", stats.nodes_expanded)?;
+    writeln!(output, "Max depth = {}.", stats.max_depth)?;
+    writeln!(output, "Iterations = {}.", stats.iterations)?;
+    Ok(())
+}
+
+/// The original output, a header followed by each solution on its own line
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HumanWriter {
+    /// Do not write the run time
+    pub quiet: bool,
+}
+
+impl ResultWriter for HumanWriter {
+    fn write_solution(&self, solution: &Solution, output: &mut dyn Write) -> std::io::Result<()> {
+        if !self.quiet {
+            writeln!(
+                output,
+                "Total run time = {:.4} seconds.",
+                solution.duration.as_secs_f32()
+            )?;
+        }
+        if let Some(stats) = solution.stats {
+            write_stats(stats, output)?;
+        }
+        let length = solution.solutions.first().map_or(0, Vec::len);
+        let summary = match solution.cost {
+            Some(cost) => format!("{} moves with total cost {}", length, cost),
+            None => format!("{} moves", length),
+        };
+        if solution.prefix_len > 0 {
+            let continuation = if solution.optimal {
+                "optimal"
+            } else {
+                "found by search"
+            };
+            writeln!(
+                output,
+                "A solution has {}, the first {} pre-applied and the rest {}:",
+                summary, solution.prefix_len, continuation
+            )?;
+        } else if solution.optimal {
+            writeln!(output, "An optimal solution has {}:", summary)?;
+        } else {
+            writeln!(output, "A solution has {}:", summary)?;
+        }
+        for moves in solution.solutions {
+            // Keep the trailing space of the original format
+            writeln!(output, "{} ", format_moves(solution, moves))?;
+        }
+        Ok(())
+    }
+
+    fn write_failure(
+        &self,
+        outcome: &SearchOutcome,
+        stats: Option<&SearchStats>,
+        output: &mut dyn Write,
+    ) -> std::io::Result<()> {
+        match outcome {
+            SearchOutcome::Unsolvable => writeln!(output, "no solution (proved by pre-check)")?,
+            SearchOutcome::NoSolution => writeln!(output, "no solution")?,
+            SearchOutcome::LimitReached => writeln!(output, "search aborted: limit reached")?,
+            SearchOutcome::Cancelled => writeln!(output, "search cancelled")?,
+            SearchOutcome::Solved(_) => unreachable!("A solved search is not a failure"),
+        }
+        if let Some(stats) = stats {
+            write_stats(stats, output)?;
+        }
+        Ok(())
+    }
+
+    fn write_interrupted(
+        &self,
+        interruption: &Interruption,
+        output: &mut dyn Write,
+    ) -> std::io::Result<()> {
+        if *interruption.outcome == SearchOutcome::LimitReached {
+            writeln!(output, "search aborted: limit reached")?;
+        }
+        writeln!(output, "{}", interruption)?;
+        if let Some(stats) = interruption.stats {
+            write_stats(stats, output)?;
+        }
+        Ok(())
+    }
+}
+
+/// Only the moves, so that scripts need no parsing
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PlainWriter;
+
+impl ResultWriter for PlainWriter {
+    fn write_solution(&self, solution: &Solution, output: &mut dyn Write) -> std::io::Result<()> {
+        for moves in solution.solutions {
+            writeln!(output, "{}", format_moves(solution, moves))?;
+        }
+        Ok(())
+    }
+
+    fn write_failure(
+        &self,
+        _outcome: &SearchOutcome,
+        _stats: Option<&SearchStats>,
+        output: &mut dyn Write,
+    ) -> std::io::Result<()> {
+        writeln!(output, "no solution")
+    }
+
+    fn write_interrupted(
+        &self,
+        _interruption: &Interruption,
+        output: &mut dyn Write,
+    ) -> std::io::Result<()> {
+        writeln!(output, "no solution")
+    }
+}
+
+/// A JSON object per search, see [`io::solution_to_json`]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct JsonWriter;
+
+impl ResultWriter for JsonWriter {
+    fn write_solution(&self, solution: &Solution, output: &mut dyn Write) -> std::io::Result<()> {
+        let moves = solution.solutions.first().map_or(&[][..], Vec::as_slice);
+        let mut result = io::solution_to_json(moves, solution.duration, solution.stats);
+        if solution.prefix_len > 0 {
+            result["prefix_moves"] = serde_json::json!(solution.prefix_len);
+        }
+        writeln!(output, "{}", result)
+    }
+
+    fn write_failure(
+        &self,
+        outcome: &SearchOutcome,
+        stats: Option<&SearchStats>,
+        output: &mut dyn Write,
+    ) -> std::io::Result<()> {
+        let reason = match outcome {
+            SearchOutcome::LimitReached => Some("limit reached"),
+            SearchOutcome::Cancelled => Some("cancelled"),
+            _ => None,
+        };
+        writeln!(output, "{}", io::failure_to_json(reason, stats))
+    }
+
+    fn write_interrupted(
+        &self,
+        interruption: &Interruption,
+        output: &mut dyn Write,
+    ) -> std::io::Result<()> {
+        let reason = match interruption.outcome {
+            SearchOutcome::LimitReached => "limit reached",
+            _ => "cancelled",
+        };
+        let mut result = io::failure_to_json(Some(reason), interruption.stats);
+        result["runtime_secs"] = serde_json::json!(interruption.duration.as_secs_f64());
+        if let Some(limit) = interruption.limit {
+            result["depth_limit"] = serde_json::json!(limit);
+        }
+        if let Some(nodes_expanded) = interruption.nodes_expanded {
+            result["nodes_expanded"] = serde_json::json!(nodes_expanded);
+        }
+        writeln!(output, "{}", result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sliding_puzzle_core::Dir;
+
+    fn solution(solutions: &[Vec<Move>]) -> Solution<'_> {
+        Solution {
+            duration: Duration::from_millis(1500),
+            solutions,
+            slide_from: None,
+            cost: None,
+            optimal: true,
+            prefix_len: 0,
+            stats: None,
+        }
+    }
+
+    fn written(write: impl FnOnce(&mut dyn Write) -> std::io::Result<()>) -> String {
+        let mut output = Vec::<u8>::new();
+        write(&mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_human_writer() {
+        let solutions = [vec![(1, Dir::Up), (2, Dir::Left)]];
+        let writer = HumanWriter { quiet: false };
+        assert_eq!(
+            written(|output| writer.write_solution(&solution(&solutions), output)),
+            "Total run time = 1.5000 seconds.\nAn optimal solution has 2 moves:\n1U 2L \n"
+        );
+
+        let stats = SearchStats {
+            nodes_expanded: 7,
+            max_depth: 2,
+            iterations: 3,
+            ..Default::default()
+        };
+        let quiet = HumanWriter { quiet: true };
+        let mut solution = solution(&solutions);
+        solution.stats = Some(&stats);
+        solution.cost = Some(4);
+        solution.optimal = false;
+        assert_eq!(
+            written(|output| quiet.write_solution(&solution, output)),
+            "Nodes expanded = 7.\nMax depth = 2.\nIterations = 3.\n\
+            A solution has 2 moves with total cost 4:\n1U 2L \n"
+        );
+
+        let prefixed = Solution {
+            prefix_len: 1,
+            ..self::solution(&solutions)
+        };
+        assert_eq!(
+            written(|output| quiet.write_solution(&prefixed, output)),
+            "A solution has 2 moves, the first 1 pre-applied and the rest optimal:\n1U 2L \n"
+        );
+
+        assert_eq!(
+            written(|output| writer.write_failure(&SearchOutcome::Unsolvable, None, output)),
+            "no solution (proved by pre-check)\n"
+        );
+        assert_eq!(
+            written(|output| writer.write_failure(&SearchOutcome::LimitReached, None, output)),
+            "search aborted: limit reached\n"
+        );
+    }
+
+    #[test]
+    fn test_write_interrupted() {
+        let stats = SearchStats {
+            nodes_expanded: 1234,
+            max_depth: 9,
+            iterations: 5,
+            ..Default::default()
+        };
+        let mut interruption = Interruption {
+            outcome: &SearchOutcome::Cancelled,
+            duration: Duration::from_millis(2500),
+            limit: Some(9),
+            nodes_expanded: Some(1234),
+            stats: None,
+        };
+        let writer = HumanWriter::default();
+        assert_eq!(
+            written(|output| writer.write_interrupted(&interruption, output)),
+            "search interrupted after 2.50 seconds at depth limit 9, 1234 nodes expanded\n"
+        );
+        assert_eq!(
+            written(|output| PlainWriter.write_interrupted(&interruption, output)),
+            "no solution\n"
+        );
+        let json = written(|output| JsonWriter.write_interrupted(&interruption, output));
+        let json = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+        assert_eq!(json["reason"], "cancelled");
+        assert_eq!(json["depth_limit"], 9);
+        assert_eq!(json["nodes_expanded"], 1234);
+
+        interruption.outcome = &SearchOutcome::LimitReached;
+        interruption.limit = None;
+        interruption.stats = Some(&stats);
+        assert_eq!(
+            written(|output| writer.write_interrupted(&interruption, output)),
+            "search aborted: limit reached\n\
+            search interrupted after 2.50 seconds, 1234 nodes expanded\n\
+            Nodes expanded = 1234.\nMax depth = 9.\nIterations = 5.\n"
+        );
+    }
+
+    #[test]
+    fn test_plain_writer() -> Result<(), String> {
+        let solutions = [vec![(1, Dir::Up), (2, Dir::Left)], vec![(2, Dir::Left)]];
+        assert_eq!(
+            written(|output| PlainWriter.write_solution(&solution(&solutions), output)),
+            "1U 2L\n2L\n"
+        );
+        assert_eq!(
+            written(|output| PlainWriter.write_solution(&solution(&[vec![]]), output)),
+            "\n"
+        );
+        assert_eq!(
+            written(|output| PlainWriter.write_failure(&SearchOutcome::Cancelled, None, output)),
+            "no solution\n"
+        );
+
+        // Slides are written with the distance
+        let board = "1 3\n0 0 1\n".parse::<Board>()?;
+        let solutions = [vec![(1, Dir::Left)]];
+        let mut solution = solution(&solutions);
+        solution.slide_from = Some(&board);
+        assert_eq!(
+            written(|output| PlainWriter.write_solution(&solution, output)),
+            "1L2\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_writer() {
+        let solutions = [vec![(3, Dir::Down)]];
+        let json = written(|output| JsonWriter.write_solution(&solution(&solutions), output));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+            io::solution_to_json(&solutions[0], Duration::from_millis(1500), None)
+        );
+        assert_eq!(
+            written(|output| JsonWriter.write_failure(&SearchOutcome::Cancelled, None, output)),
+            "{\"reason\":\"cancelled\",\"solved\":false}\n"
+        );
+    }
+}
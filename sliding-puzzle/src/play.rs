@@ -0,0 +1,381 @@
+use sliding_puzzle_core::{format_move, BlockId, Board, Dir, RenderStyle};
+use sliding_puzzle_search::search;
+use std::io::{self, Read, Write};
+
+/// Escape sequence which clears the terminal and moves the cursor to the top left corner
+const CLEAR: &str = "\x1b[2J\x1b[H";
+
+const KEYS_HELP: &str = "Keys: digits/Tab select, arrows move, u undo, r reset, h hint, q quit";
+
+/// A key press the play mode understands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Tab,
+    Arrow(Dir),
+}
+
+/// What the caller should do after a key is handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Continue,
+    Quit,
+}
+
+/// State of an interactive game, independent of the terminal
+#[derive(Debug, Clone)]
+pub struct PlaySession {
+    initial: Board,
+    board: Board,
+    selected: Option<BlockId>,
+    /// Digits typed since the last non-digit key, which select a block by id
+    typed: String,
+    /// Feedback of the latest key, e.g. why a move is illegal
+    message: Option<String>,
+    /// Optimal solution length of the initial board, if known
+    optimal: Option<usize>,
+}
+
+impl PlaySession {
+    pub fn new(mut board: Board) -> Self {
+        board.reset_move_count();
+        Self {
+            initial: board.clone(),
+            board,
+            selected: None,
+            typed: String::new(),
+            message: None,
+            optimal: None,
+        }
+    }
+
+    pub fn set_optimal(&mut self, optimal: usize) {
+        self.optimal = Some(optimal);
+    }
+
+    fn has_block(&self, id: BlockId) -> bool {
+        self.board.blocks().iter().any(|block| block.id() == id)
+    }
+
+    /// Select block `id`, fail if there is no such block
+    pub fn select(&mut self, id: BlockId) -> Result<(), String> {
+        if !self.has_block(id) {
+            return Err(format!("no block {}", id));
+        }
+        self.selected = Some(id);
+        Ok(())
+    }
+
+    /// Select the block with the next larger id, wrapping around to the smallest one
+    pub fn cycle_selection(&mut self) {
+        let mut ids = self
+            .board
+            .blocks()
+            .iter()
+            .map(|block| block.id())
+            .collect::<Vec<_>>();
+        ids.sort_unstable();
+        self.selected = match self.selected {
+            Some(selected) => ids
+                .iter()
+                .copied()
+                .find(|&id| id > selected)
+                .or_else(|| ids.first().copied()),
+            None => ids.first().copied(),
+        };
+    }
+
+    /// Move the selected block, which can be undone
+    pub fn move_selected(&mut self, dir: Dir) -> Result<(), String> {
+        let id = self.selected.ok_or("no block is selected")?;
+        self.board
+            .push_move(id, dir)
+            .map_err(|e| format!("cannot move {}: {}", format_move((id, dir)), e))
+    }
+
+    /// Undo the latest move, return `false` if there is none
+    pub fn undo(&mut self) -> bool {
+        self.board.undo().is_some()
+    }
+
+    /// Go back to the initial board, the selection is kept
+    pub fn reset(&mut self) {
+        self.board = self.initial.clone();
+    }
+
+    /// Select the block of the best next move and describe it
+    pub fn hint(&mut self) -> String {
+        match search::hint(&self.board) {
+            Some((id, dir)) => {
+                self.selected = Some(id);
+                format!("hint: {}", format_move((id, dir)))
+            }
+            None => "no hint, the board cannot be solved quickly".to_string(),
+        }
+    }
+
+    pub fn is_goal(&self) -> bool {
+        self.board.is_goal()
+    }
+
+    /// Apply a key press
+    pub fn handle(&mut self, key: Key) -> Action {
+        self.message = None;
+        if let Key::Char(c @ '0'..='9') = key {
+            self.typed.push(c);
+            // Start over from this digit if the typed id does not exist, e.g. 1 then 5
+            // on a board without block 15 selects block 5
+            for typed in [self.typed.clone(), c.to_string()] {
+                if let Ok(id) = typed.parse() {
+                    if self.select(id).is_ok() {
+                        self.typed = typed;
+                        return Action::Continue;
+                    }
+                }
+            }
+            self.typed = c.to_string();
+            self.message = Some(format!("no block {}", c));
+            return Action::Continue;
+        }
+        self.typed.clear();
+        match key {
+            Key::Tab => self.cycle_selection(),
+            Key::Arrow(dir) => {
+                if let Err(e) = self.move_selected(dir) {
+                    self.message = Some(e);
+                }
+            }
+            Key::Char('u') => {
+                if !self.undo() {
+                    self.message = Some("nothing to undo".to_string());
+                }
+            }
+            Key::Char('r') => self.reset(),
+            Key::Char('h') => self.message = Some(self.hint()),
+            Key::Char('q') => return Action::Quit,
+            Key::Char(_) => self.message = Some(KEYS_HELP.to_string()),
+        }
+        Action::Continue
+    }
+
+    /// Describe the result once the goal is reached
+    pub fn summary(&self) -> String {
+        let moves = self.board.move_count();
+        match self.optimal {
+            Some(optimal) => format!("Goal reached in {} moves, optimal is {}", moves, optimal),
+            None => format!("Goal reached in {} moves", moves),
+        }
+    }
+
+    /// Whole screen of the game
+    pub fn view(&self, style: RenderStyle) -> String {
+        let mut view = format!("Moves: {}\n\n", self.board.move_count());
+        view.push_str(&self.board.render(style));
+        view.push('\n');
+        match self.selected {
+            Some(id) => view.push_str(&format!("Selected: {}\n", id)),
+            None => view.push_str("Selected: none\n"),
+        }
+        view.push_str(self.message.as_deref().unwrap_or(KEYS_HELP));
+        view.push('\n');
+        view
+    }
+}
+
+/// Read a key from a terminal in raw mode, `None` at the end of input.
+/// Unknown escape sequences are skipped
+pub fn read_key(input: &mut dyn Read) -> io::Result<Option<Key>> {
+    let mut byte = [0u8];
+    loop {
+        if input.read(&mut byte)? == 0 {
+            return Ok(None);
+        }
+        let key = match byte[0] {
+            b'\t' => Key::Tab,
+            // Ctrl-C and Ctrl-D, signals are disabled in raw mode
+            3 | 4 => Key::Char('q'),
+            0x1b => {
+                let mut sequence = [0u8; 2];
+                input.read_exact(&mut sequence)?;
+                match sequence {
+                    [b'[', b'A'] => Key::Arrow(Dir::Up),
+                    [b'[', b'B'] => Key::Arrow(Dir::Down),
+                    [b'[', b'C'] => Key::Arrow(Dir::Right),
+                    [b'[', b'D'] => Key::Arrow(Dir::Left),
+                    _ => continue,
+                }
+            }
+            b if b.is_ascii_graphic() => Key::Char(b as char),
+            _ => continue,
+        };
+        return Ok(Some(key));
+    }
+}
+
+/// Put the terminal in raw mode until dropped
+#[cfg(unix)]
+pub struct RawMode {
+    original: libc::termios,
+}
+
+#[cfg(unix)]
+impl RawMode {
+    pub fn enable() -> io::Result<Self> {
+        let fd = libc::STDIN_FILENO;
+        // SAFETY: termios is plain data filled by tcgetattr before use
+        unsafe {
+            let mut original = std::mem::zeroed::<libc::termios>();
+            if libc::tcgetattr(fd, &mut original) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let mut raw = original;
+            raw.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG);
+            raw.c_cc[libc::VMIN] = 1;
+            raw.c_cc[libc::VTIME] = 0;
+            if libc::tcsetattr(fd, libc::TCSAFLUSH, &raw) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Self { original })
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        // SAFETY: restore the settings read in `enable`
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSAFLUSH, &self.original);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub struct RawMode;
+
+#[cfg(not(unix))]
+impl RawMode {
+    pub fn enable() -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "play is only supported on Unix terminals",
+        ))
+    }
+}
+
+/// Play `session` in the terminal until the goal is reached or the player quits.
+/// `optimal` is polled for the optimal length, which may be computed in the background
+pub fn run(
+    session: &mut PlaySession,
+    style: RenderStyle,
+    optimal: &dyn Fn() -> Option<usize>,
+    input: &mut dyn Read,
+    output: &mut dyn Write,
+) -> io::Result<()> {
+    let _raw_mode = RawMode::enable()?;
+    loop {
+        write!(output, "{}{}", CLEAR, session.view(style))?;
+        output.flush()?;
+        if session.is_goal() {
+            if let Some(optimal) = optimal() {
+                session.set_optimal(optimal);
+            }
+            writeln!(output, "{}", session.summary())?;
+            return Ok(());
+        }
+        let key = match read_key(input)? {
+            Some(key) => key,
+            None => return Ok(()),
+        };
+        if session.handle(key) == Action::Quit {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session() -> PlaySession {
+        let board = "2 3\n0 0 0\n0 2 1\n".parse::<Board>().unwrap();
+        PlaySession::new(board)
+    }
+
+    #[test]
+    fn test_select_and_move() {
+        let mut session = session();
+        assert!(session.move_selected(Dir::Up).is_err());
+        assert!(session.select(3).is_err());
+
+        session.handle(Key::Char('2'));
+        assert_eq!(session.selected, Some(2));
+        session.handle(Key::Arrow(Dir::Up));
+        assert_eq!(session.board.move_count(), 1);
+        session.handle(Key::Arrow(Dir::Up));
+        assert!(session
+            .message
+            .as_deref()
+            .unwrap()
+            .starts_with("cannot move 2U"));
+        assert_eq!(session.board.move_count(), 1);
+
+        session.handle(Key::Tab);
+        assert_eq!(session.selected, Some(1));
+        session.handle(Key::Char('2'));
+        assert_eq!(session.selected, Some(2));
+        // No block 21, so the typed id starts over from 1
+        session.handle(Key::Char('1'));
+        assert_eq!(session.selected, Some(1));
+    }
+
+    #[test]
+    fn test_undo_and_reset() {
+        let mut session = session();
+        assert_eq!(session.handle(Key::Char('u')), Action::Continue);
+        assert_eq!(session.message.as_deref(), Some("nothing to undo"));
+
+        session.select(1).unwrap();
+        session.move_selected(Dir::Up).unwrap();
+        session.move_selected(Dir::Left).unwrap();
+        session.handle(Key::Char('u'));
+        assert_eq!(session.board.move_count(), 1);
+        session.handle(Key::Char('r'));
+        assert_eq!(session.board.move_count(), 0);
+        assert_eq!(session.board, session.initial);
+        assert_eq!(session.handle(Key::Char('q')), Action::Quit);
+    }
+
+    #[test]
+    fn test_hint_reaches_goal() {
+        let mut session = session();
+        for _ in 0..4 {
+            let hint = session.hint();
+            let (id, dir) = search::parse_move(hint.trim_start_matches("hint: ")).unwrap();
+            assert_eq!(session.selected, Some(id));
+            session.move_selected(dir).unwrap();
+        }
+        assert!(session.is_goal());
+        session.set_optimal(4);
+        assert_eq!(session.summary(), "Goal reached in 4 moves, optimal is 4");
+    }
+
+    #[test]
+    fn test_read_key() {
+        let mut input: &[u8] = b"5\t\x1b[A\x1b[D\nq";
+        let mut keys = vec![];
+        while let Some(key) = read_key(&mut input).unwrap() {
+            keys.push(key);
+        }
+        assert_eq!(
+            keys,
+            [
+                Key::Char('5'),
+                Key::Tab,
+                Key::Arrow(Dir::Up),
+                Key::Arrow(Dir::Left),
+                Key::Char('q')
+            ]
+        );
+    }
+}
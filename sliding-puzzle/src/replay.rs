@@ -0,0 +1,66 @@
+use sliding_puzzle_core::{format_move, Board, Move, RenderStyle, VerifyError};
+use std::{
+    io::{BufRead, Write},
+    thread,
+    time::Duration,
+};
+
+/// Escape sequence which clears the terminal and moves the cursor to the top left corner
+const CLEAR: &str = "\x1b[2J\x1b[H";
+
+/// How frames of a replay are shown
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Pause between frames, ignored in step mode
+    pub delay: Duration,
+    /// Wait for a line from input between frames
+    pub step: bool,
+    /// Clear the screen before each frame
+    pub clear: bool,
+    /// How the board is drawn
+    pub style: RenderStyle,
+}
+
+fn write_frame(
+    board: &Board,
+    title: &str,
+    options: &Options,
+    output: &mut dyn Write,
+) -> std::io::Result<()> {
+    if options.clear {
+        write!(output, "{}", CLEAR)?;
+    }
+    writeln!(output, "{}", title)?;
+    writeln!(output, "{}", board.render(options.style))?;
+    output.flush()
+}
+
+/// Play `moves` on `board`, write the board after each move.
+/// Stop at the first illegal move, the board is left at the state before it
+pub fn run(
+    board: &mut Board,
+    moves: &[Move],
+    options: &Options,
+    input: &mut dyn BufRead,
+    output: &mut dyn Write,
+) -> std::io::Result<Result<(), VerifyError>> {
+    write_frame(board, "Start", options, output)?;
+    for (index, &(id, dir)) in moves.iter().enumerate() {
+        if options.step {
+            input.read_line(&mut String::new())?;
+        } else {
+            thread::sleep(options.delay);
+        }
+        if let Err(reason) = board.move_block(id, dir) {
+            return Ok(Err(VerifyError { index, reason }));
+        }
+        let title = format!(
+            "Move {}/{}: {}",
+            index + 1,
+            moves.len(),
+            format_move((id, dir))
+        );
+        write_frame(board, &title, options, output)?;
+    }
+    Ok(Ok(()))
+}
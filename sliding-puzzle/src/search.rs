@@ -1,19 +1,156 @@
 use clap::ArgEnum;
-use sliding_puzzle_core::{Board, Move};
-use sliding_puzzle_search::search;
+use sliding_puzzle_core::{Board, Solvability};
+use sliding_puzzle_search::{
+    heuristic::{
+        Heuristic, LinearConflictHeuristic, ManhattanHeuristic, MatchedHeuristic, ZeroHeuristic,
+    },
+    search::{CostModel, ProgressEvent, SearchOutcome, SearchStats},
+    solver::{
+        AStar, Auto, Beam, Bfs, Dfbnb, Greedy, IdaStar, IdaStarParallel, Iddfs, IddfsParallel,
+        Manual, SolveContext, Solver, WeightedAStar,
+    },
+};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ArgEnum)]
-#[allow(clippy::upper_case_acronyms)]
-pub enum Algorithm {
-    IDDFS,
-    IDAStar,
-    Manual,
-}
-
-pub fn execute(algorithm: Algorithm, board: Board) -> Option<Vec<Move>> {
-    match algorithm {
-        Algorithm::IDDFS => search::iddfs(board),
-        Algorithm::IDAStar => search::idastar(board),
-        Algorithm::Manual => search::manual(board),
+pub enum HeuristicKind {
+    Manhattan,
+    Zero,
+    LinearConflict,
+    Matched,
+}
+
+impl HeuristicKind {
+    pub fn build(&self) -> Box<dyn Heuristic> {
+        match self {
+            HeuristicKind::Manhattan => Box::new(ManhattanHeuristic),
+            HeuristicKind::Zero => Box::new(ZeroHeuristic),
+            HeuristicKind::LinearConflict => Box::new(LinearConflictHeuristic),
+            HeuristicKind::Matched => Box::new(MatchedHeuristic),
+        }
+    }
+}
+
+/// How much each move costs
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ArgEnum)]
+pub enum CostKind {
+    Uniform,
+    CellCount,
+}
+
+impl CostKind {
+    pub fn build(&self) -> CostModel {
+        match self {
+            CostKind::Uniform => CostModel::Uniform,
+            CostKind::CellCount => CostModel::CellCount,
+        }
+    }
+}
+
+/// How to pick among optimal solutions
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ArgEnum)]
+pub enum TieBreak {
+    /// Move the fewest distinct blocks
+    FewestBlocks,
+}
+
+/// Extra parameters used by some algorithms
+#[derive(Debug, Clone)]
+pub struct Params {
+    /// Heuristic weight of weighted A*
+    pub weight: f32,
+    /// Number of worker threads of parallel algorithms
+    pub threads: usize,
+    /// Heuristic used by IDA*
+    pub heuristic: HeuristicKind,
+    /// Cost of moves minimized by A*
+    pub cost: CostKind,
+    /// Number of boards kept at each depth of beam search
+    pub beam_width: usize,
+    /// Maximum depth of beam search
+    pub beam_max_depth: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            weight: 1.0,
+            threads: 4,
+            heuristic: HeuristicKind::Manhattan,
+            cost: CostKind::Uniform,
+            beam_width: 512,
+            beam_max_depth: 1000,
+        }
+    }
+}
+
+/// Options only accepted by a specific solver, as (option, solver name)
+pub const SOLVER_OPTIONS: &[(&str, &str)] = &[
+    ("--weight", "weighted-astar"),
+    ("--threads", "iddfs-parallel"),
+    ("--threads", "ida-star-parallel"),
+    ("--heuristic", "ida-star"),
+    ("--beam-width", "beam"),
+    ("--cost", "astar"),
+];
+
+/// All solvers selectable by `--algorithm`, register new solvers here
+pub fn registry(params: &Params) -> Vec<Box<dyn Solver>> {
+    vec![
+        Box::new(Auto),
+        Box::new(Iddfs),
+        Box::new(IddfsParallel {
+            threads: params.threads,
+        }),
+        Box::new(IdaStar {
+            heuristic: params.heuristic.build(),
+        }),
+        Box::new(IdaStarParallel {
+            threads: params.threads,
+        }),
+        Box::new(AStar {
+            cost: params.cost.build(),
+        }),
+        Box::new(Dfbnb),
+        Box::new(WeightedAStar {
+            weight: params.weight,
+        }),
+        Box::new(Bfs),
+        Box::new(Greedy),
+        Box::new(Beam {
+            width: params.beam_width,
+            max_depth: params.beam_max_depth,
+        }),
+        Box::new(Manual),
+    ]
+}
+
+/// Execute the solver, also return statistics if the solver supports it
+pub fn execute(
+    solver: &dyn Solver,
+    board: Board,
+    context: &mut SolveContext,
+) -> (SearchOutcome, Option<SearchStats>) {
+    if board.quick_solvability() == Solvability::Unsolvable {
+        return (SearchOutcome::Unsolvable, None);
+    }
+    solver.solve_with(board, context)
+}
+
+pub fn print_progress(label: &str, event: ProgressEvent) {
+    eprintln!(
+        "{} {}, {} nodes, {:.1}s",
+        label,
+        event.limit,
+        format_count(event.nodes_expanded),
+        event.elapsed.as_secs_f32()
+    );
+}
+
+/// Format a count in a short human readable form, e.g. 1.3M
+fn format_count(count: u64) -> String {
+    match count {
+        0..=999 => count.to_string(),
+        1_000..=999_999 => format!("{:.1}K", count as f64 / 1e3),
+        _ => format!("{:.1}M", count as f64 / 1e6),
     }
 }
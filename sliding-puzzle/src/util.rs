@@ -1,17 +1,19 @@
-use sliding_puzzle_core::Vec2;
+use sliding_puzzle_core::{BlockId, Move};
+use sliding_puzzle_search::search::parse_move;
+use std::collections::HashSet;
 
-pub fn vec2_from_str(input: &str) -> Result<Vec2, String> {
-    let input = input.split(',').collect::<Vec<_>>();
-
-    if input.len() != 2 {
-        return Err("Input shoud be 2 comma-delimited number. e.g. 4,2".to_string());
-    }
+pub fn ids_from_str(input: &str) -> Result<HashSet<BlockId>, String> {
+    input
+        .split(',')
+        .map(|id| {
+            id.trim()
+                .parse::<BlockId>()
+                .map_err(|e| format!("Cannot parse block id {:?}: {}", id, e))
+        })
+        .collect()
+}
 
-    let x = input[0]
-        .parse::<i8>()
-        .map_err(|e| format!("Cannot parse x: {}", e))?;
-    let y = input[1]
-        .parse::<i8>()
-        .map_err(|e| format!("Cannot parse y: {}", e))?;
-    Ok(Vec2::new(x, y))
+/// Parse whitespace-delimited moves, e.g. 1U 2L
+pub fn moves_from_str(input: &str) -> Result<Vec<Move>, String> {
+    input.split_whitespace().map(parse_move).collect()
 }
@@ -0,0 +1,42 @@
+use std::{fs, path::Path, process::Command};
+
+#[test]
+fn test_batch() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("batch");
+    let (input_dir, output_dir) = (dir.join("boards"), dir.join("solutions"));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&input_dir).unwrap();
+    fs::write(input_dir.join("a.txt"), "2 2\n0 0\n0 1\n").unwrap();
+    fs::write(input_dir.join("b.txt"), "2 2\n1 0\n0 0\n").unwrap();
+    fs::write(input_dir.join("broken.txt"), "2 2\n1 x\n").unwrap();
+    // Only *.txt files are boards
+    fs::write(input_dir.join("notes.md"), "not a board").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_sliding-puzzle"))
+        .arg("batch")
+        .arg("--input-dir")
+        .arg(&input_dir)
+        .arg("--output-dir")
+        .arg(&output_dir)
+        .args(["--algorithm", "ida-star", "--jobs", "2"])
+        .output()
+        .expect("Failed to run sliding-puzzle");
+
+    // The broken board is skipped but fails the run
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("broken.txt"), "{}", stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Solved            = 2"), "{}", stdout);
+    assert!(stdout.contains("Skipped           = 1"), "{}", stdout);
+    assert!(stdout.contains("Mean moves        = 1.00"), "{}", stdout);
+
+    let solution = fs::read_to_string(output_dir.join("a.txt")).unwrap();
+    assert!(
+        solution.contains("An optimal solution has 2 moves:"),
+        "{}",
+        solution
+    );
+    assert!(output_dir.join("b.txt").exists());
+    assert!(!output_dir.join("broken.txt").exists());
+}
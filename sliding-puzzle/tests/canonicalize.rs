@@ -0,0 +1,36 @@
+use std::{fs, path::Path, process::Command};
+
+/// Canonicalize `input` into `output`, return stderr
+fn canonicalize(input: &Path, output: &Path) -> String {
+    let result = Command::new(env!("CARGO_BIN_EXE_sliding-puzzle"))
+        .arg("canonicalize")
+        .arg("--input")
+        .arg(input)
+        .arg("--output")
+        .arg(output)
+        .arg("--print-mapping")
+        .output()
+        .expect("Failed to run sliding-puzzle");
+    assert!(result.status.success());
+    String::from_utf8_lossy(&result.stderr).into_owned()
+}
+
+#[test]
+fn test_canonicalize() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("canonicalize");
+    fs::create_dir_all(&dir).unwrap();
+    let board = dir.join("board.txt");
+    fs::write(&board, "2 3\n3 0 1\n2 2 0\n").unwrap();
+
+    let once = dir.join("once.txt");
+    let mapping = canonicalize(&board, &once);
+    assert_eq!(mapping, "1 -> 2\n2 -> 3\n3 -> 1\n");
+    let canonical = fs::read_to_string(&once).unwrap();
+    assert_eq!(canonical, "2 3\n1 0 2\n3 3 0\n\n2 3 3\n1 0 0\n");
+
+    // Canonicalizing again changes nothing
+    let twice = dir.join("twice.txt");
+    let mapping = canonicalize(&once, &twice);
+    assert_eq!(mapping, "1 -> 1\n2 -> 2\n3 -> 3\n");
+    assert_eq!(fs::read_to_string(&twice).unwrap(), canonical);
+}
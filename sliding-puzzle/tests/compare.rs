@@ -0,0 +1,53 @@
+use std::{fs, path::Path, process::Command};
+
+fn compare(args: &[&str]) -> (String, String) {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("compare");
+    fs::create_dir_all(&dir).unwrap();
+    let board = dir.join("board.txt");
+    fs::write(&board, "2 3\n0 0 0\n0 2 1\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_sliding-puzzle"))
+        .arg("compare")
+        .arg("-i")
+        .arg(&board)
+        .args(args)
+        .output()
+        .expect("Failed to run sliding-puzzle");
+    assert!(output.status.success());
+    (
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    )
+}
+
+#[test]
+fn test_compare_csv() {
+    for parallel in [false, true] {
+        let mut args = vec!["--algorithms", "iddfs,ida-star", "--format", "csv"];
+        if parallel {
+            args.push("--parallel");
+        }
+        let (stdout, stderr) = compare(&args);
+        let rows = stdout
+            .lines()
+            .map(|line| line.split(',').collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0][..3], ["algorithm", "outcome", "moves"]);
+        assert_eq!(rows[1][..3], ["iddfs", "solved", "4"]);
+        assert_eq!(rows[2][..3], ["ida-star", "solved", "4"]);
+        assert!(stderr.is_empty(), "{}", stderr);
+    }
+}
+
+#[test]
+fn test_compare_table() {
+    let (stdout, _) = compare(&["-a", "ida-star,greedy"]);
+    let lines = stdout.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("algorithm "));
+    assert!(lines[1].starts_with("ida-star "));
+    // Columns are aligned
+    assert_eq!(lines[0].len(), lines[1].len());
+    assert_eq!(lines[1].len(), lines[2].len());
+}
@@ -0,0 +1,53 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+/// Run difficulty on `board` piped to stdin, return the exit code and stdout
+fn difficulty(board: &str, args: &[&str]) -> (Option<i32>, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_sliding-puzzle"))
+        .arg("difficulty")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to run sliding-puzzle");
+    // Invalid arguments are rejected before stdin is read, which closes the pipe early
+    let _ = child
+        .stdin
+        .take()
+        .expect("Stdin should be piped")
+        .write_all(board.as_bytes());
+    let output = child.wait_with_output().unwrap();
+    (
+        output.status.code(),
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+    )
+}
+
+#[test]
+fn test_difficulty_buckets() {
+    let (code, stdout) = difficulty("2 2\n1 0\n0 0\n", &[]);
+    assert_eq!(code, Some(0));
+    assert!(stdout.starts_with("Length = 0 moves\n"), "{}", stdout);
+    assert!(stdout.ends_with("Difficulty = easy\n"), "{}", stdout);
+
+    let shuffled = "3 3\n8 6 7\n2 5 4\n3 0 1\n";
+    let (code, stdout) = difficulty(shuffled, &["--max-nodes", "1000"]);
+    assert_eq!(code, Some(0));
+    assert!(stdout.starts_with("Length = at least "), "{}", stdout);
+    assert!(stdout.ends_with("Difficulty = hard\n"), "{}", stdout);
+
+    // Any solvable board is easy if the thresholds are high enough
+    let (_, stdout) = difficulty(shuffled, &["--max-nodes", "1000", "--thresholds", "99,100"]);
+    assert!(stdout.ends_with("Difficulty = easy\n"), "{}", stdout);
+}
+
+#[test]
+fn test_invalid_thresholds() {
+    for thresholds in ["5", "12,5"] {
+        let (code, _) = difficulty("2 2\n1 0\n0 0\n", &["--thresholds", thresholds]);
+        assert_eq!(code, Some(2));
+    }
+}
@@ -0,0 +1,107 @@
+use std::{fs, path::Path, process::Command};
+
+fn sliding_puzzle(args: &[&str]) -> (Option<i32>, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_sliding-puzzle"))
+        .args(args)
+        .output()
+        .expect("Failed to run sliding-puzzle");
+    (
+        output.status.code(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    )
+}
+
+/// Write `content` to a file under a temporary directory, return its path
+fn write_file(name: &str, content: &str) -> String {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("errors");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    fs::write(&path, content).unwrap();
+    path.display().to_string()
+}
+
+#[test]
+fn test_missing_input() {
+    let (code, stderr) = sliding_puzzle(&["search", "-i", "missing.txt"]);
+    assert_eq!(code, Some(2));
+    assert!(
+        stderr.starts_with("error: cannot open 'missing.txt': "),
+        "{}",
+        stderr
+    );
+    assert!(!stderr.contains("panicked"));
+}
+
+#[test]
+fn test_malformed_board() {
+    let path = write_file("malformed.txt", "2 2\n1 0\n0 y\n");
+    let (code, stderr) = sliding_puzzle(&["search", "-i", &path]);
+    assert_eq!(code, Some(2));
+    assert!(
+        stderr.starts_with(&format!(
+            "error: invalid board in '{}': Invalid line 3: ",
+            path
+        )),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_malformed_moves() {
+    let board = write_file("board.txt", "2 2\n0 0\n0 1\n");
+    let moves = write_file("moves.txt", "1U 1X");
+    let (code, stderr) = sliding_puzzle(&["verify", "-i", &board, "-m", &moves]);
+    assert_eq!(code, Some(2));
+    assert!(
+        stderr.starts_with(&format!("error: invalid moves in '{}': ", moves)),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_cannot_create_output() {
+    let board = write_file("board.txt", "2 2\n0 0\n0 1\n");
+    let output = "missing-dir/solution.txt";
+    let (code, stderr) = sliding_puzzle(&["search", "-i", &board, "-o", output]);
+    assert_eq!(code, Some(3));
+    assert!(
+        stderr.starts_with("error: cannot create 'missing-dir/solution.txt': "),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_no_solution() {
+    // Block 2 cannot pass block 1 to reach its goal
+    let board = write_file("unsolvable.txt", "1 3\n2 1 0\n");
+    let (code, stderr) = sliding_puzzle(&["search", "-i", &board]);
+    assert_eq!(code, Some(1));
+    assert!(stderr.is_empty(), "{}", stderr);
+}
+
+#[test]
+fn test_no_solution_with_frozen_blocks() {
+    // Blocks 6 and 7 turn the rest of the board into a ring, around which blocks 1 and 2
+    // cannot pass each other
+    let board = write_file("frozen.txt", "3 4\n2 1 3 4\n5 6 7 8\n9 0 0 0\n");
+    for algorithm in ["iddfs", "ida-star"] {
+        let (code, stderr) =
+            sliding_puzzle(&["search", "-i", &board, "-a", algorithm, "--freeze", "6,7"]);
+        assert_eq!(code, Some(1), "{}", algorithm);
+        assert!(stderr.is_empty(), "{}", stderr);
+    }
+}
+
+#[test]
+fn test_exit_codes_in_help() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sliding-puzzle"))
+        .arg("--help")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("EXIT STATUS:"), "{}", stdout);
+}
@@ -0,0 +1,33 @@
+use std::{fs, path::Path, process::Command};
+
+fn search(args: &[&str]) -> String {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("format");
+    fs::create_dir_all(&dir).unwrap();
+    let board = dir.join("board.txt");
+    fs::write(&board, "1 3\n0 0 1\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_sliding-puzzle"))
+        .arg("search")
+        .arg("-i")
+        .arg(&board)
+        .args(args)
+        .output()
+        .expect("Failed to run sliding-puzzle");
+    assert!(output.status.success());
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn test_plain_format() {
+    assert_eq!(search(&["--format", "plain"]), "1L 1L\n");
+}
+
+#[test]
+fn test_quiet() {
+    assert_eq!(
+        search(&["--quiet"]),
+        "An optimal solution has 2 moves:\n1L 1L \n"
+    );
+    // The original name of the human format is still accepted
+    assert!(search(&["--format", "text"]).starts_with("Total run time = "));
+}
@@ -0,0 +1,100 @@
+use std::process::{Command, Output};
+
+fn generate(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_sliding-puzzle"))
+        .args(["generate", "--size", "5,4", "-n", "6"])
+        .args(args)
+        .output()
+        .expect("Failed to run sliding-puzzle")
+}
+
+#[test]
+fn test_same_seed_same_board() {
+    let first = generate(&["--seed", "42"]);
+    let second = generate(&["--seed", "42"]);
+    assert!(first.status.success());
+    assert_eq!(first.stdout, second.stdout);
+    // An explicit seed is not echoed to stderr
+    assert!(first.stderr.is_empty());
+
+    let stdout = String::from_utf8_lossy(&first.stdout);
+    assert!(stdout.starts_with("# seed: 42\n4 5\n"), "{}", stdout);
+}
+
+#[test]
+fn test_random_seed_is_reproducible() {
+    let first = generate(&["--comment", "from test"]);
+    assert!(first.status.success());
+    let stderr = String::from_utf8_lossy(&first.stderr);
+    let seed = stderr
+        .trim()
+        .strip_prefix("seed: ")
+        .expect("Seed should be printed to stderr");
+    let stdout = String::from_utf8_lossy(&first.stdout);
+    assert!(stdout.starts_with(&format!("# seed: {}\n# from test\n", seed)));
+
+    let second = generate(&["--seed", seed, "--comment", "from test"]);
+    assert_eq!(first.stdout, second.stdout);
+}
+
+#[test]
+fn test_generate_many_into_directory() {
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("generate-many");
+    let _ = std::fs::remove_dir_all(&dir);
+    let output = generate(&[
+        "--seed",
+        "7",
+        "--count",
+        "5",
+        "--output",
+        &format!("{}/", dir.display()),
+    ]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "Generated 5 boards\n"
+    );
+
+    let mut boards = vec![];
+    for i in 0..5 {
+        let content = std::fs::read_to_string(dir.join(format!("board_{}.txt", i))).unwrap();
+        assert!(content.starts_with(&format!("# seed: {}\n", 7 + i)));
+        let board = content
+            .parse::<sliding_puzzle_core::Board>()
+            .expect("Generated board should parse");
+        assert!(!boards.contains(&board));
+        boards.push(board);
+    }
+
+    // Board i is the same as a single board generated with seed + i
+    let single = generate(&["--seed", "9"]);
+    assert_eq!(
+        String::from_utf8_lossy(&single.stdout),
+        std::fs::read_to_string(dir.join("board_2.txt")).unwrap()
+    );
+}
+
+#[test]
+fn test_generate_many_to_stdout() {
+    let output = generate(&["--seed", "7", "--count", "3"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let boards = stdout.split_terminator("\n\n").collect::<Vec<_>>();
+    assert_eq!(boards.len(), 3);
+    for (i, board) in boards.iter().enumerate() {
+        assert!(board.starts_with(&format!("# seed: {}\n", 7 + i)));
+    }
+
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("generate-pattern");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let pattern = dir.join("puzzle-{i}.txt");
+    let output = generate(&[
+        "--count",
+        "2",
+        "--output-pattern",
+        pattern.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    assert!(dir.join("puzzle-0.txt").exists());
+    assert!(dir.join("puzzle-1.txt").exists());
+}
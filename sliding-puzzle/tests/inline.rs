@@ -0,0 +1,59 @@
+use std::process::Command;
+
+fn sliding_puzzle() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_sliding-puzzle"))
+}
+
+#[test]
+fn test_search_inline_board() {
+    let output = sliding_puzzle()
+        .args(["search", "-a", "ida-star", "--board", "2 3;0 0 0;0 2 1"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("An optimal solution has 4 moves:\n"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn test_analyze_inline_board() {
+    let output = sliding_puzzle()
+        .args(["analyze", "--board", "2 3;0 0 0;0 2 1"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("Size = 2x3\n"), "{}", stdout);
+}
+
+#[test]
+fn test_inline_board_conflicts_with_input() {
+    for command in ["search", "analyze"] {
+        let output = sliding_puzzle()
+            .args([command, "-i", "board.txt", "--board", "1 2;1 0"])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(2));
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("cannot be used with"), "{}", stderr);
+    }
+}
+
+#[test]
+fn test_invalid_inline_board() {
+    let output = sliding_puzzle()
+        .args(["search", "--board", "2 3;0 0 0;0 2"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.starts_with("error: invalid board in inline board: "),
+        "{}",
+        stderr
+    );
+}
@@ -0,0 +1,65 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+    time::Duration,
+};
+
+/// A board whose goal is unreachable by parity, which searches cannot tell quickly
+fn unsolvable_board() -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("interrupt");
+    fs::create_dir_all(&dir).unwrap();
+    let board = dir.join("board.txt");
+    fs::write(&board, "3 3\n2 1 3\n4 5 6\n7 8 0\n").unwrap();
+    board
+}
+
+#[test]
+fn test_timeout() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sliding-puzzle"))
+        .arg("search")
+        .arg("-i")
+        .arg(unsolvable_board())
+        .args(["-a", "iddfs", "--timeout", "0.5"])
+        .output()
+        .expect("Failed to run sliding-puzzle");
+    assert_eq!(output.status.code(), Some(4));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.starts_with("search aborted: limit reached\nsearch interrupted after "),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains(" at depth limit "), "{}", stdout);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_ctrl_c() {
+    let child = Command::new(env!("CARGO_BIN_EXE_sliding-puzzle"))
+        .arg("search")
+        .arg("-i")
+        .arg(unsolvable_board())
+        .args(["-a", "ida-star"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to run sliding-puzzle");
+    // Give the search time to start
+    thread::sleep(Duration::from_millis(500));
+    let killed = Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(killed.success());
+
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(4));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.starts_with("search interrupted after "),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains(" nodes expanded"), "{}", stdout);
+}
@@ -0,0 +1,50 @@
+use std::{fs, path::Path, process::Command};
+
+/// Search the board solved by "2U 1L 1L 1U" after applying `prefix`
+fn search(name: &str, prefix: &str, format: &str) -> std::process::Output {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("prefix");
+    fs::create_dir_all(&dir).unwrap();
+    let board = dir.join("board.txt");
+    fs::write(&board, "2 3\n0 0 0\n0 2 1\n").unwrap();
+    let prefix_file = dir.join(format!("{}.txt", name));
+    fs::write(&prefix_file, prefix).unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_sliding-puzzle"))
+        .args(["search", "-a", "ida-star", "--quiet", "--format", format])
+        .arg("--input")
+        .arg(&board)
+        .arg("--prefix-moves")
+        .arg(&prefix_file)
+        .output()
+        .expect("Failed to run sliding-puzzle")
+}
+
+#[test]
+fn test_prefix_of_optimal_solution() {
+    let output = search("half", "2U 1L", "human");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.starts_with(
+            "A solution has 4 moves, the first 2 pre-applied and the rest optimal:\n2U 1L "
+        ),
+        "{}",
+        stdout
+    );
+
+    let output = search("half-plain", "2U 1L", "plain");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.split_whitespace().count(), 4, "{}", stdout);
+}
+
+#[test]
+fn test_illegal_prefix() {
+    let output = search("illegal", "2U 2U", "human");
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.starts_with("error: invalid moves in '") && stderr.contains("Cannot apply move 1"),
+        "{}",
+        stderr
+    );
+}
@@ -0,0 +1,46 @@
+use std::{fs, path::Path, process::Command};
+
+fn render(name: &str, moves: Option<&str>) -> (std::process::Output, String) {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("render");
+    fs::create_dir_all(&dir).unwrap();
+    let board = dir.join("board.txt");
+    fs::write(&board, "2 3\n0 0 0\n0 2 1\n").unwrap();
+    let svg = dir.join(format!("{}.svg", name));
+    let _ = fs::remove_file(&svg);
+
+    let mut command = Command::new(env!("CARGO_BIN_EXE_sliding-puzzle"));
+    command
+        .arg("render")
+        .arg("--input")
+        .arg(&board)
+        .arg("--output")
+        .arg(&svg);
+    if let Some(moves) = moves {
+        let moves_file = dir.join(format!("{}.txt", name));
+        fs::write(&moves_file, moves).unwrap();
+        command.arg("--moves").arg(&moves_file);
+    }
+    let output = command.output().expect("Failed to run sliding-puzzle");
+    (output, fs::read_to_string(&svg).unwrap_or_default())
+}
+
+#[test]
+fn test_render_board() {
+    let (output, svg) = render("board", None);
+    assert!(output.status.success());
+    assert!(svg.starts_with("<svg "), "{}", svg);
+    // 2 blocks and 4 holes
+    assert_eq!(svg.matches("<rect ").count(), 6);
+    assert!(!svg.contains("<g "));
+}
+
+#[test]
+fn test_render_solution() {
+    let (output, svg) = render("solution", Some("2U 1L 1L 1U"));
+    assert!(output.status.success());
+    assert_eq!(svg.matches("<g id=\"frame-").count(), 5);
+
+    let (output, svg) = render("illegal", Some("2L 2L 1L"));
+    assert_eq!(output.status.code(), Some(1));
+    assert!(svg.is_empty());
+}
@@ -0,0 +1,45 @@
+use std::{fs, path::Path, process::Command};
+
+fn replay(name: &str, moves: &str) -> std::process::Output {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("replay");
+    fs::create_dir_all(&dir).unwrap();
+    let board = dir.join("board.txt");
+    fs::write(&board, "2 3\n0 0 0\n0 2 1\n").unwrap();
+    let moves_file = dir.join(format!("{}.txt", name));
+    fs::write(&moves_file, moves).unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_sliding-puzzle"))
+        .arg("replay")
+        .arg("--input")
+        .arg(&board)
+        .arg("--moves")
+        .arg(&moves_file)
+        .args(["--delay-ms", "0", "--no-clear"])
+        .output()
+        .expect("Failed to run sliding-puzzle")
+}
+
+#[test]
+fn test_replay_reaches_goal() {
+    let output = replay("solution", "2U 1L 1L 1U");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains('\x1b'));
+
+    let frames = stdout.split_terminator("\n\n").collect::<Vec<_>>();
+    assert_eq!(frames.len(), 5);
+    assert_eq!(frames[0], "Start\n0 0 0\n0 2 1");
+    assert_eq!(frames[1], "Move 1/4: 2U\n0 2 0\n0 0 1");
+    // Blocks are packed at the top left in the goal
+    assert_eq!(frames[4], "Move 4/4: 1U\n1 2 0\n0 0 0");
+}
+
+#[test]
+fn test_replay_stops_at_illegal_move() {
+    let output = replay("illegal", "2L 2L 1L");
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.split_terminator("\n\n").count(), 2);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.starts_with("Invalid move 1: "), "{}", stderr);
+}
@@ -0,0 +1,104 @@
+use std::{
+    fs,
+    io::Write,
+    path::Path,
+    process::{Command, Output, Stdio},
+};
+
+fn sliding_puzzle() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_sliding-puzzle"))
+}
+
+/// Run `command` with `input` written to its stdin
+fn run_with_stdin(mut command: Command, input: &[u8]) -> Output {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to run sliding-puzzle");
+    child
+        .stdin
+        .take()
+        .expect("Stdin should be piped")
+        .write_all(input)
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn test_search_from_stdin() {
+    for args in [
+        &["search", "-a", "ida-star"][..],
+        &["search", "-a", "ida-star", "-i", "-"],
+    ] {
+        let mut command = sliding_puzzle();
+        command.args(args);
+        let output = run_with_stdin(command, b"2 3\n0 0 0\n0 2 1\n");
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout.contains("An optimal solution has 4 moves:\n"),
+            "{}",
+            stdout
+        );
+    }
+}
+
+#[test]
+fn test_invalid_stdin() {
+    // Stdin is closed immediately
+    let output = sliding_puzzle().args(["search"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "error: invalid board in stdin: input is empty\n");
+
+    let mut command = sliding_puzzle();
+    command.args(["search", "-i", "-"]);
+    let output = run_with_stdin(command, b"2 2\n1 x\n");
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.starts_with("error: invalid board in stdin: Invalid line 2: "),
+        "{}",
+        stderr
+    );
+
+    // The declared size is not allocated before rows are read
+    let mut command = sliding_puzzle();
+    command.args(["search", "-a", "ida-star"]);
+    let output = run_with_stdin(command, b"32767 32767\n1\n");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_generate_pipe_to_search() {
+    let generate = sliding_puzzle()
+        .args(["generate", "--size", "4,3", "-n", "5", "--seed", "3"])
+        .output()
+        .unwrap();
+    assert!(generate.status.success());
+
+    let mut command = sliding_puzzle();
+    command.args(["search", "-a", "ida-star"]);
+    let output = run_with_stdin(command, &generate.stdout);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("An optimal solution has "), "{}", stdout);
+}
+
+#[test]
+fn test_manual_moves_from_stdin() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("manual");
+    fs::create_dir_all(&dir).unwrap();
+    let board = dir.join("board.txt");
+    fs::write(&board, "2 3\n0 0 0\n0 2 1\n").unwrap();
+
+    let mut command = sliding_puzzle();
+    command.args(["search", "-a", "manual", "-i"]).arg(&board);
+    // The second move is illegal and not counted
+    let output = run_with_stdin(command, b"2U\n2U\n1L\n1L\n1U\n");
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Reach goal in 4 moves\n"), "{}", stderr);
+}
@@ -0,0 +1,57 @@
+use std::{fs, path::Path, process::Command};
+
+/// Verify `moves` against a 2x2 board whose only block is one row and one column
+/// away from its goal, return the exit code and stdout
+fn verify(name: &str, moves: &str) -> (Option<i32>, String) {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("verify");
+    fs::create_dir_all(&dir).unwrap();
+    let board = dir.join("board.txt");
+    fs::write(&board, "2 2\n0 0\n0 1\n").unwrap();
+    let moves_file = dir.join(format!("{}.txt", name));
+    fs::write(&moves_file, moves).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_sliding-puzzle"))
+        .arg("verify")
+        .arg("--input")
+        .arg(&board)
+        .arg("--moves")
+        .arg(&moves_file)
+        .output()
+        .expect("Failed to run sliding-puzzle");
+    (
+        output.status.code(),
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+    )
+}
+
+#[test]
+fn test_correct_solution() {
+    assert_eq!(
+        verify("bare", "1U 1L"),
+        (Some(0), "OK: goal reached in 2 moves\n".to_string())
+    );
+    // The full output of search is accepted as well
+    let search_output = "Total run time = 0.0001 seconds.\n\
+        An optimal solution has 2 moves:\n\
+        1L 1U \n";
+    assert_eq!(
+        verify("search", search_output),
+        (Some(0), "OK: goal reached in 2 moves\n".to_string())
+    );
+}
+
+#[test]
+fn test_illegal_move() {
+    let (code, stdout) = verify("illegal", "1U 1U 1L");
+    assert_eq!(code, Some(1));
+    assert!(stdout.starts_with("Invalid move 1: "), "{}", stdout);
+    assert!(stdout.trim_end().ends_with("(1U)"), "{}", stdout);
+}
+
+#[test]
+fn test_goal_not_reached() {
+    assert_eq!(
+        verify("incomplete", "1U"),
+        (Some(2), "goal not reached after 1 moves\n".to_string())
+    );
+}